@@ -0,0 +1,301 @@
+//! Module to check alert rules against the latest synced values after every sync.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    include::{
+        fmt, warn, AccountNumber, Deserialize, Duration, Local, NaiveDate, Result, Serialize,
+    },
+    storage::{BalanceKind, DBInfoAccountBalance, DBInfoAlert, Storage},
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+/// Which side of the value the metric has to be on for the alert to fire.
+pub enum AlertOp {
+    Above,
+    Below,
+}
+
+impl AlertOp {
+    fn check(&self, current: f64, value: f64) -> bool {
+        match self {
+            Self::Above => current > value,
+            Self::Below => current < value,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// What an alert watches. The balance metrics are for the whole account and the position
+/// metrics take the symbol. The PNL metrics are a % change, worked out the same way as the
+/// statusbar variables of the same name.
+pub enum AlertMetric {
+    Cash,
+    CashPNL,
+    MarketValue,
+    MarketValuePNL,
+    TotalEquity,
+    TotalEquityPNL,
+    CurrentPrice(String),
+    CurrentMarketValue(String),
+    DayPNL(String),
+    OpenPNL(String),
+}
+
+impl AlertMetric {
+    // reads the latest value of the metric for the day from the DB.
+    fn read(
+        &self,
+        db: &dyn Storage,
+        account: &str,
+        kind: BalanceKind,
+        date: NaiveDate,
+    ) -> Result<f64> {
+        let pnl = |latest: f64, sod: f64| (latest - sod) / sod * 100f64;
+        let balances = || -> Result<(DBInfoAccountBalance, DBInfoAccountBalance)> {
+            Ok((
+                db.get_start_of_day_balance(account, kind, date)?,
+                db.get_latest_balance(account, kind, date)?,
+            ))
+        };
+        let position = |symbol: &str| db.get_latest_position(account, symbol, date);
+        Ok(match self {
+            Self::Cash => balances()?.1.cash,
+            Self::CashPNL => {
+                let (sod, bal) = balances()?;
+                pnl(bal.cash, sod.cash)
+            }
+            Self::MarketValue => balances()?.1.market_value,
+            Self::MarketValuePNL => {
+                let (sod, bal) = balances()?;
+                pnl(bal.market_value, sod.market_value)
+            }
+            Self::TotalEquity => balances()?.1.total_equity,
+            Self::TotalEquityPNL => {
+                let (sod, bal) = balances()?;
+                pnl(bal.total_equity, sod.total_equity)
+            }
+            Self::CurrentPrice(symbol) => position(symbol)?.current_price,
+            Self::CurrentMarketValue(symbol) => position(symbol)?.current_market_value,
+            Self::DayPNL(symbol) => {
+                let pos = position(symbol)?;
+                pos.day_pnl / (pos.current_market_value - pos.day_pnl) * 100f64
+            }
+            Self::OpenPNL(symbol) => {
+                let pos = position(symbol)?;
+                pos.open_pnl / pos.total_cost * 100f64
+            }
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// A single rule from the config, the account can be the name or the number.
+pub struct Alert {
+    pub account: String,
+    pub metric: AlertMetric,
+    pub op: AlertOp,
+    pub value: f64,
+}
+
+/// The text of a rule is what the history is kept under.
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} {:?} {}",
+            self.account, self.metric, self.op, self.value
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// The alerts section of the config file.
+pub struct AlertsConfig {
+    pub rules: Vec<Alert>,
+    /// Seconds a rule has to wait after firing before it can fire again.
+    pub cooldown: u64,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            cooldown: 3600,
+        }
+    }
+}
+
+/// Keeps track of which rules are already over their line, so one crossing only fires once.
+pub struct AlertEngine {
+    rules: Vec<Alert>,
+    cooldown: Duration,
+    breached: Vec<bool>,
+}
+
+impl AlertEngine {
+    pub fn new(config: &AlertsConfig) -> Self {
+        Self {
+            rules: config.rules.clone(),
+            cooldown: Duration::seconds(config.cooldown as i64),
+            breached: vec![false; config.rules.len()],
+        }
+    }
+    /// Checks every rule against the values synced today. Rules that just crossed their line
+    /// are stored in the DB and handed back.
+    pub fn evaluate(&mut self, db: &dyn Storage, kind: BalanceKind) -> Vec<DBInfoAlert> {
+        let now = Local::now();
+        let mut fired = Vec::new();
+        for (rule, breached) in self.rules.iter().zip(self.breached.iter_mut()) {
+            let (number, current) = match read_rule(db, rule, kind, now.date().naive_local()) {
+                Ok(val) => val,
+                Err(e) => {
+                    warn!("Could not check alert [{}], with error: {}", rule, e);
+                    continue;
+                }
+            };
+            let was_breached = *breached;
+            // a start of day of zero gives an infinite PNL, which shouldn't set anything off.
+            *breached = current.is_finite() && rule.op.check(current, rule.value);
+            if !*breached || was_breached {
+                continue;
+            }
+            // we don't know which side of the line a rule was on before a restart, so the
+            // cooldown is checked against the history as well.
+            let key = rule.to_string();
+            match db.get_last_alert(&key) {
+                Ok(Some(last)) if now.naive_local() - last.datetime() < self.cooldown => continue,
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("Could not read alert history, with error: {}", e);
+                    continue;
+                }
+            }
+            if let Err(e) = db.insert_alert(now, &number, key.clone(), current) {
+                warn!("Could not save alert [{}], with error: {}", rule, e);
+                continue;
+            }
+            warn!("Alert fired! [{}] is at {:.2}.", rule, current);
+            fired.push(DBInfoAlert::new(
+                now.date().naive_local(),
+                now.time(),
+                number,
+                key,
+                current,
+            ));
+        }
+        fired
+    }
+}
+
+// works out the account number and the current value of a rule.
+fn read_rule(
+    db: &dyn Storage,
+    rule: &Alert,
+    kind: BalanceKind,
+    date: NaiveDate,
+) -> Result<(AccountNumber, f64)> {
+    let number = db.get_account_info(&rule.account)?.number;
+    Ok((number, rule.metric.read(db, &rule.account, kind, date)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::include::{from_str, Account, AccountBalance};
+    use crate::storage::{memory_db, DBRef};
+
+    fn account() -> Account {
+        from_str("(type: Margin, number: \"123\", status: Active, isPrimary: true, isBilling: true, clientAccountType: Individual)").unwrap()
+    }
+    fn equity(total_equity: f64) -> AccountBalance {
+        from_str(&format!("(currency: CAD, cash: 0, marketValue: 0, totalEquity: {}, buyingPower: 0, maintenanceExcess: 0, isRealTime: true)", total_equity)).unwrap()
+    }
+    fn engine(metric: AlertMetric, value: f64, cooldown: u64) -> AlertEngine {
+        AlertEngine::new(&AlertsConfig {
+            rules: vec![Alert {
+                account: "main".to_string(),
+                metric,
+                op: AlertOp::Above,
+                value,
+            }],
+            cooldown,
+        })
+    }
+    // a db with the account and a start of day, each sync after is a second later.
+    fn synced_db(start_of_day: f64) -> (DBRef, impl Fn(&DBRef, f64)) {
+        let db = memory_db();
+        db.insert_account("main".to_string(), account()).unwrap();
+        let sync = move |db: &DBRef, total_equity: f64| {
+            let count = db
+                .get_balance_days(
+                    "main",
+                    BalanceKind::CAD,
+                    Local::today().naive_local(),
+                    Local::today().naive_local(),
+                )
+                .map(|days| days[0].over_day_balances.len() as u32)
+                .unwrap_or_default();
+            db.insert_account_balance(
+                Local::today().and_hms(0, 0, count),
+                &"123".to_string(),
+                BalanceKind::CAD,
+                equity(total_equity),
+                equity(start_of_day),
+            )
+            .unwrap();
+        };
+        (db, sync)
+    }
+    fn fired(engine: &mut AlertEngine, db: &DBRef) -> usize {
+        engine.evaluate(&**db, BalanceKind::CAD).len()
+    }
+
+    #[test]
+    fn one_crossing_fires_once() {
+        let (db, sync) = synced_db(900.0);
+        let mut engine = engine(AlertMetric::TotalEquity, 1000.0, 0);
+        let mut counts = Vec::new();
+        // under, crosses, stays over, back under, crosses again.
+        for total_equity in [900.0, 1100.0, 1200.0, 900.0, 1100.0].iter() {
+            sync(&db, *total_equity);
+            counts.push(fired(&mut engine, &db));
+        }
+        assert_eq!(counts, vec![0, 1, 0, 0, 1]);
+        let today = Local::today().naive_local();
+        let history = db.get_alerts(today, today).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].value, 1100.0);
+    }
+
+    #[test]
+    fn cooldown_holds_across_a_restart() {
+        let (db, sync) = synced_db(900.0);
+        let mut engine = engine(AlertMetric::TotalEquity, 1000.0, 3600);
+        sync(&db, 1100.0);
+        assert_eq!(fired(&mut engine, &db), 1);
+        // back under and over again inside the hour.
+        sync(&db, 900.0);
+        assert_eq!(fired(&mut engine, &db), 0);
+        sync(&db, 1100.0);
+        assert_eq!(fired(&mut engine, &db), 0);
+        // a new engine doesn't know the rule was already over, the stored history does.
+        let mut restarted = self::engine(AlertMetric::TotalEquity, 1000.0, 3600);
+        assert_eq!(fired(&mut restarted, &db), 0);
+        let mut no_cooldown = self::engine(AlertMetric::TotalEquity, 1000.0, 0);
+        assert_eq!(fired(&mut no_cooldown, &db), 1);
+    }
+
+    #[test]
+    fn zero_start_of_day() {
+        let (db, sync) = synced_db(0.0);
+        let mut engine = engine(AlertMetric::TotalEquityPNL, 5.0, 0);
+        sync(&db, 100.0);
+        assert_eq!(fired(&mut engine, &db), 0);
+        sync(&db, 0.0);
+        assert_eq!(fired(&mut engine, &db), 0);
+        let today = Local::today().naive_local();
+        assert!(db.get_alerts(today, today).unwrap().is_empty());
+    }
+}
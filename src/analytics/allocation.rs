@@ -0,0 +1,315 @@
+//! Sub module to compare what an account or group holds against its target allocation.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::{
+        include::{Currency, Deserialize, HashMap, NaiveDate, PositionSymbol, Result, Serialize},
+        portfolio::{group_balance, group_positions, listing_currency, PortfolioConfig},
+        storage::{BalanceKind, Storage},
+    },
+    AnalyticsError,
+};
+
+/// How far the weights of a target can add up away from 1 before we call them wrong.
+const WEIGHT_TOLERANCE: f64 = 0.001;
+
+/// Helper functions
+// shares are only bought or sold whole, rounding toward zero so a buy never spends cash
+// that isn't there. The small nudge keeps 9.9999999 from turning into 9.
+fn whole_shares(quantity: f64) -> f64 {
+    (quantity + quantity.signum() * 1e-9).trunc()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// The weights an account or portfolio group should be held at.
+pub struct AllocationTarget {
+    /// An account name or number, or the name of a portfolio group.
+    pub identifier: String,
+    /// Weight of each symbol, 0.4 is 40%.
+    pub weights: HashMap<PositionSymbol, f64>,
+    /// Weight to keep in cash, everything together has to add up to 1.
+    #[serde(default)]
+    pub cash: f64,
+}
+
+impl AllocationTarget {
+    fn check_weights(&self) -> Result<()> {
+        let total = self.weights.values().fold(self.cash, |total, w| total + w);
+        if self.cash < 0.0
+            || self.weights.values().any(|w| *w < 0.0)
+            || (total - 1.0).abs() > WEIGHT_TOLERANCE
+        {
+            return Err(Box::new(AnalyticsError::TargetWeightsError(
+                self.identifier.clone(),
+                total,
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// How one symbol sits against its target, the money values are in the report currency.
+pub struct AllocationLine {
+    pub symbol: PositionSymbol,
+    pub target_weight: f64,
+    pub current_weight: f64,
+    /// Current weight less the target, positive means there is too much of it.
+    pub drift: f64,
+    pub open_quantity: f64,
+    /// Left out for a symbol that isn't held and has never been quoted.
+    pub current_price: Option<f64>,
+    pub current_value: f64,
+    pub target_value: f64,
+    /// Whole shares to buy (positive) or sell (negative), left out when there is no price.
+    pub rebalance_quantity: Option<f64>,
+    pub rebalance_value: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// How the cash sits against its target.
+pub struct CashAllocation {
+    pub target_weight: f64,
+    pub current_weight: f64,
+    pub drift: f64,
+    pub current_value: f64,
+    pub target_value: f64,
+    /// What would be left after every trade in the report went through.
+    pub after_rebalance: f64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The allocation of an account or group on a day, along with the trades to get back on target.
+pub struct AllocationReport {
+    pub identifier: String,
+    pub currency: Currency,
+    pub date: NaiveDate,
+    pub total_value: f64,
+    /// The biggest drift of any symbol or the cash, either way.
+    pub max_drift: f64,
+    pub cash: CashAllocation,
+    pub positions: Vec<AllocationLine>,
+}
+
+// what is held of one symbol, in the report currency.
+struct Holding {
+    symbol: PositionSymbol,
+    open_quantity: f64,
+    current_price: f64,
+    current_value: f64,
+}
+
+// the cash and positions of a group, already merged and converted by the portfolio module.
+fn group_holdings(
+    db: &dyn Storage,
+    config: &PortfolioConfig,
+    group_name: &str,
+    date: NaiveDate,
+) -> Result<(Currency, f64, Vec<Holding>)> {
+    let balance = group_balance(config, group_name, |a| {
+        db.get_latest_balance(a, BalanceKind::Combined, date)
+    })?;
+    let holdings = group_positions(db, config, group_name, date)?
+        .into_iter()
+        .map(|gp| Holding {
+            symbol: gp.symbol,
+            open_quantity: gp.open_quantity,
+            current_price: gp.current_price,
+            current_value: gp.current_market_value,
+        })
+        .collect();
+    Ok((balance.currency, balance.cash, holdings))
+}
+
+// the cash and positions of one account, converted from the currency each trades in.
+fn account_holdings(
+    db: &dyn Storage,
+    config: &PortfolioConfig,
+    acct_ident: &str,
+    convert: Option<&Currency>,
+    date: NaiveDate,
+) -> Result<(Currency, f64, Vec<Holding>)> {
+    // the positions are in every currency, so the cash has to be too.
+    let balance = db.get_latest_balance(acct_ident, BalanceKind::Combined, date)?;
+    let currency = convert.unwrap_or(&balance.currency).clone();
+    let cash = config.convert(balance.cash, &balance.currency, &currency);
+    let mut holdings: Vec<Holding> = Vec::new();
+    // an account holding only cash never has positions synced.
+    for symbol in db.get_position_symbols(acct_ident).unwrap_or_default() {
+        // symbols that were sold off before the date have nothing to add.
+        let position = match db.get_latest_position(acct_ident, &symbol, date) {
+            Ok(p) => p,
+            Err(_) => continue,
+        };
+        let listing = listing_currency(db, &symbol);
+        holdings.push(Holding {
+            symbol,
+            open_quantity: position.open_quantity,
+            current_price: config.convert(position.current_price, &listing, &currency),
+            current_value: config.convert(position.current_market_value, &listing, &currency),
+        });
+    }
+    Ok((currency, cash, holdings))
+}
+
+/// Compares the latest positions and cash of an account or group against the target, and works
+/// out the whole shares to buy or sell to get back to it. Nothing is ever traded.
+pub fn compute_allocation(
+    db: &dyn Storage,
+    config: &PortfolioConfig,
+    target: &AllocationTarget,
+    convert: Option<&Currency>,
+    date: NaiveDate,
+) -> Result<AllocationReport> {
+    target.check_weights()?;
+    // a group name wins over an account, same as the raw group paths.
+    let (currency, cash, holdings) = if config.groups.iter().any(|g| g.name() == target.identifier)
+    {
+        group_holdings(db, config, &target.identifier, date)?
+    } else {
+        account_holdings(db, config, &target.identifier, convert, date)?
+    };
+    let total_value = holdings
+        .iter()
+        .fold(cash, |total, h| total + h.current_value);
+    if total_value <= 0.0 {
+        return Err(Box::new(AnalyticsError::NothingToAllocateError(
+            target.identifier.clone(),
+        )));
+    }
+    // every symbol with a target or a position gets a line, sold off ones have nothing to say.
+    let mut symbols: Vec<PositionSymbol> = target.weights.keys().cloned().collect();
+    symbols.extend(
+        holdings
+            .iter()
+            .filter(|h| h.open_quantity != 0.0)
+            .map(|h| h.symbol.clone()),
+    );
+    symbols.sort();
+    symbols.dedup();
+    let mut positions: Vec<AllocationLine> = Vec::new();
+    for symbol in symbols.into_iter() {
+        let target_weight = target.weights.get(&symbol).copied().unwrap_or_default();
+        let held = holdings.iter().find(|h| h.symbol == symbol);
+        let (open_quantity, current_value) =
+            held.map_or((0.0, 0.0), |h| (h.open_quantity, h.current_value));
+        // a symbol we want but don't hold yet is priced from its last quote.
+        let current_price = match held {
+            Some(h) => Some(h.current_price),
+            None => db.get_latest_quote(&symbol, date).ok().map(|q| {
+                config.convert(
+                    q.last_trade_price,
+                    &listing_currency(db, &symbol),
+                    &currency,
+                )
+            }),
+        }
+        .filter(|p| *p > 0.0);
+        let target_value = total_value * target_weight;
+        let rebalance_quantity = current_price.map(|price| {
+            if target_weight == 0.0 {
+                -open_quantity
+            } else {
+                whole_shares((target_value - current_value) / price).max(-open_quantity)
+            }
+        });
+        let current_weight = current_value / total_value;
+        positions.push(AllocationLine {
+            symbol,
+            target_weight,
+            current_weight,
+            drift: current_weight - target_weight,
+            open_quantity,
+            current_price,
+            current_value,
+            target_value,
+            rebalance_quantity,
+            rebalance_value: rebalance_quantity.and_then(|q| current_price.map(|p| q * p)),
+        });
+    }
+    let traded = positions.iter().fold(0.0, |total, l| {
+        total + l.rebalance_value.unwrap_or_default()
+    });
+    let current_weight = cash / total_value;
+    let cash = CashAllocation {
+        target_weight: target.cash,
+        current_weight,
+        drift: current_weight - target.cash,
+        current_value: cash,
+        target_value: total_value * target.cash,
+        after_rebalance: cash - traded,
+    };
+    let max_drift = positions
+        .iter()
+        .fold(cash.drift.abs(), |max, l| max.max(l.drift.abs()));
+    Ok(AllocationReport {
+        identifier: target.identifier.clone(),
+        currency,
+        date,
+        total_value,
+        max_drift,
+        cash,
+        positions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::include::{
+        from_str, Account, AccountBalance, AccountPosition, Local, NaiveDate, TimeZone,
+    };
+    use crate::storage::{memory_db, PositionSource};
+
+    fn account() -> Account {
+        from_str("(type: Margin, number: \"123\", status: Active, isPrimary: true, isBilling: true, clientAccountType: Individual)").unwrap()
+    }
+    fn cash(currency: &str, cash: f64) -> AccountBalance {
+        from_str(&format!("(currency: {}, cash: {}, marketValue: 0, totalEquity: {}, buyingPower: 0, maintenanceExcess: 0, isRealTime: true)", currency, cash, cash)).unwrap()
+    }
+    fn position(symbol: &str, open_quantity: f64, price: f64) -> AccountPosition {
+        from_str(&format!("(symbol: \"{}\", symbolId: 7, openQuantity: {}, closedQuantity: 0, currentMarketValue: {}, currentPrice: {}, dayPnl: Some(0), averageEntryPrice: 25, closedPnl: 0, openPnl: 0, totalCost: 0, isRealTime: true, isUnderReorg: false)", symbol, open_quantity, open_quantity * price, price)).unwrap()
+    }
+
+    #[test]
+    fn usd_cash_counts_toward_the_total() {
+        let db = memory_db();
+        let number = "123".to_string();
+        let at = Local.ymd(2026, 10, 16).and_hms(12, 0, 0);
+        db.insert_account("main".to_string(), account()).unwrap();
+        // 100 CAD and 100 USD, 235 CAD together.
+        for (kind, balance) in [
+            (BalanceKind::CAD, cash("CAD", 100.0)),
+            (BalanceKind::USD, cash("USD", 100.0)),
+            (BalanceKind::Combined, cash("CAD", 235.0)),
+        ]
+        .iter()
+        {
+            db.insert_account_balance(at, &number, *kind, balance.clone(), balance.clone())
+                .unwrap();
+        }
+        db.insert_account_position(
+            at,
+            &number,
+            position("XEQT.TO", 10.0, 30.0),
+            PositionSource::Live,
+        )
+        .unwrap();
+        let target: AllocationTarget =
+            from_str("(identifier: \"main\", weights: { \"XEQT.TO\": 0.5 }, cash: 0.5)").unwrap();
+        let config = PortfolioConfig::default();
+        let date = NaiveDate::from_ymd(2026, 10, 16);
+        let report = compute_allocation(&*db, &config, &target, None, date).unwrap();
+        assert_eq!(report.currency, Currency::CAD);
+        assert!((report.total_value - 535.0).abs() < 1e-9);
+        assert!((report.cash.current_value - 235.0).abs() < 1e-9);
+        assert!((report.cash.drift - (235.0 / 535.0 - 0.5)).abs() < 1e-9);
+        // 32.5 over target sells one whole share.
+        assert_eq!(report.positions[0].rebalance_quantity, Some(-1.0));
+        assert!((report.cash.after_rebalance - 265.0).abs() < 1e-9);
+        // reported in USD the cash is the same 235 CAD at the config rate.
+        let usd = compute_allocation(&*db, &config, &target, Some(&Currency::USD), date).unwrap();
+        assert!((usd.cash.current_value - 235.0 / config.usd_cad_rate).abs() < 1e-9);
+    }
+}
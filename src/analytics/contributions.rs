@@ -0,0 +1,269 @@
+//! Sub module to track how much of the contribution room of each registered account is used.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::{
+        fx::FxConverter,
+        include::{
+            AccountNumber, AccountType, Currency, Datelike, Deserialize, NaiveDate, Result,
+            Serialize,
+        },
+        storage::{ActivityType, Storage},
+        tax::is_registered,
+    },
+    AnalyticsConfig, AnalyticsError,
+};
+
+/// Helper functions
+// deposits and withdrawals are all that count, transfers in kind are left to the user.
+fn is_contribution(activity_type: ActivityType) -> bool {
+    matches!(
+        activity_type,
+        ActivityType::Deposits | ActivityType::Withdrawals
+    )
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// The room an account has to contribute in one calendar year.
+pub struct ContributionRoom {
+    /// An account name or number.
+    pub identifier: String,
+    pub year: i32,
+    /// Room at the start of the year in CAD, your notice of assessment or CRA My Account has it.
+    pub room: f64,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+/// Where the contributions of a year sit against its room.
+pub enum ContributionStatus {
+    NoRoomSet,
+    UnderLimit,
+    NearLimit,
+    OverLimit,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// Everything deposited into and withdrawn from one registered account over the year, in CAD.
+pub struct AccountContributions {
+    pub name: String,
+    pub number: AccountNumber,
+    pub account_type: AccountType,
+    pub deposits: f64,
+    /// Always positive. Withdrawals never give room back in the same year.
+    pub withdrawals: f64,
+    pub net: f64,
+    pub room: Option<f64>,
+    /// Room less the deposits, negative means too much went in.
+    pub remaining: Option<f64>,
+    pub status: ContributionStatus,
+}
+
+impl AccountContributions {
+    /// What to tell the user when the account is close to or over its room, if anything.
+    pub fn warning(&self, year: i32) -> Option<String> {
+        match (self.status, self.remaining) {
+            (ContributionStatus::OverLimit, Some(r)) => Some(format!(
+                "{} ({:?}) is over its {} contribution room by {:.2} CAD.",
+                self.name, self.account_type, year, -r
+            )),
+            (ContributionStatus::NearLimit, Some(r)) => Some(format!(
+                "{} ({:?}) only has {:.2} CAD of {} contribution room left.",
+                self.name, self.account_type, r, year
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The contributions of every registered account in a year, with a warning for each one close to
+/// or over its room.
+pub struct ContributionReport {
+    pub year: i32,
+    pub currency: Currency,
+    pub accounts: Vec<AccountContributions>,
+    pub warnings: Vec<String>,
+}
+
+// adds up the deposits and withdrawals of an account between two dates.
+fn contribution_totals(
+    db: &dyn Storage,
+    acct_num: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    cad: &FxConverter,
+) -> (f64, f64) {
+    // an account that was never funded has no activities to read.
+    db.get_activities(acct_num, from, to, None)
+        .unwrap_or_default()
+        .iter()
+        .filter(|a| is_contribution(a.activity_type))
+        .fold((0.0, 0.0), |(deposits, withdrawals), a| {
+            let amount = cad.convert(
+                a.net_amount.abs(),
+                &a.currency,
+                a.transaction_date.and_hms(23, 59, 59),
+            );
+            match a.activity_type {
+                ActivityType::Deposits => (deposits + amount, withdrawals),
+                _ => (deposits, withdrawals + amount),
+            }
+        })
+}
+
+/// Sums the deposits and withdrawals of every registered account over a calendar year and
+/// compares them against the room in the config. Only the current year gets warnings, the years
+/// before can't be fixed anymore.
+pub fn compute_contributions(
+    db: &dyn Storage,
+    config: &AnalyticsConfig,
+    usd_cad_rate: f64,
+    year: i32,
+    today: NaiveDate,
+) -> Result<ContributionReport> {
+    let from = NaiveDate::from_ymd(year, 1, 1);
+    let to = NaiveDate::from_ymd(year, 12, 31).min(today).max(from);
+    // room is in CAD, so USD deposits are converted at the rate of the day.
+    let cad = FxConverter::load_or_fixed(db, from, to, Currency::CAD, usd_cad_rate);
+    let mut accounts: Vec<AccountContributions> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+    for name in db.get_account_list()? {
+        let account = db.get_account_info(&name)?;
+        if !is_registered(&account.account_type) {
+            continue;
+        }
+        let (deposits, withdrawals) = contribution_totals(db, &account.number, from, to, &cad);
+        let room = config.get_room(db, &account.number, year);
+        let remaining = room.map(|r| r - deposits);
+        let status = match remaining {
+            None => ContributionStatus::NoRoomSet,
+            Some(r) if r < 0.0 => ContributionStatus::OverLimit,
+            Some(r) if r <= config.contribution_warning => ContributionStatus::NearLimit,
+            Some(_) => ContributionStatus::UnderLimit,
+        };
+        let contributions = AccountContributions {
+            name,
+            number: account.number,
+            account_type: account.account_type,
+            deposits,
+            withdrawals,
+            net: deposits - withdrawals,
+            room,
+            remaining,
+            status,
+        };
+        if year == today.year() {
+            warnings.extend(contributions.warning(year));
+        }
+        accounts.push(contributions);
+    }
+    if accounts.is_empty() {
+        return Err(Box::new(AnalyticsError::NoRegisteredAccountsError));
+    }
+    accounts.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(ContributionReport {
+        year,
+        currency: Currency::CAD,
+        accounts,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::include::{from_str, Account, AccountActivity, Local, TimeZone};
+    use crate::storage::memory_db;
+
+    fn tfsa(number: &str) -> Account {
+        from_str(&format!("(type: TFSA, number: \"{}\", status: Active, isPrimary: false, isBilling: false, clientAccountType: Individual)", number)).unwrap()
+    }
+    fn flow(activity_type: &str, currency: &str, net_amount: f64) -> AccountActivity {
+        from_str(&format!("(tradeDate: \"2026-03-02T05:00:00Z\", transactionDate: \"2026-03-02T05:00:00Z\", settlementDate: \"2026-03-02T05:00:00Z\", action: \"\", symbol: \"\", symbolId: 0, description: \"\", currency: \"{}\", quantity: 0, price: 0, grossAmount: 0, commission: 0, netAmount: {}, type: \"{}\")", currency, net_amount, activity_type)).unwrap()
+    }
+    fn room(identifier: &str, room: f64) -> ContributionRoom {
+        ContributionRoom {
+            identifier: identifier.to_string(),
+            year: 2026,
+            room,
+        }
+    }
+
+    #[test]
+    fn status_against_the_room() {
+        let db = memory_db();
+        let today = NaiveDate::from_ymd(2026, 6, 1);
+        let funded = vec![
+            ("under", vec![flow("Deposits", "CAD", 1000.0)]),
+            ("edge", vec![flow("Deposits", "CAD", 6000.0)]),
+            // a withdrawal doesn't give the room back in the same year.
+            (
+                "over",
+                vec![
+                    flow("Deposits", "CAD", 7500.0),
+                    flow("Withdrawals", "CAD", -1000.0),
+                ],
+            ),
+            ("unset", vec![flow("Deposits", "CAD", 1000.0)]),
+        ];
+        for (i, (name, activities)) in funded.into_iter().enumerate() {
+            let number = format!("{}", i + 1);
+            db.insert_account(name.to_string(), tfsa(&number)).unwrap();
+            db.insert_account_activities(&number, activities).unwrap();
+        }
+        let config = AnalyticsConfig {
+            contribution_room: vec![
+                room("under", 7000.0),
+                room("edge", 7000.0),
+                room("over", 7000.0),
+            ],
+            ..AnalyticsConfig::default()
+        };
+        let report = compute_contributions(&*db, &config, 1.3, 2026, today).unwrap();
+        let statuses: Vec<(&str, ContributionStatus)> = report
+            .accounts
+            .iter()
+            .map(|a| (a.name.as_str(), a.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("edge", ContributionStatus::NearLimit),
+                ("over", ContributionStatus::OverLimit),
+                ("under", ContributionStatus::UnderLimit),
+                ("unset", ContributionStatus::NoRoomSet),
+            ]
+        );
+        let over = &report.accounts[1];
+        assert_eq!(over.remaining, Some(-500.0));
+        assert_eq!(over.net, 6500.0);
+        assert_eq!(report.warnings.len(), 2);
+        // the years before only get the totals.
+        let last_year = NaiveDate::from_ymd(2027, 1, 15);
+        let report = compute_contributions(&*db, &config, 1.3, 2026, last_year).unwrap();
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn usd_deposits_count_in_cad() {
+        let db = memory_db();
+        let today = NaiveDate::from_ymd(2026, 6, 1);
+        db.insert_account("tfsa".to_string(), tfsa("1")).unwrap();
+        db.insert_account_activities(&"1".to_string(), vec![flow("Deposits", "USD", 100.0)])
+            .unwrap();
+        let config = AnalyticsConfig {
+            contribution_room: vec![room("tfsa", 7000.0)],
+            ..AnalyticsConfig::default()
+        };
+        // without any synced rates the rate from the config is used.
+        let report = compute_contributions(&*db, &config, 1.3, 2026, today).unwrap();
+        assert!((report.accounts[0].deposits - 130.0).abs() < 1e-9);
+        // the rate of the day wins once there is one.
+        db.insert_fx_rate(Local.ymd(2026, 3, 2).and_hms(12, 0, 0), 1.25)
+            .unwrap();
+        let report = compute_contributions(&*db, &config, 1.3, 2026, today).unwrap();
+        assert!((report.accounts[0].deposits - 125.0).abs() < 1e-9);
+        assert_eq!(report.accounts[0].remaining, Some(6875.0));
+    }
+}
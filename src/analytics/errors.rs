@@ -0,0 +1,65 @@
+//! Module containing the errors that can come up while working out analytics.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{error, fmt, NaiveDate};
+
+#[derive(Debug)]
+// Enum representing errors that are possible while crunching the stored history.
+pub enum AnalyticsError {
+    NotEnoughHistoryError(NaiveDate, NaiveDate),
+    ZeroStartingValueError(NaiveDate),
+    NoRateOfReturnError,
+    NotEnoughDaysError(usize),
+    TargetNotFoundError(String),
+    TargetWeightsError(String, f64),
+    NothingToAllocateError(String),
+    NoRegisteredAccountsError,
+}
+
+impl error::Error for AnalyticsError {}
+
+impl fmt::Display for AnalyticsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEnoughHistoryError(from, to) => write!(
+                f,
+                "Could not compute analytics! No balances synced between {} and {}.",
+                from, to
+            ),
+            Self::ZeroStartingValueError(date) => write!(
+                f,
+                "Could not compute analytics! Account had no value to start {}.",
+                date
+            ),
+            Self::NoRateOfReturnError => write!(
+                f,
+                "Could not compute analytics! No rate of return matches the cash flows."
+            ),
+            Self::NotEnoughDaysError(days) => write!(
+                f,
+                "Could not compute analytics! Need at least 2 days of balances, only found {}.",
+                days
+            ),
+            Self::TargetNotFoundError(ident) => write!(
+                f,
+                "Could not compute analytics! No target allocation set for {}.",
+                ident
+            ),
+            Self::TargetWeightsError(ident, total) => write!(
+                f,
+                "Could not compute analytics! Target weights of {} add up to {} instead of 1.",
+                ident, total
+            ),
+            Self::NothingToAllocateError(ident) => write!(
+                f,
+                "Could not compute analytics! {} has no value to allocate.",
+                ident
+            ),
+            Self::NoRegisteredAccountsError => write!(
+                f,
+                "Could not compute analytics! No registered accounts are synced."
+            ),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Module to work out portfolio analytics from the history we have stored.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    fx::FxConverter,
+    include::{warn, Currency, Deserialize, NaiveDate, Result, Serialize},
+    storage::{ActivityType, BalanceKind, DBInfoAccountBalanceDay, Storage},
+};
+
+/// Sub modules
+mod allocation;
+mod contributions;
+mod errors;
+mod returns;
+mod risk;
+
+/// Re-export sub-modules so we can read from them in other modules.
+pub use allocation::*;
+pub use contributions::*;
+pub use errors::*;
+pub use returns::*;
+pub use risk::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// The analytics section of the config file.
+pub struct AnalyticsConfig {
+    /// Annual risk-free rate used by the Sharpe and Sortino ratios, 0.04 is 4%.
+    pub risk_free_rate: f64,
+    /// Target allocations of accounts and portfolio groups, used by the allocation api.
+    pub targets: Vec<AllocationTarget>,
+    /// Contribution room of the registered accounts, one entry per account and year.
+    pub contribution_room: Vec<ContributionRoom>,
+    /// Warn once the room left for this year drops to this much, in CAD.
+    pub contribution_warning: f64,
+}
+
+impl Default for AnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            risk_free_rate: 0.0,
+            targets: Vec::new(),
+            contribution_room: Vec::new(),
+            contribution_warning: 1000.0,
+        }
+    }
+}
+
+impl AnalyticsConfig {
+    /// Finds the target of an account or group. Accounts can be asked for by name or number no
+    /// matter which one the target was set with.
+    pub fn get_target(&self, db: &dyn Storage, identifier: &str) -> Result<&AllocationTarget> {
+        let number = db.get_account_info(identifier).ok().map(|a| a.number);
+        let same_account = |ident: &str| {
+            number.is_some() && db.get_account_info(ident).ok().map(|a| a.number) == number
+        };
+        match self
+            .targets
+            .iter()
+            .find(|t| t.identifier == identifier || same_account(&t.identifier))
+        {
+            Some(t) => Ok(t),
+            None => Err(Box::new(AnalyticsError::TargetNotFoundError(
+                identifier.to_string(),
+            ))),
+        }
+    }
+    /// Finds the room of an account for a year, set with either its name or number.
+    pub fn get_room(&self, db: &dyn Storage, acct_num: &str, year: i32) -> Option<f64> {
+        self.contribution_room
+            .iter()
+            .filter(|r| r.year == year)
+            .find(|r| {
+                db.get_account_info(&r.identifier).ok().map(|a| a.number)
+                    == Some(acct_num.to_string())
+            })
+            .map(|r| r.room)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+/// The value of an account over one day, along with the money that moved in or out.
+pub struct DailyValue {
+    pub date: NaiveDate,
+    pub start_value: f64,
+    pub end_value: f64,
+    pub net_flow: f64,
+}
+
+impl DailyValue {
+    /// Return for the day using Modified Dietz with the flows landing mid-day, so a deposit
+    /// doesn't show up as a gain.
+    pub fn daily_return(&self) -> Result<f64> {
+        let base = self.start_value + 0.5 * self.net_flow;
+        if base == 0.0 {
+            return Err(Box::new(AnalyticsError::ZeroStartingValueError(self.date)));
+        }
+        Ok((self.end_value - self.start_value - self.net_flow) / base)
+    }
+}
+
+/// Change from one value to another in percent. Starting from zero shows as no change, since a
+/// quote can come back with no open price and NaN would break the Json.
+pub fn percent_change(from: f64, to: f64) -> f64 {
+    if from == 0.0 {
+        0.0
+    } else {
+        (to - from) / from * 100.0
+    }
+}
+
+/// Helper functions
+// These are the activities that move money in or out of an account. The rest (dividends, fees,
+// interest, trades) are part of how the account performed. A conversion only moves money
+// between the currencies of the account, so it is a flow for one currency but not the whole.
+fn is_external_flow(activity_type: ActivityType, per_currency: bool) -> bool {
+    match activity_type {
+        ActivityType::Deposits | ActivityType::Withdrawals | ActivityType::Transfers => true,
+        ActivityType::FXConversion => per_currency,
+        _ => false,
+    }
+}
+
+/// All the external cash flows between two dates, summed up by day. With a converter the flows
+/// of every currency are converted into its currency, otherwise only flows in the currency
+/// provided are counted.
+pub fn cash_flows(
+    db: &dyn Storage,
+    acct_ident: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+    currency: &Currency,
+    fx: Option<&FxConverter>,
+) -> Vec<(NaiveDate, f64)> {
+    let activities = match db.get_activities(acct_ident, from, to, None) {
+        Ok(acts) => acts,
+        // no activities synced means no flows to account for.
+        Err(e) => {
+            warn!("Treating cash flows as empty, with error: {}", e);
+            return Vec::new();
+        }
+    };
+    let mut flows: Vec<(NaiveDate, f64)> = Vec::new();
+    // per currency balances never see flows in the other currency.
+    for mut act in activities
+        .into_iter()
+        .filter(|a| is_external_flow(a.activity_type, fx.is_none()))
+        .filter(|a| fx.is_some() || a.currency == *currency)
+    {
+        if let Some(fx) = fx {
+            fx.activity(&mut act);
+        }
+        match flows.last_mut() {
+            Some((date, amount)) if *date == act.transaction_date => *amount += act.net_amount,
+            _ => flows.push((act.transaction_date, act.net_amount)),
+        }
+    }
+    flows
+}
+
+/// Builds the day by day value of an account between two dates. The first day starts at its
+/// sod balance and every day after starts where the last synced day ended, so flows on days
+/// we have no balances for still land on the next day we do.
+pub fn daily_values(
+    db: &dyn Storage,
+    acct_ident: &str,
+    kind: BalanceKind,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<(Currency, Vec<DailyValue>)> {
+    let days: Vec<DBInfoAccountBalanceDay> = db.get_balance_days(acct_ident, kind, from, to)?;
+    let first = match days.first() {
+        Some(day) => day,
+        None => return Err(Box::new(AnalyticsError::NotEnoughHistoryError(from, to))),
+    };
+    let currency = first.get_start_of_day().currency.clone();
+    // the combined balance is the whole account, so flows in every currency land in it.
+    let fx = match kind {
+        BalanceKind::Combined => Some(FxConverter::load(db, first.date, to, currency.clone())?),
+        _ => None,
+    };
+    let flows = cash_flows(db, acct_ident, first.date, to, &currency, fx.as_ref());
+    let mut result: Vec<DailyValue> = Vec::new();
+    for day in days.iter() {
+        let (prev_date, start_value) = match result.last() {
+            Some(prev) => (Some(prev.date), prev.end_value),
+            None => (None, day.get_start_of_day().total_equity),
+        };
+        let net_flow = flows
+            .iter()
+            .filter(|(date, _)| prev_date.map_or(*date == day.date, |pd| *date > pd))
+            .filter(|(date, _)| *date <= day.date)
+            .fold(0.0, |total, (_, amount)| total + amount);
+        result.push(DailyValue {
+            date: day.date,
+            start_value,
+            end_value: day.get_most_recent().total_equity,
+            net_flow,
+        });
+    }
+    Ok((currency, result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::include::{from_str, Account, AccountActivity, AccountBalance, Local, TimeZone};
+    use crate::storage::memory_db;
+
+    fn account() -> Account {
+        from_str("(type: Margin, number: \"123\", status: Active, isPrimary: true, isBilling: true, clientAccountType: Individual)").unwrap()
+    }
+    fn equity(total_equity: f64) -> AccountBalance {
+        from_str(&format!("(currency: CAD, cash: 0, marketValue: 0, totalEquity: {}, buyingPower: 0, maintenanceExcess: 0, isRealTime: true)", total_equity)).unwrap()
+    }
+    fn flow(activity_type: &str, currency: &str, net_amount: f64) -> AccountActivity {
+        from_str(&format!("(tradeDate: \"2026-03-02T05:00:00Z\", transactionDate: \"2026-03-02T05:00:00Z\", settlementDate: \"2026-03-02T05:00:00Z\", action: \"\", symbol: \"\", symbolId: 0, description: \"\", currency: \"{}\", quantity: 0, price: 0, grossAmount: 0, commission: 0, netAmount: {}, type: \"{}\")", currency, net_amount, activity_type)).unwrap()
+    }
+
+    #[test]
+    fn combined_flows_convert_deposits_and_skip_conversions() {
+        let db = memory_db();
+        let day = NaiveDate::from_ymd(2026, 3, 2);
+        let number = "123".to_string();
+        let at = Local.ymd(2026, 3, 2).and_hms(12, 0, 0);
+        db.insert_account("main".to_string(), account()).unwrap();
+        db.insert_fx_rate(at, 1.25).unwrap();
+        db.insert_account_balance(
+            at,
+            &number,
+            BalanceKind::Combined,
+            equity(1231.25),
+            equity(1000.0),
+        )
+        .unwrap();
+        // 100 USD deposited, then converted into CAD.
+        db.insert_account_activities(
+            &number,
+            vec![
+                flow("Deposits", "USD", 100.0),
+                flow("FX conversion", "USD", -100.0),
+                flow("FX conversion", "CAD", 125.0),
+            ],
+        )
+        .unwrap();
+        let (currency, values) =
+            daily_values(&*db, "main", BalanceKind::Combined, day, day).unwrap();
+        assert_eq!(currency, Currency::CAD);
+        assert!((values[0].net_flow - 125.0).abs() < 1e-9);
+        // 106.25 gained on 1000 plus half of the 125 deposited.
+        assert!((values[0].daily_return().unwrap() - 0.1).abs() < 1e-9);
+        // a single currency still sees the conversion as money coming in.
+        let cad = cash_flows(&*db, "main", day, day, &Currency::CAD, None);
+        assert_eq!(cad, vec![(day, 125.0)]);
+    }
+}
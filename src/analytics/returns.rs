@@ -0,0 +1,163 @@
+//! Sub module to work out time-weighted and money-weighted returns.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::{
+        include::{Currency, NaiveDate, Result, Serialize},
+        storage::{BalanceKind, Storage},
+    },
+    daily_values, AnalyticsError, DailyValue,
+};
+
+/// How close to zero the net present value has to get before we call the rate found.
+const XIRR_TOLERANCE: f64 = 1e-9;
+/// Most times to split the range while looking for the rate.
+const XIRR_MAX_ITERATIONS: u32 = 200;
+/// Biggest annual rate we bother searching up to, 1,000,000%.
+const XIRR_MAX_RATE: f64 = 10_000.0;
+
+/// Helper functions
+// net present value of the cash flows, t is in years from the start.
+fn npv(rate: f64, flows: &[(f64, f64)]) -> f64 {
+    flows
+        .iter()
+        .map(|(t, amount)| amount / (1.0 + rate).powf(*t))
+        .sum()
+}
+// Solves for the annual rate that makes the npv zero. The npv only ever goes down as the rate
+// goes up for a normal set of flows, so bisection always gets there if there is an answer.
+fn xirr(flows: &[(f64, f64)]) -> Result<f64> {
+    let (mut low, mut high) = (-0.999_999, 1.0);
+    while npv(low, flows) * npv(high, flows) > 0.0 {
+        if high >= XIRR_MAX_RATE {
+            return Err(Box::new(AnalyticsError::NoRateOfReturnError));
+        }
+        high *= 10.0;
+    }
+    for _ in 0..XIRR_MAX_ITERATIONS {
+        let mid = (low + high) / 2.0;
+        let value = npv(mid, flows);
+        if value.abs() < XIRR_TOLERANCE {
+            return Ok(mid);
+        }
+        if value * npv(low, flows) > 0.0 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok((low + high) / 2.0)
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+/// The two ways we know to measure returns.
+pub enum ReturnMethod {
+    /// Time-weighted, takes the timing and size of deposits out of the picture.
+    TWR,
+    /// Money-weighted, the internal rate of return of the money that was put in.
+    MWR,
+}
+
+impl ReturnMethod {
+    pub fn parse(method: &str) -> Option<Self> {
+        match method.to_lowercase().as_str() {
+            "twr" => Some(Self::TWR),
+            "mwr" => Some(Self::MWR),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// One day of the return series.
+pub struct DailyReturn {
+    #[serde(flatten)]
+    pub value: DailyValue,
+    pub daily_return: f64,
+    pub cumulative_return: f64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The returns of an account over a range of days.
+pub struct ReturnsReport {
+    pub method: ReturnMethod,
+    pub currency: Currency,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub start_value: f64,
+    pub end_value: f64,
+    pub net_flows: f64,
+    pub cumulative_return: f64,
+    /// Left out for ranges shorter than a year, scaling those up is misleading.
+    pub annualized_return: Option<f64>,
+    pub daily: Vec<DailyReturn>,
+}
+
+/// Chains the daily values together into the time-weighted return series.
+pub fn daily_returns(values: Vec<DailyValue>) -> Result<Vec<DailyReturn>> {
+    let mut result: Vec<DailyReturn> = Vec::new();
+    let mut growth = 1.0;
+    for value in values.into_iter() {
+        let daily_return = value.daily_return()?;
+        growth *= 1.0 + daily_return;
+        result.push(DailyReturn {
+            value,
+            daily_return,
+            cumulative_return: growth - 1.0,
+        });
+    }
+    Ok(result)
+}
+
+/// Works out the returns of an account between two dates, only days that have synced
+/// balances are counted.
+pub fn compute_returns(
+    db: &dyn Storage,
+    acct_ident: &str,
+    kind: BalanceKind,
+    from: NaiveDate,
+    to: NaiveDate,
+    method: ReturnMethod,
+) -> Result<ReturnsReport> {
+    let (currency, values) = daily_values(db, acct_ident, kind, from, to)?;
+    // the daily series is chained together the same way no matter the method.
+    let daily = daily_returns(values)?;
+    // daily_values never gives back an empty list so we can unwrap these.
+    let first = &daily.first().unwrap().value;
+    let last = &daily.last().unwrap().value;
+    // the range runs from the start of the first day to the end of the last.
+    let years = ((last.date - first.date).num_days() + 1) as f64 / 365.0;
+    let growth = 1.0 + daily.last().unwrap().cumulative_return;
+    let (cumulative_return, annual_rate) = match method {
+        ReturnMethod::TWR => (growth - 1.0, growth.powf(1.0 / years) - 1.0),
+        ReturnMethod::MWR => {
+            // money going in is negative from the investor's side, what's left at the end
+            // comes back out. Flows land mid-day like they do in the daily returns.
+            let mut flows = vec![(0.0, -first.start_value)];
+            flows.extend(daily.iter().filter(|d| d.value.net_flow != 0.0).map(|d| {
+                let t = ((d.value.date - first.date).num_days() as f64 + 0.5) / 365.0;
+                (t, -d.value.net_flow)
+            }));
+            flows.push((years, last.end_value));
+            let rate = xirr(&flows)?;
+            ((1.0 + rate).powf(years) - 1.0, rate)
+        }
+    };
+    Ok(ReturnsReport {
+        method,
+        currency,
+        from: first.date,
+        to: last.date,
+        start_value: first.start_value,
+        end_value: last.end_value,
+        net_flows: daily.iter().fold(0.0, |total, d| total + d.value.net_flow),
+        cumulative_return,
+        annualized_return: if years >= 1.0 {
+            Some(annual_rate)
+        } else {
+            None
+        },
+        daily,
+    })
+}
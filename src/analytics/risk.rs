@@ -0,0 +1,149 @@
+//! Sub module to work out the risk metrics of an account.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::{
+        include::{Currency, NaiveDate, Result, Serialize},
+        storage::{BalanceKind, Storage},
+    },
+    daily_returns, daily_values, AnalyticsError, DailyReturn,
+};
+
+/// Used to scale daily numbers up to a year.
+const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The return of a single day.
+pub struct DayReturn {
+    pub date: NaiveDate,
+    pub daily_return: f64,
+}
+
+impl DayReturn {
+    fn new(daily: &DailyReturn) -> Self {
+        Self {
+            date: daily.value.date,
+            daily_return: daily.daily_return,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The biggest drop from a high to a low, as a fraction of the high.
+pub struct Drawdown {
+    pub depth: f64,
+    pub peak_date: NaiveDate,
+    pub trough_date: NaiveDate,
+    /// First day back at the old high, if it has happened yet.
+    pub recovery_date: Option<NaiveDate>,
+}
+
+impl Drawdown {
+    // walks the time-weighted growth of the account so deposits don't hide a drop.
+    fn find(daily: &[DailyReturn]) -> Self {
+        // the peak starts as the value going into the first day.
+        let first_date = daily[0].value.date;
+        let mut peak = (first_date, 1.0);
+        let mut result = Self {
+            depth: 0.0,
+            peak_date: first_date,
+            trough_date: first_date,
+            recovery_date: None,
+        };
+        for day in daily.iter() {
+            let growth = 1.0 + day.cumulative_return;
+            if growth >= peak.1 {
+                // we are back to the high so the last big drop has recovered.
+                if result.depth > 0.0 && result.recovery_date.is_none() {
+                    result.recovery_date = Some(day.value.date);
+                }
+                peak = (day.value.date, growth);
+            } else {
+                let depth = 1.0 - growth / peak.1;
+                if depth > result.depth {
+                    result = Self {
+                        depth,
+                        peak_date: peak.0,
+                        trough_date: day.value.date,
+                        recovery_date: None,
+                    };
+                }
+            }
+        }
+        result
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The risk metrics of an account over a range of days.
+pub struct RiskReport {
+    pub currency: Currency,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub days: usize,
+    pub risk_free_rate: f64,
+    /// Standard deviation of the daily returns, scaled to a year.
+    pub volatility: f64,
+    pub max_drawdown: Drawdown,
+    /// Left out when there is no volatility to divide by.
+    pub sharpe_ratio: Option<f64>,
+    /// Left out when there were no days below the risk-free rate.
+    pub sortino_ratio: Option<f64>,
+    pub best_day: DayReturn,
+    pub worst_day: DayReturn,
+}
+
+/// Works out the risk metrics of an account between two dates from the flow adjusted daily
+/// returns, so a deposit doesn't count as a good day.
+pub fn compute_risk(
+    db: &dyn Storage,
+    acct_ident: &str,
+    kind: BalanceKind,
+    from: NaiveDate,
+    to: NaiveDate,
+    risk_free_rate: f64,
+) -> Result<RiskReport> {
+    let (currency, values) = daily_values(db, acct_ident, kind, from, to)?;
+    let daily = daily_returns(values)?;
+    if daily.len() < 2 {
+        return Err(Box::new(AnalyticsError::NotEnoughDaysError(daily.len())));
+    }
+    let days = daily.len() as f64;
+    let returns: Vec<f64> = daily.iter().map(|d| d.daily_return).collect();
+    let mean = returns.iter().sum::<f64>() / days;
+    let std_dev = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (days - 1.0)).sqrt();
+    let daily_risk_free = (1.0 + risk_free_rate).powf(1.0 / TRADING_DAYS_PER_YEAR) - 1.0;
+    let downside_dev = (returns
+        .iter()
+        .map(|r| (r - daily_risk_free).min(0.0).powi(2))
+        .sum::<f64>()
+        / days)
+        .sqrt();
+    let annualize = TRADING_DAYS_PER_YEAR.sqrt();
+    let ratio = |dev: f64| {
+        if dev > 0.0 {
+            Some((mean - daily_risk_free) / dev * annualize)
+        } else {
+            None
+        }
+    };
+    let by_return = |a: &&DailyReturn, b: &&DailyReturn| {
+        a.daily_return
+            .partial_cmp(&b.daily_return)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    };
+    Ok(RiskReport {
+        currency,
+        from: daily.first().unwrap().value.date,
+        to: daily.last().unwrap().value.date,
+        days: daily.len(),
+        risk_free_rate,
+        volatility: std_dev * annualize,
+        max_drawdown: Drawdown::find(&daily),
+        sharpe_ratio: ratio(std_dev),
+        sortino_ratio: ratio(downside_dev),
+        best_day: DayReturn::new(daily.iter().max_by(by_return).unwrap()),
+        worst_day: DayReturn::new(daily.iter().min_by(by_return).unwrap()),
+    })
+}
@@ -0,0 +1,86 @@
+//! Sub module with the raw requests for Questrade's candle endpoint, questrade_rs has no wrapper.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::{
+        include::{
+            AuthenticationInfo, BoxFuture, Client, DateTime, Deserialize, FixedOffset, FutureExt,
+            NaiveDate, NaiveTime, Result,
+        },
+        scheduler::eastern_datetime,
+        symbols::{api_url, search_symbol_id},
+    },
+    BackfillError, CandleSource,
+};
+
+#[derive(Deserialize)]
+struct CandlesResponse {
+    candles: Vec<Candle>,
+}
+
+#[derive(Deserialize)]
+struct Candle {
+    start: DateTime<FixedOffset>,
+    close: f64,
+}
+
+/// Pulls daily candles using the access token of the monitor.
+pub struct QuestradeCandles {
+    client: Client,
+    auth: AuthenticationInfo,
+}
+
+impl QuestradeCandles {
+    pub fn new(auth: AuthenticationInfo) -> Self {
+        Self {
+            client: Client::new(),
+            auth,
+        }
+    }
+}
+
+impl CandleSource for QuestradeCandles {
+    fn daily_closes<'a>(
+        &'a self,
+        symbol: &'a str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> BoxFuture<'a, Result<Vec<(NaiveDate, f64)>>> {
+        async move {
+            let id = match search_symbol_id(&self.client, &self.auth, symbol).await? {
+                Some(id) => id,
+                None => return Err(BackfillError::SymbolNotFoundError(symbol.to_string()).into()),
+            };
+            // the candles are asked for in Eastern time, so every day lines up with the exchange.
+            let time = |date: NaiveDate, h: u32, m: u32, s: u32| {
+                eastern_datetime(date, NaiveTime::from_hms(h, m, s)).to_rfc3339()
+            };
+            let response = self
+                .client
+                .get(&api_url(&self.auth, &format!("markets/candles/{}", id)))
+                .bearer_auth(&self.auth.access_token)
+                .query(&[
+                    ("startTime", time(from, 0, 0, 0)),
+                    ("endTime", time(to, 23, 59, 59)),
+                    ("interval", "OneDay".to_string()),
+                ])
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(Box::new(BackfillError::CandleStatusError(
+                    symbol.to_string(),
+                    response.status().as_u16(),
+                )) as Box<dyn std::error::Error>);
+            }
+            Ok(response
+                .json::<CandlesResponse>()
+                .await?
+                .candles
+                .into_iter()
+                .map(|c| (c.start.naive_local().date(), c.close))
+                .collect())
+        }
+        .boxed()
+    }
+}
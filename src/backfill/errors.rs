@@ -0,0 +1,33 @@
+//! Module containing the errors that can come up while backfilling positions.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{error, fmt};
+
+#[derive(Debug)]
+// Enum representing errors that are possible while pulling candles from Questrade.
+pub enum BackfillError {
+    NotAuthenticatedError,
+    SymbolNotFoundError(String),
+    CandleStatusError(String, u16),
+}
+
+impl error::Error for BackfillError {}
+
+impl fmt::Display for BackfillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAuthenticatedError => {
+                write!(f, "No Questrade authentication to request candles with.")
+            }
+            Self::SymbolNotFoundError(symbol) => {
+                write!(f, "Could not find a Questrade symbol id for {}.", symbol)
+            }
+            Self::CandleStatusError(symbol, status) => write!(
+                f,
+                "Questrade answered the candle request for {} with status {}.",
+                symbol, status
+            ),
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Module to fill in the positions of days the monitor wasn't running from daily candles.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    include::{
+        warn, AccountNumber, AccountPosition, BoxFuture, Datelike, Deserialize, Duration,
+        NaiveDate, PositionSymbol, Result, Serialize, Weekday,
+    },
+    storage::{
+        api_number, close_snapshot_time, ActivityType, DBInfoAccountActivity,
+        DBInfoAccountPosition, PositionSource, Storage,
+    },
+};
+
+/// Sub modules
+mod candles;
+mod errors;
+
+/// Re-export sub-modules so we can read from them in other modules.
+pub use candles::*;
+pub use errors::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// The backfill section of the config file.
+pub struct BackfillConfig {
+    pub enabled: bool,
+    /// How many days back to look for gaps every time the monitor starts.
+    pub lookback_days: i64,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lookback_days: 90,
+        }
+    }
+}
+
+/// Anything that can give us the closing price of a symbol for each trading day in a range.
+pub trait CandleSource: Send + Sync {
+    /// Closes between two dates inclusive, days the market was shut are just left out.
+    fn daily_closes<'a>(
+        &'a self,
+        symbol: &'a str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> BoxFuture<'a, Result<Vec<(NaiveDate, f64)>>>;
+}
+
+#[derive(Debug, Clone)]
+/// A stretch of weekdays with no snapshots of a position that was held on both sides of it.
+pub struct PositionGap {
+    pub account: AccountNumber,
+    pub symbol: PositionSymbol,
+    /// Last day with a snapshot before the gap.
+    pub from: NaiveDate,
+    /// First day with a snapshot after the gap.
+    pub to: NaiveDate,
+    /// The last snapshot of the from day.
+    pub before: DBInfoAccountPosition,
+}
+
+impl PositionGap {
+    /// The weekdays between the two synced days, holidays are only known by having no candle.
+    pub fn missing_days(&self) -> Vec<NaiveDate> {
+        let mut days = Vec::new();
+        let mut day = self.from + Duration::days(1);
+        while day < self.to {
+            if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+                days.push(day);
+            }
+            day += Duration::days(1);
+        }
+        days
+    }
+}
+
+/// Helper functions
+// questrade sends sells with a negative quantity, but we don't rely on it.
+fn trade_quantity(trade: &DBInfoAccountActivity) -> f64 {
+    match trade.action.to_lowercase().as_str() {
+        "buy" => trade.quantity.abs(),
+        "sell" => -trade.quantity.abs(),
+        _ => trade.quantity,
+    }
+}
+
+/// Finds the gaps in the positions of an account between two dates.
+pub fn find_gaps(
+    db: &dyn Storage,
+    account: &str,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<PositionGap>> {
+    let mut gaps = Vec::new();
+    // an account that never held anything has no symbols, which is fine.
+    for symbol in db.get_position_symbols(account).unwrap_or_default() {
+        let days = db
+            .get_position_days(account, &symbol, from, to)
+            .unwrap_or_default();
+        for pair in days.windows(2) {
+            let ((first, positions), (next, _)) = (&pair[0], &pair[1]);
+            let before = match positions.last() {
+                Some(p) if p.open_quantity > 0.0 => p.clone(),
+                _ => continue,
+            };
+            let gap = PositionGap {
+                account: account.to_string(),
+                symbol: symbol.clone(),
+                from: *first,
+                to: *next,
+                before,
+            };
+            if !gap.missing_days().is_empty() {
+                gaps.push(gap);
+            }
+        }
+    }
+    Ok(gaps)
+}
+
+/// Rebuilds a position for every missing day of a gap that has a close. The quantity and cost
+/// carry over from the snapshot before the gap, moved by any trades made during it.
+pub fn rebuild_gap(
+    gap: &PositionGap,
+    closes: &[(NaiveDate, f64)],
+    trades: &[DBInfoAccountActivity],
+) -> Vec<(NaiveDate, AccountPosition)> {
+    let missing = gap.missing_days();
+    let mut closes = closes.to_vec();
+    closes.sort_by_key(|(date, _)| *date);
+    let mut trades: Vec<&DBInfoAccountActivity> = trades
+        .iter()
+        .filter(|t| t.symbol.eq_ignore_ascii_case(&gap.symbol) && t.trade_date > gap.from)
+        .collect();
+    trades.sort_by_key(|t| t.trade_date);
+    let mut trades = trades.into_iter().peekable();
+    let mut quantity = gap.before.open_quantity;
+    let mut total_cost = gap.before.total_cost;
+    let mut previous_close = gap.before.current_price;
+    let mut result = Vec::new();
+    for (date, close) in closes {
+        if date <= gap.from {
+            previous_close = close;
+            continue;
+        }
+        if !missing.contains(&date) {
+            continue;
+        }
+        while let Some(trade) = trades.peek() {
+            if trade.trade_date > date {
+                break;
+            }
+            let traded = trade_quantity(trade);
+            if traded > 0.0 {
+                total_cost += trade.net_amount.abs();
+            } else if quantity > 0.0 {
+                total_cost *= ((quantity + traded) / quantity).max(0.0);
+            }
+            quantity += traded;
+            trades.next();
+        }
+        // sold off during the gap, so there is nothing left to fill in.
+        if quantity <= 0.0 {
+            break;
+        }
+        let market_value = quantity * close;
+        result.push((
+            date,
+            AccountPosition {
+                symbol: gap.symbol.clone(),
+                symbol_id: 0,
+                open_quantity: api_number(quantity),
+                closed_quantity: api_number(gap.before.closed_quantity),
+                current_market_value: api_number(market_value),
+                current_price: api_number(close),
+                day_profit_and_loss: api_number((close - previous_close) * quantity),
+                average_entry_price: api_number(total_cost / quantity),
+                closed_profit_and_loss: api_number(gap.before.closed_pnl),
+                open_profit_and_loss: api_number(market_value - total_cost),
+                total_cost: api_number(total_cost),
+                is_real_time: false,
+                is_under_reorg: false,
+            },
+        ));
+        previous_close = close;
+    }
+    result
+}
+
+/// Looks for gaps in the last lookback_days of every account and fills them in from candles.
+/// A symbol the candles can't be found for is skipped with a warning. Returns how many
+/// positions were stored.
+pub async fn backfill_positions(
+    db: &dyn Storage,
+    source: &dyn CandleSource,
+    config: &BackfillConfig,
+    today: NaiveDate,
+) -> Result<usize> {
+    let from = today - Duration::days(config.lookback_days);
+    let mut stored = 0;
+    for account in db.list_account_numbers()? {
+        for gap in find_gaps(db, &account, from, today)? {
+            let closes = match source.daily_closes(&gap.symbol, gap.from, gap.to).await {
+                Ok(closes) => closes,
+                Err(e) => {
+                    warn!(
+                        "Could not get candles for {}, with error: {}",
+                        gap.symbol, e
+                    );
+                    continue;
+                }
+            };
+            let trades = db
+                .get_activities(&account, gap.from, gap.to, Some(ActivityType::Trades))
+                .unwrap_or_default();
+            for (date, position) in rebuild_gap(&gap, &closes, &trades) {
+                db.insert_account_position(
+                    close_snapshot_time(date),
+                    &gap.account,
+                    position,
+                    PositionSource::Backfilled,
+                )?;
+                stored += 1;
+            }
+        }
+    }
+    Ok(stored)
+}
@@ -9,6 +9,8 @@ pub static DEFAULT_CONFIG: &str = "ConfigFile(\n\
     \t// Where you want to store the database. If it's relative it will be measured relative to \n\
     \t// the directory of the config file.\n\
     \tdb_file_path: \"db.ron\",\n\
+    \t// The encoding to store the database in. Options are Ron, Yaml, Bincode, or Sqlite.\n\
+    \tdb_backend: Ron,\n\
     \t// The directory to store logs in, one active and up to 5 zipped archives. If it's relative\n\
     \t// it will be measured relative to the directory of the config file.\n\
     \tlog_file_dir: \"logs\",\n\
@@ -38,8 +40,93 @@ pub static DEFAULT_CONFIG: &str = "ConfigFile(\n\
     \t// Status(AccountStatus) => Where AccountStatus is one of: Active, SuspendedClosed, \n\
     \t// SuspendedViewOnly, Liquidate, or Closed.\n\
     \taccounts_to_sync: [AccountToSync(\"Primary\", [IsPrimary(true)])],\n\
-    \t// Where the currency can be CAD or USD.\n\
+    \t// Where the currency can be CAD or USD. Combined balances are stored in this currency and\n\
+    \t// the balance apis default to the balance in it.\n\
     \taccount_balance_currency: CAD,\n\
-    \t// The delay in seconds you want between Api syncs.\n\
+    \t// The timeout in seconds for a single round of Api syncs.\n\
     \tdelay: 300,\n\
+    \t// When to sync, based on the trading sessions of the exchanges listed (TSX and/or NYSE).\n\
+    \t// Each session gets a PollCadence that is either Off or Every($seconds). A sync always\n\
+    \t// happens anchor_delay seconds after the regular open and close, even if the session is Off,\n\
+    \t// and right after midnight if it isn't. Turning the quiet sessions Off means nothing is stored\n\
+    \t// for today until the next pre-market, so the statusbar and the like error out until then.\n\
+    \t// holiday_file is an optional path to a RON list of Holiday(date: \"YYYY-MM-DD\", name: \"\",\n\
+    \t// exchanges: [TSX, NYSE]) objects, relative paths work the same as the ones above.\n\
+    \tschedule: (\n\
+    \t\texchanges: [TSX, NYSE],\n\
+    \t\tpre_market: Every(900),\n\
+    \t\tregular: Every(300),\n\
+    \t\tafter_hours: Every(900),\n\
+    \t\tclosed: Every(3600),\n\
+    \t\tweekend: Every(3600),\n\
+    \t\tholiday: Every(3600),\n\
+    \t\tholiday_file: None,\n\
+    \t\tanchor_delay: 60,\n\
+    \t),\n\
+    \t// The annual risk-free rate used by the Sharpe and Sortino ratios, 0.04 is 4%.\n\
+    \t// targets are the weights to hold an account or group at, each is\n\
+    \t// (identifier: \"TFSA\", weights: { \"XEQT.TO\": 0.4, \"VFV.TO\": 0.3 }, cash: 0.3).\n\
+    \t// contribution_room is the room of a registered account for a year in CAD, each is\n\
+    \t// (identifier: \"TFSA\", year: 2026, room: 7000.0), and contribution_warning is how\n\
+    \t// close to the room (in CAD) the deposits of this year get before a warning is logged.\n\
+    \tanalytics: (\n\
+    \t\trisk_free_rate: 0.0,\n\
+    \t\ttargets: [],\n\
+    \t\tcontribution_room: [],\n\
+    \t\tcontribution_warning: 1000.0,\n\
+    \t),\n\
+    \t// Groups of accounts to view as one, each is PortfolioGroup(\"Name\", [\"AccountToSync name\"], CAD).\n\
+    \t// usd_cad_rate is how many CAD one USD buys, used to convert into the group currency\n\
+    \t// until a rate has been recorded by a sync.\n\
+    \tportfolio: (\n\
+    \t\tgroups: [],\n\
+    \t\tusd_cad_rate: 1.35,\n\
+    \t),\n\
+    \t// Where the USD/CAD rate recorded at each sync comes from. Balances works it out from the\n\
+    \t// combined balances, Fixed($rate) always records the rate given.\n\
+    \tfx: (\n\
+    \t\tsource: Balances,\n\
+    \t),\n\
+    \t// Rules checked after every sync, e.g. Alert(account: \"Primary\", metric: TotalEquityPNL,\n\
+    \t// op: Below, value: -2.0) or Alert(account: \"Primary\", metric: DayPNL(\"AAPL\"), op: Above,\n\
+    \t// value: 5.0). A rule fires once per crossing, and not again for cooldown seconds.\n\
+    \talerts: (\n\
+    \t\trules: [],\n\
+    \t\tcooldown: 3600,\n\
+    \t),\n\
+    \t// Where notifications go, e.g. Webhook(url: \"https://example.com/hook\"),\n\
+    \t// Smtp(host: \"localhost\", from: \"qtmon@localhost\", to: [\"me@localhost\"]), or\n\
+    \t// Command(program: \"notify-send\", args: [\"%title\", \"%message\"]). Failed sends are\n\
+    \t// retried and then written to the dead letter file.\n\
+    \tnotify: (\n\
+    \t\tsinks: [],\n\
+    \t\tretries: 3,\n\
+    \t\tretry_delay: 10,\n\
+    \t\tdead_letter_file: \"dead_letter.log\",\n\
+    \t),\n\
+    \t// End of day reports, made after the sync at the close. The command gets the report on\n\
+    \t// stdin, e.g. Some(\"mail -s report me@localhost\").\n\
+    \treports: (\n\
+    \t\tenabled: true,\n\
+    \t\tdirectory: \"reports\",\n\
+    \t\tformats: [Markdown, Html, Text],\n\
+    \t\ttop_movers: 5,\n\
+    \t\tcommand: None,\n\
+    \t\tcommand_format: Text,\n\
+    \t),\n\
+    \t// Positions for days the monitor wasn't running are rebuilt from daily candles when it\n\
+    \t// starts, looking this many days back.\n\
+    \tbackfill: (\n\
+    \t\tenabled: true,\n\
+    \t\tlookback_days: 90,\n\
+    \t),\n\
+    \t// Symbols to fetch quotes of at every sync without holding them, like \"XEQT.TO\".\n\
+    \twatchlist: (\n\
+    \t\tsymbols: [],\n\
+    \t),\n\
+    \t// The info of every symbol held or watched is looked up again after this many days.\n\
+    \tsymbols: (\n\
+    \t\tenabled: true,\n\
+    \t\trefresh_days: 7,\n\
+    \t),\n\
 )";
@@ -2,12 +2,28 @@
 //! By: Curtis Jones <mail@curtisjones.ca>
 //! Started on: November 8, 2020
 
-use super::include::{
-    clap_app, config_dir, default_format, error, from_str, io, read_to_string, to_string, Account,
-    AccountNumber, AccountStatus, AccountType, AdaptiveFormat, AuthenticationInfo, Cleanup,
-    ClientAccountType, ColoredHelp, Criterion, Currency, DateTime, Deserialize, DirBuilder,
-    Duplicate, Duration, Instant, Ipv4Addr, LevelFilter, Local, LogSpecBuilder, Logger, Naming,
-    OpenOptions, Path, PathBuf, ReconfigurationHandle, Result, Serialize, Write,
+use super::{
+    alerts::AlertsConfig,
+    analytics::AnalyticsConfig,
+    backfill::BackfillConfig,
+    export::ExportRequest,
+    fx::FxConfig,
+    import::ImportRequest,
+    include::{
+        clap_app, config_dir, default_format, error, from_str, io, read_to_string, to_string, warn,
+        Account, AccountNumber, AccountStatus, AccountType, AdaptiveFormat, AuthenticationInfo,
+        Cleanup, ClientAccountType, ColoredHelp, Criterion, Currency, DateTime, Deserialize,
+        DirBuilder, Duplicate, Duration, Instant, Ipv4Addr, LevelFilter, Local, LogSpecBuilder,
+        Logger, Naming, OpenOptions, Path, PathBuf, ReconfigurationHandle, Result, Serialize,
+        Write,
+    },
+    notify::NotifyConfig,
+    portfolio::PortfolioConfig,
+    reports::ReportsConfig,
+    scheduler::ScheduleConfig,
+    storage::DBBackend,
+    symbols::SymbolsConfig,
+    watchlist::WatchlistConfig,
 };
 
 mod default;
@@ -36,6 +52,9 @@ fn validate_pathbuf(to_validate: PathBuf, config_path_arg: &PathBuf) -> PathBuf
 pub struct Config {
     pub settings: ConfigFile,
     pub auth: AuthInfo,
+    pub migrate_from: Option<PathBuf>,
+    pub export: Option<ExportRequest>,
+    pub import: Option<ImportRequest>,
     _logger: ReconfigurationHandle,
 }
 
@@ -49,9 +68,53 @@ impl Config {
         (author: "Curtis Jones <mail@curtisjones.ca>")
         (about: "program to monitor questrade account using official api")
         (@arg CONFIG:  -c --config        +takes_value "Sets a custom config file.")
-        (@arg REFRESH: -r --refreshtoken  +takes_value "Override the inital loading of authentication with a RefreshToken. Recommended for first run."))
+        (@arg REFRESH: -r --refreshtoken  +takes_value "Override the inital loading of authentication with a RefreshToken. Recommended for first run.")
+        (@arg MIGRATE: -m --migrate       +takes_value "Import an old Ron, Yaml, or Bincode DB file into the DB set in the config, then exit.")
+        (@subcommand run =>
+            (about: "Sync the accounts and serve the api, the same as giving no subcommand."))
+        (@subcommand export =>
+            (about: "Write the stored history to stdout or a file, the DB is only read so the monitor can keep running.")
+            (@arg KIND:     +required possible_value[balances positions accounts gains] "What to export, gains are the capital gains of the non-registered accounts.")
+            (@arg ACCOUNT:  -a --account  +takes_value "Only export this account, by name or number.")
+            (@arg FROM:     --from        +takes_value "First day to export as YYYY-MM-DD, defaults to today.")
+            (@arg TO:       --to          +takes_value "Last day to export as YYYY-MM-DD, defaults to today.")
+            (@arg CURRENCY: --currency    +takes_value "Which balances to export, CAD, USD, or combined. Defaults to the account_balance_currency.")
+            (@arg FORMAT:   -f --format   +takes_value possible_value[csv json ndjson] default_value[csv] "Format to write the rows in.")
+            (@arg OUTPUT:   -o --output   +takes_value "File to write to instead of stdout."))
+        (@subcommand import =>
+            (about: "Back-fill accounts, activities, positions, and balances from Questrade CSV exports, then exit.")
+            (@arg FILES:    +required +multiple "Activity or position CSV files to import.")
+            (@arg ACCOUNT:  -a --account  +takes_value "Account for rows without an account number, by name or number.")
+            (@arg NAME:     -n --name     +takes_value "Name to give the --account if it isn't stored yet.")
+            (@arg DATE:     --date        +takes_value "Day the positions in a position export were held as YYYY-MM-DD.")
+            (@arg DRY_RUN:  --dryrun      "Print what would be imported without writing anything.")))
         .setting(ColoredHelp)
         .get_matches();
+        // pull out the export arguments now so a bad one fails before anything is set up.
+        let export = match args.subcommand_matches("export") {
+            Some(ex) => Some(ExportRequest::new(
+                ex.value_of("KIND").unwrap_or_default(),
+                ex.value_of("ACCOUNT"),
+                ex.value_of("FROM"),
+                ex.value_of("TO"),
+                ex.value_of("CURRENCY"),
+                ex.value_of("FORMAT").unwrap_or_default(),
+                ex.value_of("OUTPUT"),
+            )?),
+            None => None,
+        };
+        let import = match args.subcommand_matches("import") {
+            Some(im) => Some(ImportRequest::new(
+                im.values_of("FILES")
+                    .map(|f| f.collect())
+                    .unwrap_or_default(),
+                im.value_of("ACCOUNT"),
+                im.value_of("NAME"),
+                im.value_of("DATE"),
+                im.is_present("DRY_RUN"),
+            )?),
+            None => None,
+        };
         // check for X_DEFAULT_DIR.
         let default_config_path = match config_dir() {
             Some(mut cd) => {
@@ -116,7 +179,12 @@ impl Config {
         // check the file path and allow for relative paths if using default config dir
         settings.log_file_dir = validate_pathbuf(settings.log_file_dir, &config_path_arg);
         let (file_log_level, _) = settings.file_log_level.to_usable();
-        let (_, stdout_log_level) = settings.stdout_log_level.to_usable();
+        // an export can be written to stdout and an import prints its report there, so the logs
+        // are kept out of both.
+        let stdout_log_level = match (&export, &import) {
+            (None, None) => settings.stdout_log_level.to_usable().1,
+            _ => Duplicate::None,
+        };
         let mut builder = LogSpecBuilder::new();
         builder.default(file_log_level);
         let _logger = Logger::with(builder.build())
@@ -155,11 +223,44 @@ impl Config {
 
         // validate the db file path and add the default path if using it
         settings.db_file_path = validate_pathbuf(settings.db_file_path, &config_path_arg);
+        // same thing for the holiday file if there is one.
+        settings.schedule.holiday_file = settings
+            .schedule
+            .holiday_file
+            .map(|hf| validate_pathbuf(hf, &config_path_arg));
+        // and for the file that failed notifications get written to.
+        settings.notify.dead_letter_file =
+            validate_pathbuf(settings.notify.dead_letter_file, &config_path_arg);
+        // and for the directory the daily reports are saved in.
+        settings.reports.directory = validate_pathbuf(settings.reports.directory, &config_path_arg);
+
+        // groups can only be built from accounts we actually sync.
+        for group in settings.portfolio.groups.iter() {
+            for account in group.accounts().iter() {
+                if !settings
+                    .accounts_to_sync
+                    .iter()
+                    .any(|ats| ats.name() == *account)
+                {
+                    warn!(
+                        "Portfolio group {} lists account {} that is not in accounts_to_sync.",
+                        group.name(),
+                        account
+                    );
+                }
+            }
+        }
+
+        // if we were asked to migrate an old DB file keep track of where it is.
+        let migrate_from = args.value_of("MIGRATE").map(PathBuf::from);
 
         // return our generated config.
         Ok(Config {
             settings,
             auth,
+            migrate_from,
+            export,
+            import,
             _logger,
         })
     }
@@ -178,6 +279,8 @@ impl Config {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConfigFile {
     pub db_file_path: PathBuf,
+    #[serde(default)]
+    pub db_backend: DBBackend,
     pub auth_file_path: PathBuf,
     pub log_file_dir: PathBuf,
     pub file_log_level: LogLevel,
@@ -187,6 +290,26 @@ pub struct ConfigFile {
     pub accounts_to_sync: Vec<AccountToSync>,
     pub account_balance_currency: Currency,
     pub delay: u64,
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+    #[serde(default)]
+    pub analytics: AnalyticsConfig,
+    #[serde(default)]
+    pub portfolio: PortfolioConfig,
+    #[serde(default)]
+    pub fx: FxConfig,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    #[serde(default)]
+    pub reports: ReportsConfig,
+    #[serde(default)]
+    pub backfill: BackfillConfig,
+    #[serde(default)]
+    pub watchlist: WatchlistConfig,
+    #[serde(default)]
+    pub symbols: SymbolsConfig,
 }
 
 impl ConfigFile {
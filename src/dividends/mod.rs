@@ -0,0 +1,409 @@
+//! Module to track the dividends paid into accounts and project what is coming next.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    fx::FxConverter,
+    include::{
+        AccountNumber, Currency, Datelike, Duration, NaiveDate, PositionSymbol, Result, Serialize,
+    },
+    portfolio::PortfolioConfig,
+    storage::{ActivityType, BalanceKind, Storage},
+};
+
+/// How far back the payments used for the projection go, and how far forward it looks.
+const PROJECTION_DAYS: i64 = 365;
+/// How far back we look for the last day an account was synced.
+const LAST_SYNC_DAYS: i64 = 7;
+
+/// Helper functions
+fn month_of(date: NaiveDate) -> String {
+    format!("{}-{:02}", date.year(), date.month())
+}
+// adds an amount to the total of a key, keeping the keys in the order they first show up.
+fn add_to<K: PartialEq>(totals: &mut Vec<(K, f64, usize)>, key: K, amount: f64) {
+    match totals.iter_mut().find(|(k, _, _)| *k == key) {
+        Some((_, total, count)) => {
+            *total += amount;
+            *count += 1;
+        }
+        None => totals.push((key, amount, 1)),
+    }
+}
+// the same day a year on, Feb 29 becomes Feb 28.
+fn next_year(date: NaiveDate) -> NaiveDate {
+    date.with_year(date.year() + 1)
+        .unwrap_or_else(|| date + Duration::days(PROJECTION_DAYS))
+}
+// the last day on or before the date that anything of the account was stored.
+fn last_synced(db: &dyn Storage, account: &str, date: NaiveDate) -> Option<NaiveDate> {
+    let from = date - Duration::days(LAST_SYNC_DAYS);
+    let balances = db
+        .get_balance_days(account, BalanceKind::Combined, from, date)
+        .ok()
+        .and_then(|days| days.last().map(|d| d.date));
+    // imported positions come without balances.
+    let positions = db
+        .get_position_symbols(account)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|s| {
+            db.get_position_days(account, s, from, date)
+                .ok()?
+                .last()
+                .map(|d| d.0)
+        })
+        .max();
+    balances.max(positions)
+}
+// shares held at the last sync on or before the date. Nothing is synced on quiet days, and a
+// symbol missing from that sync was sold so we don't look any further back for it.
+fn held_on(db: &dyn Storage, account: &str, symbol: &str, date: NaiveDate) -> Option<f64> {
+    let synced = last_synced(db, account, date)?;
+    db.get_latest_position(account, symbol, synced)
+        .ok()
+        .map(|p| p.open_quantity)
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// One dividend paid into an account, the amount is what landed after any withholding tax.
+pub struct DividendPayment {
+    pub date: NaiveDate,
+    pub account: AccountNumber,
+    pub symbol: PositionSymbol,
+    pub amount: f64,
+    /// Shares held at the last sync on or before the day, if a position was stored then.
+    pub open_quantity: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The dividends of a month (YYYY-MM) or a year (YYYY).
+pub struct DividendPeriod {
+    pub period: String,
+    pub amount: f64,
+    pub payments: usize,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The dividends of one symbol.
+pub struct SymbolDividends {
+    pub symbol: PositionSymbol,
+    pub amount: f64,
+    pub payments: usize,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// What one symbol is expected to pay over the next year at the shares held today.
+pub struct ProjectedSymbol {
+    pub symbol: PositionSymbol,
+    pub open_quantity: f64,
+    pub payments: usize,
+    pub per_share: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The income expected over the next 12 months, from the payments of the last 12.
+pub struct DividendProjection {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub total: f64,
+    pub by_month: Vec<DividendPeriod>,
+    pub by_symbol: Vec<ProjectedSymbol>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The dividends received by an account or group over a range of days, and the projection.
+pub struct DividendReport {
+    pub identifier: String,
+    pub currency: Currency,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub total: f64,
+    pub by_year: Vec<DividendPeriod>,
+    pub by_month: Vec<DividendPeriod>,
+    pub by_symbol: Vec<SymbolDividends>,
+    pub payments: Vec<DividendPayment>,
+    pub projection: DividendProjection,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// The few numbers the statusbar has room for.
+pub struct DividendSummary {
+    pub month_to_date: f64,
+    pub year_to_date: f64,
+    pub trailing: f64,
+    pub projected: f64,
+}
+
+/// The accounts behind an identifier, every account of a group or just the one account, along
+/// with the currency to report in when none is asked for.
+pub fn dividend_accounts(
+    db: &dyn Storage,
+    config: &PortfolioConfig,
+    identifier: &str,
+) -> Result<(Vec<AccountNumber>, Currency)> {
+    match config.groups.iter().find(|g| g.name() == identifier) {
+        Some(_) => {
+            let group = config.get_group(identifier)?;
+            let mut numbers = Vec::new();
+            for account in group.accounts().iter() {
+                numbers.push(db.get_account_info(account)?.number);
+            }
+            Ok((numbers, group.currency().clone()))
+        }
+        // dividends land in both currencies, so a single account is reported in CAD.
+        None => Ok((vec![db.get_account_info(identifier)?.number], Currency::CAD)),
+    }
+}
+
+/// Every dividend paid into the accounts between two dates, earliest first, converted into the
+/// currency of the converter at the rate of the day.
+pub fn dividend_payments(
+    db: &dyn Storage,
+    accounts: &[AccountNumber],
+    from: NaiveDate,
+    to: NaiveDate,
+    fx: &FxConverter,
+) -> Vec<DividendPayment> {
+    let mut result: Vec<DividendPayment> = Vec::new();
+    for account in accounts.iter() {
+        // an account that never got a dividend has no activities to read.
+        let activities = db
+            .get_activities(account, from, to, Some(ActivityType::Dividends))
+            .unwrap_or_default();
+        for activity in activities.iter() {
+            let date = activity.transaction_date;
+            result.push(DividendPayment {
+                date,
+                account: account.clone(),
+                symbol: activity.symbol.clone(),
+                amount: fx.convert(
+                    activity.net_amount,
+                    &activity.currency,
+                    date.and_hms(23, 59, 59),
+                ),
+                open_quantity: held_on(db, account, &activity.symbol, date),
+            });
+        }
+    }
+    result.sort_by_key(|p| p.date);
+    result
+}
+
+/// Repeats every payment of the last year a year later, scaled from the shares held then to the
+/// shares held today. Each account is projected from its own payments, so a symbol that was
+/// sold stops paying and one that was just bought hasn't started yet.
+pub fn project_dividends(
+    db: &dyn Storage,
+    accounts: &[AccountNumber],
+    fx: &FxConverter,
+    today: NaiveDate,
+) -> DividendProjection {
+    let trailing = dividend_payments(
+        db,
+        accounts,
+        today - Duration::days(PROJECTION_DAYS - 1),
+        today,
+        fx,
+    );
+    let mut by_month: Vec<(String, f64, usize)> = Vec::new();
+    let mut by_symbol: Vec<(PositionSymbol, f64, usize)> = Vec::new();
+    let mut held: Vec<(AccountNumber, PositionSymbol, f64)> = Vec::new();
+    for payment in trailing.iter() {
+        let open_quantity = match held_on(db, &payment.account, &payment.symbol, today) {
+            Some(quantity) if quantity > 0.0 => quantity,
+            _ => continue,
+        };
+        // without a position on the day the payment is taken as it was.
+        let amount = match payment.open_quantity {
+            Some(then) if then > 0.0 => payment.amount / then * open_quantity,
+            _ => payment.amount,
+        };
+        add_to(&mut by_month, month_of(next_year(payment.date)), amount);
+        add_to(&mut by_symbol, payment.symbol.clone(), amount);
+        if !held
+            .iter()
+            .any(|(a, s, _)| *a == payment.account && *s == payment.symbol)
+        {
+            held.push((
+                payment.account.clone(),
+                payment.symbol.clone(),
+                open_quantity,
+            ));
+        }
+    }
+    by_month.sort_by(|a, b| a.0.cmp(&b.0));
+    by_symbol.sort_by(|a, b| a.0.cmp(&b.0));
+    DividendProjection {
+        from: today.succ(),
+        to: today + Duration::days(PROJECTION_DAYS),
+        total: by_symbol.iter().fold(0.0, |total, s| total + s.1),
+        by_month: by_month
+            .into_iter()
+            .map(|(period, amount, payments)| DividendPeriod {
+                period,
+                amount,
+                payments,
+            })
+            .collect(),
+        by_symbol: by_symbol
+            .into_iter()
+            .map(|(symbol, amount, payments)| {
+                let open_quantity = held
+                    .iter()
+                    .filter(|(_, s, _)| *s == symbol)
+                    .fold(0.0, |total, (_, _, q)| total + q);
+                ProjectedSymbol {
+                    symbol,
+                    open_quantity,
+                    payments,
+                    per_share: amount / open_quantity,
+                    amount,
+                }
+            })
+            .collect(),
+    }
+}
+
+/// The dividends of an account or group between two dates, and the projection from today.
+pub fn dividend_report(
+    db: &dyn Storage,
+    config: &PortfolioConfig,
+    identifier: &str,
+    convert: Option<&Currency>,
+    from: NaiveDate,
+    to: NaiveDate,
+    today: NaiveDate,
+) -> Result<DividendReport> {
+    let (accounts, currency) = dividend_accounts(db, config, identifier)?;
+    let currency = convert.unwrap_or(&currency).clone();
+    let fx = FxConverter::load_or_fixed(
+        db,
+        from.min(today - Duration::days(PROJECTION_DAYS)),
+        to.max(today),
+        currency.clone(),
+        config.usd_cad_rate,
+    );
+    let payments = dividend_payments(db, &accounts, from, to, &fx);
+    let mut by_year: Vec<(String, f64, usize)> = Vec::new();
+    let mut by_month: Vec<(String, f64, usize)> = Vec::new();
+    let mut by_symbol: Vec<(PositionSymbol, f64, usize)> = Vec::new();
+    for payment in payments.iter() {
+        add_to(
+            &mut by_year,
+            payment.date.year().to_string(),
+            payment.amount,
+        );
+        add_to(&mut by_month, month_of(payment.date), payment.amount);
+        add_to(&mut by_symbol, payment.symbol.clone(), payment.amount);
+    }
+    by_symbol.sort_by(|a, b| a.0.cmp(&b.0));
+    let period = |(period, amount, payments): (String, f64, usize)| DividendPeriod {
+        period,
+        amount,
+        payments,
+    };
+    Ok(DividendReport {
+        identifier: identifier.to_string(),
+        currency,
+        from,
+        to,
+        total: payments.iter().fold(0.0, |total, p| total + p.amount),
+        by_year: by_year.into_iter().map(period).collect(),
+        by_month: by_month.into_iter().map(period).collect(),
+        by_symbol: by_symbol
+            .into_iter()
+            .map(|(symbol, amount, payments)| SymbolDividends {
+                symbol,
+                amount,
+                payments,
+            })
+            .collect(),
+        projection: project_dividends(db, &accounts, &fx, today),
+        payments,
+    })
+}
+
+/// The month and year to date, the last 12 months, and the next 12 months of dividends.
+pub fn dividend_summary(
+    db: &dyn Storage,
+    config: &PortfolioConfig,
+    identifier: &str,
+    convert: Option<&Currency>,
+    today: NaiveDate,
+) -> Result<DividendSummary> {
+    let year_start = NaiveDate::from_ymd(today.year(), 1, 1);
+    let trailing_start = today - Duration::days(PROJECTION_DAYS - 1);
+    let report = dividend_report(
+        db,
+        config,
+        identifier,
+        convert,
+        year_start.min(trailing_start),
+        today,
+        today,
+    )?;
+    let since = |start: NaiveDate| {
+        report
+            .payments
+            .iter()
+            .filter(|p| p.date >= start)
+            .fold(0.0, |total, p| total + p.amount)
+    };
+    Ok(DividendSummary {
+        month_to_date: since(NaiveDate::from_ymd(today.year(), today.month(), 1)),
+        year_to_date: since(year_start),
+        trailing: since(trailing_start),
+        projected: report.projection.total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::include::{
+        from_str, Account, AccountActivity, AccountBalance, AccountPosition, Local, TimeZone,
+    };
+    use crate::storage::{memory_db, PositionSource};
+
+    fn account() -> Account {
+        from_str("(type: Margin, number: \"123\", status: Active, isPrimary: true, isBilling: true, clientAccountType: Individual)").unwrap()
+    }
+    fn balance() -> AccountBalance {
+        from_str("(currency: CAD, cash: 0, marketValue: 0, totalEquity: 0, buyingPower: 0, maintenanceExcess: 0, isRealTime: true)").unwrap()
+    }
+    fn position(open_quantity: f64) -> AccountPosition {
+        from_str(&format!("(symbol: \"XEQT.TO\", symbolId: 7, openQuantity: {}, closedQuantity: 0, currentMarketValue: 0, currentPrice: 30, dayPnl: Some(0), averageEntryPrice: 25, closedPnl: 0, openPnl: 0, totalCost: 0, isRealTime: true, isUnderReorg: false)", open_quantity)).unwrap()
+    }
+    fn dividend(date: &str, net_amount: f64) -> AccountActivity {
+        from_str(&format!("(tradeDate: \"{d}T05:00:00Z\", transactionDate: \"{d}T05:00:00Z\", settlementDate: \"{d}T05:00:00Z\", action: \"\", symbol: \"XEQT.TO\", symbolId: 7, description: \"\", currency: \"CAD\", quantity: 0, price: 0, grossAmount: 0, commission: 0, netAmount: {}, type: \"Dividends\")", net_amount, d = date)).unwrap()
+    }
+
+    #[test]
+    fn projection_uses_the_last_synced_day() {
+        let db = memory_db();
+        let number = "123".to_string();
+        db.insert_account("main".to_string(), account()).unwrap();
+        // 100 shares when the dividend was paid, 200 at the sync on Friday.
+        for (day, quantity) in [(2, 100.0), (16, 200.0)].iter() {
+            let at = Local.ymd(2026, 10, *day).and_hms(12, 0, 0);
+            db.insert_account_balance(at, &number, BalanceKind::Combined, balance(), balance())
+                .unwrap();
+            db.insert_account_position(at, &number, position(*quantity), PositionSource::Live)
+                .unwrap();
+        }
+        db.insert_account_activities(&number, vec![dividend("2026-10-05", 50.0)])
+            .unwrap();
+        let fx = FxConverter::fixed(Currency::CAD, 1.35);
+        let accounts = vec![number];
+        // the Sunday after has nothing synced.
+        let sunday = NaiveDate::from_ymd(2026, 10, 18);
+        let projection = project_dividends(&*db, &accounts, &fx, sunday);
+        assert!((projection.total - 100.0).abs() < 1e-9);
+        assert_eq!(projection.by_symbol[0].open_quantity, 200.0);
+        // nothing synced for over a week means nothing is known to be held.
+        let later = sunday + Duration::days(LAST_SYNC_DAYS);
+        assert_eq!(project_dividends(&*db, &accounts, &fx, later).total, 0.0);
+    }
+}
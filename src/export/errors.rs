@@ -0,0 +1,35 @@
+//! Module containing the errors that can come up while exporting history.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{error, fmt, NaiveDate};
+
+#[derive(Debug)]
+// Enum representing errors that are possible with the arguments to the export command.
+pub enum ExportError {
+    InvalidDateError(String),
+    InvalidDateRangeError(NaiveDate, NaiveDate),
+    InvalidCurrencyError(String),
+}
+
+impl error::Error for ExportError {}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidDateError(date) => write!(
+                f,
+                "Could not parse date: {}. Expected the YYYY-MM-DD format.",
+                date
+            ),
+            Self::InvalidDateRangeError(from, to) => {
+                write!(f, "Invalid date range: {} is after {}.", from, to)
+            }
+            Self::InvalidCurrencyError(currency) => write!(
+                f,
+                "Could not parse currency: {}. Expected CAD, USD, or combined.",
+                currency
+            ),
+        }
+    }
+}
@@ -0,0 +1,515 @@
+//! Module to write the stored history out as CSV or Json for use outside of qtmon.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    include::{
+        io, to_json_string, warn, AccountName, AccountNumber, Local, NaiveDate, NaiveTime,
+        OpenOptions, PathBuf, PositionSymbol, Result, Serialize, Write,
+    },
+    storage::{BalanceKind, DBInfoAccountBalance, DBInfoAccountPosition, PositionSource, Storage},
+    tax::{build_ledger, Disposition},
+};
+
+/// Sub modules
+mod errors;
+
+/// Re-export sub-modules so we can read from them in other modules.
+pub use errors::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Which part of the history to export.
+pub enum ExportKind {
+    Balances,
+    Positions,
+    Accounts,
+    /// Sales out of the non-registered accounts, with their capital gains in CAD.
+    Gains,
+}
+
+impl ExportKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.to_lowercase().as_str() {
+            "balances" => Some(Self::Balances),
+            "positions" => Some(Self::Positions),
+            "accounts" => Some(Self::Accounts),
+            "gains" => Some(Self::Gains),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// How the rows are written. Json is one array, Ndjson is one object per line so big exports
+/// can be streamed into other tools.
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            "ndjson" => Some(Self::Ndjson),
+            _ => None,
+        }
+    }
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Json => "application/json",
+            Self::Ndjson => "application/x-ndjson",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Everything the export subcommand was asked for.
+pub struct ExportRequest {
+    pub kind: ExportKind,
+    /// Name or number of the only account to export, all of them if None.
+    pub account: Option<String>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Which balances to export, the account_balance_currency from the config if None.
+    pub currency: Option<BalanceKind>,
+    pub format: ExportFormat,
+    /// File to write to, stdout if None.
+    pub output: Option<PathBuf>,
+}
+
+/// Helper functions
+// missing dates default to today, same as the http apis.
+fn parse_date(date: Option<&str>) -> Result<NaiveDate> {
+    match date {
+        Some(d) => NaiveDate::parse_from_str(d, "%Y-%m-%d")
+            .map_err(|_| ExportError::InvalidDateError(d.to_string()).into()),
+        None => Ok(Local::today().naive_local()),
+    }
+}
+
+impl ExportRequest {
+    /// Builds the request out of the raw arguments. The kind and format have already been
+    /// checked by clap, so anything unknown falls back to balances as CSV.
+    pub fn new(
+        kind: &str,
+        account: Option<&str>,
+        from: Option<&str>,
+        to: Option<&str>,
+        currency: Option<&str>,
+        format: &str,
+        output: Option<&str>,
+    ) -> Result<Self> {
+        let (from, to) = (parse_date(from)?, parse_date(to)?);
+        if from > to {
+            return Err(Box::new(ExportError::InvalidDateRangeError(from, to)));
+        }
+        let currency = match currency {
+            Some(c) => match BalanceKind::parse(c) {
+                Some(k) => Some(k),
+                None => return Err(Box::new(ExportError::InvalidCurrencyError(c.to_string()))),
+            },
+            None => None,
+        };
+        Ok(Self {
+            kind: ExportKind::parse(kind).unwrap_or(ExportKind::Balances),
+            account: account.map(str::to_string),
+            from,
+            to,
+            currency,
+            format: ExportFormat::parse(format).unwrap_or(ExportFormat::Csv),
+            output: output.map(PathBuf::from),
+        })
+    }
+}
+
+/// A row of one of the exports, the same fields are used for the CSV columns and Json keys.
+pub trait ExportRow: Serialize {
+    const HEADER: &'static [&'static str];
+    fn fields(&self) -> Vec<String>;
+}
+
+#[derive(Debug, Serialize)]
+struct AccountRow {
+    name: AccountName,
+    number: AccountNumber,
+    account_type: String,
+    status: String,
+    client_account_type: String,
+    is_primary: bool,
+    is_billing: bool,
+}
+
+impl ExportRow for AccountRow {
+    const HEADER: &'static [&'static str] = &[
+        "name",
+        "number",
+        "account_type",
+        "status",
+        "client_account_type",
+        "is_primary",
+        "is_billing",
+    ];
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.number.clone(),
+            self.account_type.clone(),
+            self.status.clone(),
+            self.client_account_type.clone(),
+            self.is_primary.to_string(),
+            self.is_billing.to_string(),
+        ]
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BalanceRow {
+    account: AccountNumber,
+    date: NaiveDate,
+    time: NaiveTime,
+    kind: &'static str,
+    /// The start of day balance is exported as its own row ahead of the day's syncs.
+    start_of_day: bool,
+    cash: f64,
+    market_value: f64,
+    total_equity: f64,
+    buying_power: f64,
+    maintenance_excess: f64,
+}
+
+impl BalanceRow {
+    fn new(
+        account: &str,
+        date: NaiveDate,
+        kind: BalanceKind,
+        start_of_day: bool,
+        balance: &DBInfoAccountBalance,
+    ) -> Self {
+        Self {
+            account: account.to_string(),
+            date,
+            time: balance.time_retrieved,
+            kind: kind.as_str(),
+            start_of_day,
+            cash: balance.cash,
+            market_value: balance.market_value,
+            total_equity: balance.total_equity,
+            buying_power: balance.buying_power,
+            maintenance_excess: balance.maitenance_excess,
+        }
+    }
+}
+
+impl ExportRow for BalanceRow {
+    const HEADER: &'static [&'static str] = &[
+        "account",
+        "date",
+        "time",
+        "kind",
+        "start_of_day",
+        "cash",
+        "market_value",
+        "total_equity",
+        "buying_power",
+        "maintenance_excess",
+    ];
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.account.clone(),
+            self.date.to_string(),
+            self.time.to_string(),
+            self.kind.to_string(),
+            self.start_of_day.to_string(),
+            self.cash.to_string(),
+            self.market_value.to_string(),
+            self.total_equity.to_string(),
+            self.buying_power.to_string(),
+            self.maintenance_excess.to_string(),
+        ]
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct PositionRow {
+    account: AccountNumber,
+    date: NaiveDate,
+    time: NaiveTime,
+    symbol: PositionSymbol,
+    open_quantity: f64,
+    closed_quantity: f64,
+    current_market_value: f64,
+    current_price: f64,
+    average_entry_price: f64,
+    closed_pnl: f64,
+    day_pnl: f64,
+    open_pnl: f64,
+    total_cost: f64,
+    source: PositionSource,
+}
+
+impl PositionRow {
+    fn new(account: &str, date: NaiveDate, position: &DBInfoAccountPosition) -> Self {
+        Self {
+            account: account.to_string(),
+            date,
+            time: position.time_retrieved,
+            symbol: position.symbol.clone(),
+            open_quantity: position.open_quantity,
+            closed_quantity: position.closed_quantity,
+            current_market_value: position.current_market_value,
+            current_price: position.current_price,
+            average_entry_price: position.average_entry_price,
+            closed_pnl: position.closed_pnl,
+            day_pnl: position.day_pnl,
+            open_pnl: position.open_pnl,
+            total_cost: position.total_cost,
+            source: position.source,
+        }
+    }
+}
+
+impl ExportRow for PositionRow {
+    const HEADER: &'static [&'static str] = &[
+        "account",
+        "date",
+        "time",
+        "symbol",
+        "open_quantity",
+        "closed_quantity",
+        "current_market_value",
+        "current_price",
+        "average_entry_price",
+        "closed_pnl",
+        "day_pnl",
+        "open_pnl",
+        "total_cost",
+        "source",
+    ];
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.account.clone(),
+            self.date.to_string(),
+            self.time.to_string(),
+            self.symbol.clone(),
+            self.open_quantity.to_string(),
+            self.closed_quantity.to_string(),
+            self.current_market_value.to_string(),
+            self.current_price.to_string(),
+            self.average_entry_price.to_string(),
+            self.closed_pnl.to_string(),
+            self.day_pnl.to_string(),
+            self.open_pnl.to_string(),
+            self.total_cost.to_string(),
+            self.source.as_str().to_string(),
+        ]
+    }
+}
+
+impl ExportRow for Disposition {
+    const HEADER: &'static [&'static str] = &[
+        "trade_date",
+        "settlement_date",
+        "account",
+        "symbol",
+        "quantity",
+        "proceeds",
+        "acb",
+        "outlays",
+        "gain",
+        "superficial_loss",
+    ];
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.trade_date.to_string(),
+            self.settlement_date.to_string(),
+            self.account.clone(),
+            self.symbol.clone(),
+            self.quantity.to_string(),
+            self.proceeds.to_string(),
+            self.acb.to_string(),
+            self.outlays.to_string(),
+            self.gain.to_string(),
+            self.superficial_loss.to_string(),
+        ]
+    }
+}
+
+// fields with a comma, quote, or line break get quoted, with any quotes inside doubled.
+fn csv_field(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Writes the rows out in the format asked for, the tax api uses it for its CSV too.
+pub fn write_rows<R: ExportRow>(
+    out: &mut dyn Write,
+    format: ExportFormat,
+    rows: &[R],
+) -> Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            writeln!(out, "{}", R::HEADER.join(","))?;
+            for row in rows {
+                let fields: Vec<String> = row.fields().iter().map(|f| csv_field(f)).collect();
+                writeln!(out, "{}", fields.join(","))?;
+            }
+        }
+        ExportFormat::Json => writeln!(out, "{}", to_json_string(rows)?)?,
+        ExportFormat::Ndjson => {
+            for row in rows {
+                writeln!(out, "{}", to_json_string(row)?)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// the numbers of the accounts asked for, an unknown account is an error.
+fn account_numbers(db: &dyn Storage, account: &Option<String>) -> Result<Vec<AccountNumber>> {
+    match account {
+        Some(a) => Ok(vec![db.get_account_info(a)?.number]),
+        None => db.list_account_numbers(),
+    }
+}
+
+fn account_rows(db: &dyn Storage, request: &ExportRequest) -> Result<Vec<AccountRow>> {
+    let wanted = account_numbers(db, &request.account)?;
+    let mut rows = Vec::new();
+    for name in db.get_account_list()? {
+        let account = db.get_account_info(&name)?;
+        if wanted.contains(&account.number) {
+            rows.push(AccountRow {
+                name,
+                number: account.number,
+                account_type: format!("{:?}", account.account_type),
+                status: format!("{:?}", account.status),
+                client_account_type: format!("{:?}", account.client_account_type),
+                is_primary: account.is_primary,
+                is_billing: account.is_billing,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+fn balance_rows(
+    db: &dyn Storage,
+    request: &ExportRequest,
+    kind: BalanceKind,
+) -> Result<Vec<BalanceRow>> {
+    let mut rows = Vec::new();
+    for number in account_numbers(db, &request.account)? {
+        // an account with nothing in the range just has no rows.
+        let days = match db.get_balance_days(&number, kind, request.from, request.to) {
+            Ok(days) => days,
+            Err(e) => {
+                warn!("No balances to export for account {}: {}", number, e);
+                continue;
+            }
+        };
+        for day in days {
+            rows.push(BalanceRow::new(
+                &number,
+                day.date,
+                kind,
+                true,
+                &day.start_of_day_bal,
+            ));
+            for balance in day.over_day_balances.iter() {
+                rows.push(BalanceRow::new(&number, day.date, kind, false, balance));
+            }
+        }
+    }
+    Ok(rows)
+}
+
+fn position_rows(db: &dyn Storage, request: &ExportRequest) -> Result<Vec<PositionRow>> {
+    let mut rows = Vec::new();
+    for number in account_numbers(db, &request.account)? {
+        for symbol in db.get_position_symbols(&number).unwrap_or_default() {
+            let days = match db.get_position_days(&number, &symbol, request.from, request.to) {
+                Ok(days) => days,
+                Err(_) => continue,
+            };
+            for (date, positions) in days {
+                for position in positions.iter() {
+                    rows.push(PositionRow::new(&number, date, position));
+                }
+            }
+        }
+    }
+    // grouped by account then by time, so each day reads like a snapshot of the account.
+    rows.sort_by(|a, b| {
+        (&a.account, a.date, a.time, &a.symbol).cmp(&(&b.account, b.date, b.time, &b.symbol))
+    });
+    Ok(rows)
+}
+
+// the gains are worked out over the whole history, only the sales settled in the range are
+// written out.
+fn gain_rows(
+    db: &dyn Storage,
+    request: &ExportRequest,
+    usd_cad_rate: f64,
+) -> Result<Vec<Disposition>> {
+    let wanted = account_numbers(db, &request.account)?;
+    let ledger = build_ledger(db, usd_cad_rate, Local::today().naive_local())?;
+    Ok(ledger
+        .dispositions
+        .into_iter()
+        .filter(|d| d.settlement_date >= request.from && d.settlement_date <= request.to)
+        .filter(|d| wanted.contains(&d.account))
+        .collect())
+}
+
+/// Writes the history asked for to the output of the request, returns how many rows there were.
+/// The usd_cad_rate is only used by the gains if no rates have been recorded.
+pub fn export_history(
+    db: &dyn Storage,
+    request: &ExportRequest,
+    default_kind: BalanceKind,
+    usd_cad_rate: f64,
+) -> Result<usize> {
+    let mut out: Box<dyn Write> = match &request.output {
+        Some(path) => Box::new(
+            OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)?,
+        ),
+        None => Box::new(io::stdout()),
+    };
+    let count = match request.kind {
+        ExportKind::Accounts => {
+            let rows = account_rows(db, request)?;
+            write_rows(&mut *out, request.format, &rows)?;
+            rows.len()
+        }
+        ExportKind::Balances => {
+            let kind = request.currency.unwrap_or(default_kind);
+            let rows = balance_rows(db, request, kind)?;
+            write_rows(&mut *out, request.format, &rows)?;
+            rows.len()
+        }
+        ExportKind::Positions => {
+            let rows = position_rows(db, request)?;
+            write_rows(&mut *out, request.format, &rows)?;
+            rows.len()
+        }
+        ExportKind::Gains => {
+            let rows = gain_rows(db, request, usd_cad_rate)?;
+            write_rows(&mut *out, request.format, &rows)?;
+            rows.len()
+        }
+    };
+    out.flush()?;
+    Ok(count)
+}
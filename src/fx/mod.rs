@@ -0,0 +1,361 @@
+//! Module to track the USD/CAD exchange rate and convert stored values between currencies.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    include::{
+        AccountBalances, Currency, Deserialize, HashMap, Local, NaiveDate, NaiveDateTime,
+        NaiveTime, PositionSymbol, Result, Serialize,
+    },
+    portfolio::symbol_currency,
+    storage::{
+        DBInfoAccountActivity, DBInfoAccountBalance, DBInfoAccountBalanceDay,
+        DBInfoAccountExecution, DBInfoAccountOrder, DBInfoAccountPosition, DBInfoFxRate, Storage,
+    },
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// Where the rate recorded at each sync comes from.
+pub enum FxSource {
+    /// Worked out from the combined balances Questrade sends back in both currencies.
+    Balances,
+    /// The same rate every time, how many CAD one USD buys.
+    Fixed(f64),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// The fx section of the config file.
+pub struct FxConfig {
+    pub source: FxSource,
+}
+
+impl Default for FxConfig {
+    fn default() -> Self {
+        Self {
+            source: FxSource::Balances,
+        }
+    }
+}
+
+impl FxConfig {
+    /// Builds the rate source picked in the config.
+    pub fn rate_source(&self) -> Box<dyn RateSource> {
+        match self.source {
+            FxSource::Balances => Box::new(BalancesRateSource),
+            FxSource::Fixed(rate) => Box::new(FixedRateSource(rate)),
+        }
+    }
+}
+
+/// Anything that can tell us how many CAD one USD buys at the time of a sync.
+pub trait RateSource: Send + Sync {
+    /// Gets handed the balances of an account we just pulled, None if there is no rate to give.
+    fn usd_cad(&self, balances: &AccountBalances) -> Option<f64>;
+}
+
+/// The combined balances are the whole account valued in each currency, so the ratio of the
+/// two equities is the rate Questrade used.
+pub struct BalancesRateSource;
+
+impl RateSource for BalancesRateSource {
+    fn usd_cad(&self, balances: &AccountBalances) -> Option<f64> {
+        let equity_in = |currency: Currency| {
+            balances
+                .combined_balances
+                .iter()
+                .find(|bl| bl.currency == currency)
+                .and_then(|bl| bl.total_equity.as_f64())
+                .filter(|equity| *equity != 0.0)
+        };
+        Some(equity_in(Currency::CAD)? / equity_in(Currency::USD)?)
+    }
+}
+
+/// A rate set in the config, for when the balances can't be trusted to give one.
+pub struct FixedRateSource(f64);
+
+impl RateSource for FixedRateSource {
+    fn usd_cad(&self, _balances: &AccountBalances) -> Option<f64> {
+        Some(self.0)
+    }
+}
+
+/// Works out the most recent rate we have recorded, if we have any.
+pub fn latest_usd_cad(db: &dyn Storage) -> Option<f64> {
+    let now = Local::now().naive_local();
+    let rates = db.get_fx_rates(now.date(), now.date()).ok()?;
+    Some(rate_at(&rates, now))
+}
+
+// the last rate recorded at or before the moment, or the first one if the moment is older
+// than everything we have.
+fn rate_at(rates: &[DBInfoFxRate], at: NaiveDateTime) -> f64 {
+    rates
+        .iter()
+        .take_while(|r| r.datetime() <= at)
+        .last()
+        .unwrap_or(&rates[0])
+        .usd_cad
+}
+
+// the currency of every symbol looked up so far.
+fn looked_up_currencies(db: &dyn Storage) -> HashMap<PositionSymbol, Currency> {
+    db.get_symbols()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|info| (info.symbol, info.currency))
+        .collect()
+}
+
+/// Converts stored values into one currency at the rate recorded closest before each value.
+pub struct FxConverter {
+    rates: Vec<DBInfoFxRate>,
+    to: Currency,
+    currencies: HashMap<PositionSymbol, Currency>,
+}
+
+impl FxConverter {
+    /// Loads the rates needed to convert anything stored between two dates, inclusive.
+    pub fn load(
+        db: &dyn Storage,
+        from: NaiveDate,
+        to: NaiveDate,
+        currency: Currency,
+    ) -> Result<Self> {
+        Ok(Self {
+            rates: db.get_fx_rates(from, to)?,
+            to: currency,
+            currencies: looked_up_currencies(db),
+        })
+    }
+    /// Same as load, but falls back to a fixed rate when none have been recorded yet.
+    pub fn load_or_fixed(
+        db: &dyn Storage,
+        from: NaiveDate,
+        to: NaiveDate,
+        currency: Currency,
+        usd_cad_rate: f64,
+    ) -> Self {
+        match db.get_fx_rates(from, to) {
+            Ok(rates) if !rates.is_empty() => Self {
+                rates,
+                to: currency,
+                currencies: looked_up_currencies(db),
+            },
+            _ => Self::fixed(currency, usd_cad_rate),
+        }
+    }
+    /// Converts everything at the one rate, no matter when.
+    pub fn fixed(currency: Currency, usd_cad_rate: f64) -> Self {
+        Self {
+            rates: vec![DBInfoFxRate::new(
+                NaiveDate::from_ymd(1970, 1, 1),
+                NaiveTime::from_hms(0, 0, 0),
+                usd_cad_rate,
+            )],
+            to: currency,
+            currencies: HashMap::new(),
+        }
+    }
+    /// Whether a rate was recorded at or before the moment. Anything older is converted at the
+    /// first rate we have, which can be years off.
+    pub fn covers(&self, at: NaiveDateTime) -> bool {
+        matches!(self.rates.first(), Some(rate) if rate.datetime() <= at)
+    }
+    // the currency a symbol trades in, the looked up one if we have it.
+    fn listing(&self, symbol: &str) -> Currency {
+        match self.currencies.get(symbol) {
+            Some(currency) => currency.clone(),
+            None => symbol_currency(symbol),
+        }
+    }
+    /// Converts an amount in a currency at a moment into the target currency.
+    pub fn convert(&self, amount: f64, from: &Currency, at: NaiveDateTime) -> f64 {
+        match (from, &self.to) {
+            (Currency::USD, Currency::CAD) => amount * rate_at(&self.rates, at),
+            (Currency::CAD, Currency::USD) => amount / rate_at(&self.rates, at),
+            _ => amount,
+        }
+    }
+    pub fn balance(&self, balance: &mut DBInfoAccountBalance, date: NaiveDate) {
+        let at = date.and_time(balance.time_retrieved);
+        let from = balance.currency.clone();
+        let convert = |amount: f64| self.convert(amount, &from, at);
+        balance.cash = convert(balance.cash);
+        balance.market_value = convert(balance.market_value);
+        balance.total_equity = convert(balance.total_equity);
+        balance.buying_power = convert(balance.buying_power);
+        balance.maitenance_excess = convert(balance.maitenance_excess);
+        balance.currency = self.to.clone();
+    }
+    pub fn balance_days(&self, days: &mut [DBInfoAccountBalanceDay]) {
+        for day in days.iter_mut() {
+            self.balance(&mut day.start_of_day_bal, day.date);
+            for bal in day.over_day_balances.iter_mut() {
+                self.balance(bal, day.date);
+            }
+        }
+    }
+    /// Positions don't carry a currency, so the one they are listed in is used.
+    pub fn position(&self, position: &mut DBInfoAccountPosition, date: NaiveDate) {
+        let at = date.and_time(position.time_retrieved);
+        let from = self.listing(&position.symbol);
+        let convert = |amount: f64| self.convert(amount, &from, at);
+        position.current_market_value = convert(position.current_market_value);
+        position.current_price = convert(position.current_price);
+        position.average_entry_price = convert(position.average_entry_price);
+        position.closed_pnl = convert(position.closed_pnl);
+        position.day_pnl = convert(position.day_pnl);
+        position.open_pnl = convert(position.open_pnl);
+        position.total_cost = convert(position.total_cost);
+    }
+    pub fn position_days(&self, days: &mut [(NaiveDate, Vec<DBInfoAccountPosition>)]) {
+        for (date, positions) in days.iter_mut() {
+            for pos in positions.iter_mut() {
+                self.position(pos, *date);
+            }
+        }
+    }
+    pub fn order(&self, order: &mut DBInfoAccountOrder) {
+        let at = order.creation_time.naive_local();
+        let from = self.listing(&order.symbol);
+        let convert = |amount: f64| self.convert(amount, &from, at);
+        order.limit_price = order.limit_price.map(convert);
+        order.stop_price = order.stop_price.map(convert);
+        order.avg_execution_price = order.avg_execution_price.map(convert);
+        order.commission_charged = convert(order.commission_charged);
+    }
+    pub fn execution(&self, execution: &mut DBInfoAccountExecution) {
+        let at = execution.timestamp.naive_local();
+        let from = self.listing(&execution.symbol);
+        let convert = |amount: f64| self.convert(amount, &from, at);
+        execution.price = convert(execution.price);
+        execution.commission = convert(execution.commission);
+        execution.execution_fee = convert(execution.execution_fee);
+        execution.sec_fee = convert(execution.sec_fee);
+        execution.canadian_execution_fee = convert(execution.canadian_execution_fee);
+    }
+    /// Activities only have a date, so the last rate of that day is used.
+    pub fn activity(&self, activity: &mut DBInfoAccountActivity) {
+        let at = activity
+            .transaction_date
+            .and_time(NaiveTime::from_hms(23, 59, 59));
+        let from = activity.currency.clone();
+        let convert = |amount: f64| self.convert(amount, &from, at);
+        activity.price = convert(activity.price);
+        activity.gross_amount = convert(activity.gross_amount);
+        activity.commission = convert(activity.commission);
+        activity.net_amount = convert(activity.net_amount);
+        activity.currency = self.to.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::include::{from_str, AccountPosition, TimeZone};
+    use crate::storage::{memory_db, DBInfoSymbol, PositionSource};
+
+    fn position(symbol: &str, price: f64) -> DBInfoAccountPosition {
+        let position: AccountPosition = from_str(&format!("(symbol: \"{}\", symbolId: 7, openQuantity: 1, closedQuantity: 0, currentMarketValue: {}, currentPrice: {}, dayPnl: Some(0), averageEntryPrice: {}, closedPnl: 0, openPnl: 0, totalCost: {}, isRealTime: true, isUnderReorg: false)", symbol, price, price, price, price)).unwrap();
+        DBInfoAccountPosition::new(
+            position,
+            NaiveTime::from_hms(12, 0, 0),
+            PositionSource::Live,
+        )
+    }
+
+    fn rate(hour: u32, usd_cad: f64) -> DBInfoFxRate {
+        DBInfoFxRate::new(
+            NaiveDate::from_ymd(2026, 10, 16),
+            NaiveTime::from_hms(hour, 0, 0),
+            usd_cad,
+        )
+    }
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(2026, 10, 16).and_hms(hour, 0, 0)
+    }
+
+    #[test]
+    fn rate_at_the_moment() {
+        let rates = vec![rate(9, 1.25), rate(15, 1.3), rate(15, 1.35)];
+        // older than every rate, so the first is the best we have.
+        assert_eq!(rate_at(&rates, at(8)), 1.25);
+        assert_eq!(rate_at(&rates, at(9)), 1.25);
+        assert_eq!(rate_at(&rates, at(12)), 1.25);
+        // two rates at the same moment, the one recorded last wins.
+        assert_eq!(rate_at(&rates, at(15)), 1.35);
+        assert_eq!(rate_at(&rates, at(20)), 1.35);
+    }
+
+    #[test]
+    fn covers_from_the_first_rate() {
+        let fx = FxConverter {
+            rates: vec![rate(9, 1.25)],
+            to: Currency::CAD,
+            currencies: HashMap::new(),
+        };
+        assert!(!fx.covers(at(8)));
+        assert!(fx.covers(at(9)));
+        assert!(fx.covers(at(20)));
+        assert!(FxConverter::fixed(Currency::CAD, 1.3).covers(at(8)));
+    }
+
+    #[test]
+    fn load_or_fixed_prefers_recorded_rates() {
+        let db = memory_db();
+        let day = NaiveDate::from_ymd(2026, 10, 16);
+        let fx = FxConverter::load_or_fixed(&*db, day, day, Currency::CAD, 1.3);
+        assert!((fx.convert(100.0, &Currency::USD, at(12)) - 130.0).abs() < 1e-9);
+        db.insert_fx_rate(Local.ymd(2026, 10, 16).and_hms(9, 0, 0), 1.25)
+            .unwrap();
+        let fx = FxConverter::load_or_fixed(&*db, day, day, Currency::CAD, 1.3);
+        assert!((fx.convert(100.0, &Currency::USD, at(12)) - 125.0).abs() < 1e-9);
+        // a day without rates of its own carries the last one over instead of falling back.
+        let next = day.succ();
+        let fx = FxConverter::load_or_fixed(&*db, next, next, Currency::CAD, 1.3);
+        let noon = next.and_hms(12, 0, 0);
+        assert!((fx.convert(100.0, &Currency::USD, noon) - 125.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn convert_both_ways() {
+        let to_cad = FxConverter::fixed(Currency::CAD, 1.25);
+        let to_usd = FxConverter::fixed(Currency::USD, 1.25);
+        assert!((to_cad.convert(100.0, &Currency::USD, at(12)) - 125.0).abs() < 1e-9);
+        assert!((to_usd.convert(125.0, &Currency::CAD, at(12)) - 100.0).abs() < 1e-9);
+        // already in the target currency.
+        assert_eq!(to_cad.convert(100.0, &Currency::CAD, at(12)), 100.0);
+        assert_eq!(to_usd.convert(100.0, &Currency::USD, at(12)), 100.0);
+    }
+
+    #[test]
+    fn positions_convert_from_the_looked_up_currency() {
+        let db = memory_db();
+        let day = NaiveDate::from_ymd(2026, 10, 16);
+        db.insert_fx_rate(Local.ymd(2026, 10, 16).and_hms(9, 0, 0), 1.25)
+            .unwrap();
+        db.insert_symbol(DBInfoSymbol {
+            symbol: "DLR.U.TO".to_string(),
+            symbol_id: 1,
+            description: String::new(),
+            exchange: "TSX".to_string(),
+            security_type: "Stock".to_string(),
+            currency: Currency::USD,
+            sector: None,
+            industry: None,
+            updated: day,
+        })
+        .unwrap();
+        let fx = FxConverter::load(&*db, day, day, Currency::CAD).unwrap();
+        // listed on the TSX but traded in USD.
+        let mut usd = position("DLR.U.TO", 10.0);
+        fx.position(&mut usd, day);
+        assert!((usd.current_price - 12.5).abs() < 1e-9);
+        // never looked up, so the suffix decides.
+        let mut cad = position("XEQT.TO", 10.0);
+        fx.position(&mut cad, day);
+        assert!((cad.current_price - 10.0).abs() < 1e-9);
+    }
+}
@@ -3,18 +3,41 @@
 //! Started on: November 8, 2020
 
 use super::{
+    analytics::{
+        compute_allocation, compute_contributions, compute_returns, compute_risk, AnalyticsConfig,
+    },
+    dividends::dividend_report,
+    export::{write_rows, ExportFormat},
+    fx::latest_usd_cad,
     include::{
         error, info, json, tokio, warn,
         warp::{self, Filter},
-        with_status, Account, Deserialize, Duration, Ipv4Addr, Json, Local, Result, Serialize,
-        SocketAddr, SocketAddrV4, StatusCode,
+        with_status, Datelike, Deserialize, Duration, Ipv4Addr, Json, Local, Serialize, SocketAddr,
+        SocketAddrV4, StatusCode,
+    },
+    portfolio::{group_balance, group_positions, PortfolioConfig},
+    reports::{read_report, ReportFormat, ReportsConfig},
+    storage::{
+        downsample_balances, downsample_positions, downsample_quotes, BalanceKind,
+        DBInfoAccountPosition, DBInfoSymbol, DBRef, Storage,
     },
-    storage::{DBInfoAccountBalance, DBInfoAccountPosition, DBRef},
+    tax::{build_ledger, parse_year},
 };
 
+mod stream;
 mod util;
+// the stream types are shared with the Monitor that sends the events.
+pub use stream::{event_channel, EventSender, SyncEvent};
+use stream::{sse_reply, ws_reply, Subscription};
 // we seperated out our util funtions to another mod, so we include them here.
-use util::{api_string_replacement, parse_date, parse_time};
+use util::{
+    api_string_replacement, get_statusbar_dividends, get_statusbar_info, get_statusbar_quotes,
+    parse_activity_type, parse_balance_kind, parse_convert, parse_converter, parse_date,
+    parse_date_range, parse_interval, parse_return_method, parse_time, parse_trailing_date_range,
+};
+
+/// How many days the risk api looks back when no from date is given.
+const RISK_DEFAULT_DAYS: i64 = 365;
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ErrorReply {
@@ -26,6 +49,138 @@ impl ErrorReply {
     }
 }
 
+#[derive(Debug, Serialize)]
+/// A position along with the info of its symbol, if it has been looked up yet.
+pub struct PositionReply {
+    #[serde(flatten)]
+    position: DBInfoAccountPosition,
+    symbol_info: Option<DBInfoSymbol>,
+}
+impl PositionReply {
+    pub fn new(db: &dyn Storage, position: DBInfoAccountPosition) -> Self {
+        let symbol_info = db.get_symbol(&position.symbol).ok();
+        Self {
+            position,
+            symbol_info,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the apis that take a date range, both default to today.
+pub struct DateRangeQuery {
+    from: Option<String>,
+    to: Option<String>,
+    convert: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the apis that only take a currency to convert the values into.
+pub struct ConvertQuery {
+    convert: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the balance apis, which of the balances to read.
+pub struct CurrencyQuery {
+    currency: Option<String>,
+    convert: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the range apis, a date range and the size of the buckets to return.
+pub struct RangeQuery {
+    from: Option<String>,
+    to: Option<String>,
+    interval: Option<String>,
+    currency: Option<String>,
+    convert: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the quote range api, quotes have no currency so there is nothing to convert.
+pub struct QuoteRangeQuery {
+    from: Option<String>,
+    to: Option<String>,
+    interval: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the returns api, a date range and how to measure the returns.
+pub struct ReturnsQuery {
+    from: Option<String>,
+    to: Option<String>,
+    method: Option<String>,
+    currency: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the risk api, a date range that defaults to the last year.
+pub struct RiskQuery {
+    from: Option<String>,
+    to: Option<String>,
+    currency: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the allocation api, the currency to report in.
+pub struct AllocationQuery {
+    convert: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the contributions api, the calendar year to report on.
+pub struct ContributionQuery {
+    year: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the dividends api, a date range that defaults to the year so far.
+pub struct DividendQuery {
+    from: Option<String>,
+    to: Option<String>,
+    convert: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the activity api, a date range and an optional activity type.
+pub struct ActivityQuery {
+    from: Option<String>,
+    to: Option<String>,
+    #[serde(rename = "type")]
+    activity_type: Option<String>,
+    convert: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the alerts api, a date range and optionally just one account.
+pub struct AlertQuery {
+    from: Option<String>,
+    to: Option<String>,
+    account: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the reports api, which format to send back.
+pub struct ReportQuery {
+    format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the capital gains api, json for the whole report or csv for just the sales.
+pub struct TaxQuery {
+    format: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+/// Query string for the stream api, comma seperated lists of what to send.
+pub struct StreamQuery {
+    account: Option<String>,
+    symbol: Option<String>,
+    #[serde(rename = "type")]
+    event_type: Option<String>,
+}
+
 #[derive(Debug)]
 /// Struct to hold a reference to the thread that is runnning the http server.
 pub struct HTTPServer {
@@ -33,7 +188,16 @@ pub struct HTTPServer {
 }
 
 impl HTTPServer {
-    pub fn new(addr: Ipv4Addr, port: u16, db: DBRef) -> Self {
+    pub fn new(
+        addr: Ipv4Addr,
+        port: u16,
+        db: DBRef,
+        analytics_config: &AnalyticsConfig,
+        portfolio_config: &PortfolioConfig,
+        reports_config: &ReportsConfig,
+        default_kind: BalanceKind,
+        events: EventSender,
+    ) -> Self {
         // gen the log filters
         let log = warp::filters::log::custom(|info| {
             let log_str = format!(
@@ -61,44 +225,47 @@ impl HTTPServer {
         let any = warp::any().map(|| with_status(format!("Not implemented."), StatusCode::OK));
         // the statusbar api.
         let db_sb = db.clone();
+        let pc_sb = portfolio_config.clone();
         let statusbar = warp::path!("statusbar" / String / String)
             .and(warp::path::end())
-            .map(move |a: String, b: String| {
+            .and(warp::query::<ConvertQuery>())
+            .map(move |a: String, b: String, q: ConvertQuery| {
                 // first we pull info from our DB to use during the string replace.
-                let (mut positions, sod_balance, latest_balance): (
-                    Vec<(String, DBInfoAccountPosition)>,
-                    DBInfoAccountBalance,
-                    DBInfoAccountBalance,
-                ) = match (*db_sb).db.read(
-                    |db| -> Result<(
-                        Vec<(String, DBInfoAccountPosition)>,
-                        DBInfoAccountBalance,
-                        DBInfoAccountBalance,
-                    )> {
-                        let today = Local::today().naive_local();
-                        let mut position_list = db.get_position_symbols(&a)?;
-                        let mut positions: Vec<DBInfoAccountPosition> = position_list
-                            .iter()
-                            .map(|pn| db.get_latest_position(&a, pn, today).unwrap())
-                            .collect();
-                        let sod_balance = db.get_start_of_day_balance(&a, today)?;
-                        let latest_balance = db.get_latest_balance(&a, today)?;
-                        let positions = position_list.drain(..).zip(positions.drain(..)).collect();
-                        Ok((positions, sod_balance, latest_balance))
-                    },
-                ) {
-                    Ok(Ok(val)) => val,
-                    Ok(Err(e)) => {
-                        return format!("Error getting account info from db. Error: {}", e)
+                let (mut positions, mut sod_balance, mut latest_balance) =
+                    match get_statusbar_info(&*db_sb, &a, default_kind) {
+                        Ok(val) => val,
+                        Err(e) => {
+                            return format!("Error getting account info from db. Error: {}", e)
+                        }
+                    };
+                // everything is from today so one day of rates is all we need.
+                let today = Local::today().naive_local();
+                let convert = parse_convert(q.convert.clone()).ok().flatten();
+                match parse_converter(&*db_sb, q.convert, today, today) {
+                    Ok(Some(c)) => {
+                        c.balance(&mut sod_balance, today);
+                        c.balance(&mut latest_balance, today);
+                        for (_, pos) in positions.iter_mut() {
+                            c.position(pos, today);
+                        }
                     }
-                    Err(e) => return format!("Database Error. Error: {}", e),
-                };
+                    Ok(None) => {}
+                    // the statusbar only returns text, so the json error can't be passed on.
+                    Err(_) => {
+                        return "Error converting values, check the currency and the rates synced."
+                            .to_string()
+                    }
+                }
+                // quotes are left in the currency they trade in.
+                let quotes = get_statusbar_quotes(&*db_sb, &b);
+                let config = pc_sb.for_request(latest_usd_cad(&*db_sb), None);
+                let dividends = get_statusbar_dividends(&*db_sb, &config, &a, convert.as_ref(), &b);
                 // now we filter down the list to make the replace methods faster.
                 let positions: Vec<(String, DBInfoAccountPosition)> = positions
                     .drain_filter(|pos| b.contains(&format!("%{}", pos.0)))
                     .collect();
                 // next up we do our replacements on the string
-                api_string_replacement(positions, sod_balance, latest_balance, b)
+                api_string_replacement(positions, quotes, dividends, sod_balance, latest_balance, b)
             });
         //  the raw json api
         let raw = warp::path("raw");
@@ -112,44 +279,101 @@ impl HTTPServer {
             .and(warp::path::end());
         let raw_position_latest = raw_position
             .and(warp::path!(String / String / "latest"))
-            .and(warp::path::end());
+            .and(warp::path::end())
+            .and(warp::query::<ConvertQuery>());
         let raw_position_date_latest = raw_position
             .and(warp::path!(String / String / String / "latest"))
-            .and(warp::path::end());
+            .and(warp::path::end())
+            .and(warp::query::<ConvertQuery>());
         let raw_position_date_time = raw_position
             .and(warp::path!(String / String / String / String))
-            .and(warp::path::end());
+            .and(warp::path::end())
+            .and(warp::query::<ConvertQuery>());
+        let raw_position_range = raw_position
+            .and(warp::path!(String / String / "range"))
+            .and(warp::path::end())
+            .and(warp::query::<RangeQuery>());
+        let raw_position_group_list = raw_position
+            .and(warp::path!("group" / String / "list"))
+            .and(warp::path::end())
+            .and(warp::query::<ConvertQuery>());
         // ** /raw/balance paths
         let raw_balance = raw.and(warp::path("balance"));
         let raw_balance_sod = raw_balance
             .and(warp::path!(String / "sod"))
-            .and(warp::path::end());
+            .and(warp::path::end())
+            .and(warp::query::<CurrencyQuery>());
         let raw_balance_sod_date = raw_balance
             .and(warp::path!(String / String / "sod"))
-            .and(warp::path::end());
+            .and(warp::path::end())
+            .and(warp::query::<CurrencyQuery>());
         let raw_balance_latest = raw_balance
             .and(warp::path!(String / "latest"))
-            .and(warp::path::end());
+            .and(warp::path::end())
+            .and(warp::query::<CurrencyQuery>());
         let raw_balance_latest_date = raw_balance
             .and(warp::path!(String / String / "latest"))
-            .and(warp::path::end());
+            .and(warp::path::end())
+            .and(warp::query::<CurrencyQuery>());
         let raw_balance_date_time = raw_balance
             .and(warp::path!(String / String / String))
+            .and(warp::path::end())
+            .and(warp::query::<CurrencyQuery>());
+        let raw_balance_range = raw_balance
+            .and(warp::path!(String / "range"))
+            .and(warp::path::end())
+            .and(warp::query::<RangeQuery>());
+        let raw_balance_group_sod = raw_balance
+            .and(warp::path!("group" / String / "sod"))
+            .and(warp::path::end())
+            .and(warp::query::<CurrencyQuery>());
+        let raw_balance_group_latest = raw_balance
+            .and(warp::path!("group" / String / "latest"))
+            .and(warp::path::end())
+            .and(warp::query::<CurrencyQuery>());
+        // ** /raw/quote paths
+        let raw_quote = raw.and(warp::path("quote"));
+        let raw_quote_list = raw_quote.and(warp::path("list").and(warp::path::end()));
+        let raw_quote_latest = raw_quote
+            .and(warp::path!(String / "latest"))
             .and(warp::path::end());
+        let raw_quote_range = raw_quote
+            .and(warp::path!(String / "range"))
+            .and(warp::path::end())
+            .and(warp::query::<QuoteRangeQuery>());
+        // ** /raw/symbol paths
+        let raw_symbol = raw.and(warp::path("symbol"));
+        let raw_symbol_list = raw_symbol.and(warp::path("list").and(warp::path::end()));
+        let raw_symbol_info = raw_symbol.and(warp::path!(String).and(warp::path::end()));
+        // ** /raw/order paths
+        let raw_order = raw.and(warp::path("order"));
+        let raw_order_list = raw_order
+            .and(warp::path!(String / "list"))
+            .and(warp::path::end())
+            .and(warp::query::<DateRangeQuery>());
+        let raw_order_id = raw_order
+            .and(warp::path!(String / u32))
+            .and(warp::path::end())
+            .and(warp::query::<ConvertQuery>());
+        // ** /raw/execution paths
+        let raw_execution = raw.and(warp::path("execution"));
+        let raw_execution_list = raw_execution
+            .and(warp::path!(String / "list"))
+            .and(warp::path::end())
+            .and(warp::query::<DateRangeQuery>());
+        // ** /raw/activity paths
+        let raw_activity = raw
+            .and(warp::path("activity"))
+            .and(warp::path!(String))
+            .and(warp::path::end())
+            .and(warp::query::<ActivityQuery>());
 
         // account name list api.
         // clone so we can move it to the new runtime
         let db_al = db.clone();
         let raw_account_list = raw_account_list.map(move || -> Json {
-            match (*db_al)
-                .db
-                .read(|db| -> Result<Vec<String>> { Ok(db.get_account_list()?) })
-            {
-                Ok(Ok(val)) => json(&val),
-                Ok(Err(e)) => json(&ErrorReply::new(format!(
-                    "Error getting account list. Error: {}",
-                    e
-                ))),
+            match db_al.get_account_list() {
+                Ok(val) => json(&val),
                 Err(e) => json(&ErrorReply::new(format!(
                     "Error getting account list. Error: {}",
                     e
@@ -161,15 +385,8 @@ impl HTTPServer {
         // clone so we can move it to the new runtime
         let db_ai = db.clone();
         let raw_account_info = raw_account_info.map(move |a: String| -> Json {
-            match (*db_ai)
-                .db
-                .read(|db| -> Result<Account> { Ok(db.get_account_info(&a)?) })
-            {
-                Ok(Ok(val)) => json(&val),
-                Ok(Err(e)) => json(&ErrorReply::new(format!(
-                    "Error getting account info. Error: {}",
-                    e
-                ))),
+            match db_ai.get_account_info(&a) {
+                Ok(val) => json(&val),
                 Err(e) => json(&ErrorReply::new(format!(
                     "Error getting account info. Error: {}",
                     e
@@ -180,15 +397,8 @@ impl HTTPServer {
         // clone so we can move it to the new runtime
         let db_rpl = db.clone();
         let raw_position_list = raw_position_list.map(move |a: String| -> Json {
-            match (*db_rpl)
-                .db
-                .read(|db| -> Result<Vec<String>> { Ok(db.get_position_symbols(&a)?) })
-            {
-                Ok(Ok(val)) => json(&val),
-                Ok(Err(e)) => json(&ErrorReply::new(format!(
-                    "Error getting position list. Error: {}",
-                    e
-                ))),
+            match db_rpl.get_position_symbols(&a) {
+                Ok(val) => json(&val),
                 Err(e) => json(&ErrorReply::new(format!(
                     "Error getting position list. Error: {}",
                     e
@@ -198,53 +408,58 @@ impl HTTPServer {
 
         // clone so we can move it to the new runtime
         let db_rplatest = db.clone();
-        let raw_position_latest = raw_position_latest.map(move |a: String, b: String| -> Json {
-            match (*db_rplatest)
-                .db
-                .read(|db| -> Result<DBInfoAccountPosition> {
-                    Ok(db.get_latest_position(&a, &b, Local::today().naive_local())?)
-                }) {
-                Ok(Ok(val)) => json(&val),
-                Ok(Err(e)) => json(&ErrorReply::new(format!(
-                    "Error getting position list. Error: {}",
-                    e
-                ))),
-                Err(e) => json(&ErrorReply::new(format!(
-                    "Error getting position list. Error: {}",
-                    e
-                ))),
-            }
-        });
+        let raw_position_latest =
+            raw_position_latest.map(move |a: String, b: String, q: ConvertQuery| -> Json {
+                let date = Local::today().naive_local();
+                let converter = match parse_converter(&*db_rplatest, q.convert, date, date) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                match db_rplatest.get_latest_position(&a, &b, date) {
+                    Ok(mut val) => {
+                        if let Some(c) = converter {
+                            c.position(&mut val, date);
+                        }
+                        json(&PositionReply::new(&*db_rplatest, val))
+                    }
+                    Err(e) => json(&ErrorReply::new(format!(
+                        "Error getting position list. Error: {}",
+                        e
+                    ))),
+                }
+            });
 
         // clone so we can move it to the new runtime
         let db_rpdlatest = db.clone();
-        let raw_position_date_latest =
-            raw_position_date_latest.map(move |a: String, b: String, c: String| -> Json {
+        let raw_position_date_latest = raw_position_date_latest.map(
+            move |a: String, b: String, c: String, q: ConvertQuery| -> Json {
                 let date = match parse_date(c) {
                     Ok(d) => d,
                     Err(e) => return e,
                 };
-                match (*db_rpdlatest)
-                    .db
-                    .read(|db| -> Result<DBInfoAccountPosition> {
-                        Ok(db.get_latest_position(&a, &b, date)?)
-                    }) {
-                    Ok(Ok(val)) => json(&val),
-                    Ok(Err(e)) => json(&ErrorReply::new(format!(
-                        "Error getting position list. Error: {}",
-                        e
-                    ))),
+                let converter = match parse_converter(&*db_rpdlatest, q.convert, date, date) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                match db_rpdlatest.get_latest_position(&a, &b, date) {
+                    Ok(mut val) => {
+                        if let Some(c) = converter {
+                            c.position(&mut val, date);
+                        }
+                        json(&PositionReply::new(&*db_rpdlatest, val))
+                    }
                     Err(e) => json(&ErrorReply::new(format!(
                         "Error getting position list. Error: {}",
                         e
                     ))),
                 }
-            });
+            },
+        );
 
         // clone so we can move it to the new runtime
         let db_rpdtime = db.clone();
-        let raw_position_date_time =
-            raw_position_date_time.map(move |a: String, b: String, c: String, d: String| -> Json {
+        let raw_position_date_time = raw_position_date_time.map(
+            move |a: String, b: String, c: String, d: String, q: ConvertQuery| -> Json {
                 let date = match parse_date(c) {
                     Ok(d) => d,
                     Err(e) => return e,
@@ -253,28 +468,136 @@ impl HTTPServer {
                     Ok(t) => t,
                     Err(e) => return e,
                 };
-                match (*db_rpdtime)
-                    .db
-                    .read(|db| -> Result<DBInfoAccountPosition> {
-                        Ok(db.get_closest_position(&a, &b, date, time)?)
-                    }) {
-                    Ok(Ok(val)) => json(&val),
-                    Ok(Err(e)) => json(&ErrorReply::new(format!(
+                let converter = match parse_converter(&*db_rpdtime, q.convert, date, date) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                match db_rpdtime.get_closest_position(&a, &b, date, time) {
+                    Ok(mut val) => {
+                        if let Some(c) = converter {
+                            c.position(&mut val, date);
+                        }
+                        json(&PositionReply::new(&*db_rpdtime, val))
+                    }
+                    Err(e) => json(&ErrorReply::new(format!(
                         "Error getting position list. Error: {}",
                         e
                     ))),
+                }
+            },
+        );
+
+        // clone so we can move it to the new runtime
+        let db_rpr = db.clone();
+        let raw_position_range =
+            raw_position_range.map(move |a: String, b: String, q: RangeQuery| -> Json {
+                let (from, to) = match parse_date_range(q.from, q.to) {
+                    Ok(r) => r,
+                    Err(e) => return e,
+                };
+                let interval = match parse_interval(q.interval) {
+                    Ok(i) => i,
+                    Err(e) => return e,
+                };
+                let converter = match parse_converter(&*db_rpr, q.convert, from, to) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                match db_rpr.get_position_days(&a, &b, from, to) {
+                    Ok(mut val) => {
+                        // convert every snapshot first so each one uses the rate of its time.
+                        if let Some(c) = converter {
+                            c.position_days(&mut val);
+                        }
+                        json(&downsample_positions(&val, interval))
+                    }
                     Err(e) => json(&ErrorReply::new(format!(
-                        "Error getting position list. Error: {}",
+                        "Error getting position range. Error: {}",
                         e
                     ))),
                 }
             });
 
+        // clone so we can move it to the new runtime
+        let db_rql = db.clone();
+        let raw_quote_list = raw_quote_list.map(move || -> Json {
+            match db_rql.get_quote_symbols() {
+                Ok(val) => json(&val),
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting quote list. Error: {}",
+                    e
+                ))),
+            }
+        });
+
+        // clone so we can move it to the new runtime
+        let db_rqlatest = db.clone();
+        let raw_quote_latest = raw_quote_latest.map(move |a: String| -> Json {
+            match db_rqlatest.get_latest_quote(&a, Local::today().naive_local()) {
+                Ok(val) => json(&val),
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting latest quote. Error: {}",
+                    e
+                ))),
+            }
+        });
+
+        // clone so we can move it to the new runtime
+        let db_rqr = db.clone();
+        let raw_quote_range = raw_quote_range.map(move |a: String, q: QuoteRangeQuery| -> Json {
+            let (from, to) = match parse_date_range(q.from, q.to) {
+                Ok(r) => r,
+                Err(e) => return e,
+            };
+            let interval = match parse_interval(q.interval) {
+                Ok(i) => i,
+                Err(e) => return e,
+            };
+            match db_rqr.get_quote_days(&a, from, to) {
+                Ok(val) => json(&downsample_quotes(&val, interval)),
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting quote range. Error: {}",
+                    e
+                ))),
+            }
+        });
+
+        // clone so we can move it to the new runtime
+        let db_rsl = db.clone();
+        let raw_symbol_list = raw_symbol_list.map(move || -> Json {
+            match db_rsl.get_symbols() {
+                Ok(mut val) => {
+                    val.sort_unstable_by(|a, b| a.symbol.cmp(&b.symbol));
+                    json(&val)
+                }
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting symbol list. Error: {}",
+                    e
+                ))),
+            }
+        });
+
+        // clone so we can move it to the new runtime
+        let db_rsi = db.clone();
+        let raw_symbol_info = raw_symbol_info.map(move |a: String| -> Json {
+            match db_rsi.get_symbol(&a) {
+                Ok(val) => json(&val),
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting symbol info. Error: {}",
+                    e
+                ))),
+            }
+        });
+
         // clone so we can move it to the new runtime
         let db_rbdt = db.clone();
         // and now we format our actual response.
-        let raw_balance_date_time =
-            raw_balance_date_time.map(move |a: String, b: String, c: String| -> Json {
+        let raw_balance_date_time = raw_balance_date_time.map(
+            move |a: String, b: String, c: String, q: CurrencyQuery| -> Json {
+                let kind = match parse_balance_kind(q.currency, default_kind) {
+                    Ok(k) => k,
+                    Err(e) => return e,
+                };
                 let date = match parse_date(b) {
                     Ok(d) => d,
                     Err(e) => return e,
@@ -283,38 +606,49 @@ impl HTTPServer {
                     Ok(t) => t,
                     Err(e) => return e,
                 };
-                match (*db_rbdt).db.read(|db| -> Result<DBInfoAccountBalance> {
-                    Ok(db.get_closest_balance(&a, date, time)?)
-                }) {
-                    Ok(Ok(val)) => json(&val),
-                    Ok(Err(e)) => json(&ErrorReply::new(format!(
-                        "Error getting latest balance. Error: {}",
-                        e
-                    ))),
+                let converter = match parse_converter(&*db_rbdt, q.convert, date, date) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                match db_rbdt.get_closest_balance(&a, kind, date, time) {
+                    Ok(mut val) => {
+                        if let Some(c) = converter {
+                            c.balance(&mut val, date);
+                        }
+                        json(&val)
+                    }
                     Err(e) => json(&ErrorReply::new(format!(
                         "Error getting latest balance. Error: {}",
                         e
                     ))),
                 }
-            });
+            },
+        );
 
         // clone so we can move it to the new runtime
         let db_rbld = db.clone();
         // and now we format our actual response.
         let raw_balance_latest_date =
-            raw_balance_latest_date.map(move |a: String, b: String| -> Json {
+            raw_balance_latest_date.map(move |a: String, b: String, q: CurrencyQuery| -> Json {
+                let kind = match parse_balance_kind(q.currency, default_kind) {
+                    Ok(k) => k,
+                    Err(e) => return e,
+                };
                 let date = match parse_date(b) {
                     Ok(d) => d,
                     Err(e) => return e,
                 };
-                match (*db_rbld).db.read(|db| -> Result<DBInfoAccountBalance> {
-                    Ok(db.get_latest_balance(&a, date)?)
-                }) {
-                    Ok(Ok(val)) => json(&val),
-                    Ok(Err(e)) => json(&ErrorReply::new(format!(
-                        "Error getting latest balance. Error: {}",
-                        e
-                    ))),
+                let converter = match parse_converter(&*db_rbld, q.convert, date, date) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                match db_rbld.get_latest_balance(&a, kind, date) {
+                    Ok(mut val) => {
+                        if let Some(c) = converter {
+                            c.balance(&mut val, date);
+                        }
+                        json(&val)
+                    }
                     Err(e) => json(&ErrorReply::new(format!(
                         "Error getting latest balance. Error: {}",
                         e
@@ -325,19 +659,87 @@ impl HTTPServer {
         // clone so we can move it to the new runtime
         let db_rbl = db.clone();
         // and now we format our actual response.
-        let raw_balance_latest =
-            raw_balance_latest.map(move |a: String| -> warp::reply::WithStatus<Json> {
-                match (*db_rbl).db.read(|db| -> Result<DBInfoAccountBalance> {
-                    Ok(db.get_latest_balance(&a, Local::today().naive_local())?)
-                }) {
-                    Ok(Ok(val)) => with_status(json(&val), StatusCode::OK),
-                    Ok(Err(e)) => with_status(
+        let raw_balance_latest = raw_balance_latest.map(
+            move |a: String, q: CurrencyQuery| -> warp::reply::WithStatus<Json> {
+                let kind = match parse_balance_kind(q.currency, default_kind) {
+                    Ok(k) => k,
+                    Err(e) => return with_status(e, StatusCode::BAD_REQUEST),
+                };
+                let date = Local::today().naive_local();
+                let converter = match parse_converter(&*db_rbl, q.convert, date, date) {
+                    Ok(c) => c,
+                    Err(e) => return with_status(e, StatusCode::BAD_REQUEST),
+                };
+                match db_rbl.get_latest_balance(&a, kind, date) {
+                    Ok(mut val) => {
+                        if let Some(c) = converter {
+                            c.balance(&mut val, date);
+                        }
+                        with_status(json(&val), StatusCode::OK)
+                    }
+                    Err(e) => with_status(
                         json(&ErrorReply::new(format!(
                             "Error getting latest balance. Error: {}",
                             e
                         ))),
                         StatusCode::NOT_FOUND,
                     ),
+                }
+            },
+        );
+
+        // clone so we can move it to the new runtime
+        let db_rbsd = db.clone();
+        // and now we format our actual response.
+        let raw_balance_sod_date =
+            raw_balance_sod_date.map(move |a: String, b: String, q: CurrencyQuery| -> Json {
+                let kind = match parse_balance_kind(q.currency, default_kind) {
+                    Ok(k) => k,
+                    Err(e) => return e,
+                };
+                let date = match parse_date(b) {
+                    Ok(d) => d,
+                    Err(e) => return e,
+                };
+                let converter = match parse_converter(&*db_rbsd, q.convert, date, date) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                match db_rbsd.get_start_of_day_balance(&a, kind, date) {
+                    Ok(mut val) => {
+                        if let Some(c) = converter {
+                            c.balance(&mut val, date);
+                        }
+                        json(&val)
+                    }
+                    Err(e) => json(&ErrorReply::new(format!(
+                        "Error getting latest balance. Error: {}",
+                        e
+                    ))),
+                }
+            });
+
+        // clone so we can move it to the new runtime
+        let db_rbs = db.clone();
+        // and now we format our actual response.
+        let raw_balance_sod = raw_balance_sod.map(
+            move |a: String, q: CurrencyQuery| -> warp::reply::WithStatus<Json> {
+                let kind = match parse_balance_kind(q.currency, default_kind) {
+                    Ok(k) => k,
+                    Err(e) => return with_status(e, StatusCode::BAD_REQUEST),
+                };
+                let date = Local::today().naive_local();
+                let converter = match parse_converter(&*db_rbs, q.convert, date, date) {
+                    Ok(c) => c,
+                    Err(e) => return with_status(e, StatusCode::BAD_REQUEST),
+                };
+                match db_rbs.get_start_of_day_balance(&a, kind, date) {
+                    Ok(mut val) => {
+                        if let Some(c) = converter {
+                            c.balance(&mut val, date);
+                        }
+                        with_status(json(&val), StatusCode::OK)
+                    }
                     Err(e) => with_status(
                         json(&ErrorReply::new(format!(
                             "Error getting latest balance. Error: {}",
@@ -346,72 +748,568 @@ impl HTTPServer {
                         StatusCode::NOT_FOUND,
                     ),
                 }
-            });
+            },
+        );
 
         // clone so we can move it to the new runtime
-        let db_rbsd = db.clone();
-        // and now we format our actual response.
-        let raw_balance_sod_date = raw_balance_sod_date.map(move |a: String, b: String| -> Json {
-            let date = match parse_date(b) {
-                Ok(d) => d,
+        let db_rbr = db.clone();
+        let raw_balance_range = raw_balance_range.map(move |a: String, q: RangeQuery| -> Json {
+            let (from, to) = match parse_date_range(q.from, q.to) {
+                Ok(r) => r,
+                Err(e) => return e,
+            };
+            let interval = match parse_interval(q.interval) {
+                Ok(i) => i,
+                Err(e) => return e,
+            };
+            let kind = match parse_balance_kind(q.currency, default_kind) {
+                Ok(k) => k,
                 Err(e) => return e,
             };
-            match (*db_rbsd).db.read(|db| -> Result<DBInfoAccountBalance> {
-                Ok(db.get_start_of_day_balance(&a, date)?)
-            }) {
-                Ok(Ok(val)) => json(&val),
-                Ok(Err(e)) => json(&ErrorReply::new(format!(
-                    "Error getting latest balance. Error: {}",
+            let converter = match parse_converter(&*db_rbr, q.convert, from, to) {
+                Ok(c) => c,
+                Err(e) => return e,
+            };
+            match db_rbr.get_balance_days(&a, kind, from, to) {
+                Ok(mut val) => {
+                    // convert every snapshot first so each one uses the rate of its time.
+                    if let Some(c) = converter {
+                        c.balance_days(&mut val);
+                    }
+                    json(&downsample_balances(&val, interval))
+                }
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting balance range. Error: {}",
                     e
                 ))),
+            }
+        });
+
+        // clone so we can move it to the new runtime
+        let db_rol = db.clone();
+        let raw_order_list = raw_order_list.map(move |a: String, q: DateRangeQuery| -> Json {
+            let (from, to) = match parse_date_range(q.from, q.to) {
+                Ok(r) => r,
+                Err(e) => return e,
+            };
+            let converter = match parse_converter(&*db_rol, q.convert, from, to) {
+                Ok(c) => c,
+                Err(e) => return e,
+            };
+            match db_rol.get_orders(&a, from, to) {
+                Ok(mut val) => {
+                    if let Some(c) = converter {
+                        val.iter_mut().for_each(|o| c.order(o));
+                    }
+                    json(&val)
+                }
                 Err(e) => json(&ErrorReply::new(format!(
-                    "Error getting latest balance. Error: {}",
+                    "Error getting order list. Error: {}",
                     e
                 ))),
             }
         });
 
         // clone so we can move it to the new runtime
-        let db_rbs = db.clone();
-        // and now we format our actual response.
-        let raw_balance_sod =
-            raw_balance_sod.map(move |a: String| -> warp::reply::WithStatus<Json> {
-                match (*db_rbs).db.read(|db| -> Result<DBInfoAccountBalance> {
-                    Ok(db.get_start_of_day_balance(&a, Local::today().naive_local())?)
+        let db_roi = db.clone();
+        let raw_order_id = raw_order_id.map(move |a: String, b: u32, q: ConvertQuery| -> Json {
+            match db_roi.get_order(&a, b) {
+                Ok(mut val) => {
+                    // we only know which day of rates we need once we have the order.
+                    let date = val.creation_time.date().naive_local();
+                    match parse_converter(&*db_roi, q.convert, date, date) {
+                        Ok(Some(c)) => c.order(&mut val),
+                        Ok(None) => {}
+                        Err(e) => return e,
+                    }
+                    json(&val)
+                }
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting order. Error: {}",
+                    e
+                ))),
+            }
+        });
+
+        // clone so we can move it to the new runtime
+        let db_rel = db.clone();
+        let raw_execution_list =
+            raw_execution_list.map(move |a: String, q: DateRangeQuery| -> Json {
+                let (from, to) = match parse_date_range(q.from, q.to) {
+                    Ok(r) => r,
+                    Err(e) => return e,
+                };
+                let converter = match parse_converter(&*db_rel, q.convert, from, to) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                match db_rel.get_executions(&a, from, to) {
+                    Ok(mut val) => {
+                        if let Some(c) = converter {
+                            val.iter_mut().for_each(|ex| c.execution(ex));
+                        }
+                        json(&val)
+                    }
+                    Err(e) => json(&ErrorReply::new(format!(
+                        "Error getting execution list. Error: {}",
+                        e
+                    ))),
+                }
+            });
+
+        // clone so we can move it to the new runtime
+        let db_ra = db.clone();
+        let raw_activity = raw_activity.map(move |a: String, q: ActivityQuery| -> Json {
+            let (from, to) = match parse_date_range(q.from, q.to) {
+                Ok(r) => r,
+                Err(e) => return e,
+            };
+            let activity_type = match q.activity_type.map(parse_activity_type).transpose() {
+                Ok(at) => at,
+                Err(e) => return e,
+            };
+            let converter = match parse_converter(&*db_ra, q.convert, from, to) {
+                Ok(c) => c,
+                Err(e) => return e,
+            };
+            match db_ra.get_activities(&a, from, to, activity_type) {
+                Ok(mut val) => {
+                    if let Some(c) = converter {
+                        val.iter_mut().for_each(|act| c.activity(act));
+                    }
+                    json(&val)
+                }
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting activity list. Error: {}",
+                    e
+                ))),
+            }
+        });
+
+        // portfolio group apis.
+        // clone so we can move it to the new runtime
+        let db_bgs = db.clone();
+        let pc_bgs = portfolio_config.clone();
+        let raw_balance_group_sod =
+            raw_balance_group_sod.map(move |g: String, q: CurrencyQuery| -> Json {
+                let kind = match parse_balance_kind(q.currency, default_kind) {
+                    Ok(k) => k,
+                    Err(e) => return e,
+                };
+                let currency = match parse_convert(q.convert) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                // the rate from the last sync is closer to the balances than the one in the config.
+                let config = pc_bgs.for_request(latest_usd_cad(&*db_bgs), currency.as_ref());
+                let date = Local::today().naive_local();
+                match group_balance(&config, &g, |a| {
+                    db_bgs.get_start_of_day_balance(a, kind, date)
                 }) {
-                    Ok(Ok(val)) => with_status(json(&val), StatusCode::OK),
-                    Ok(Err(e)) => with_status(
-                        json(&ErrorReply::new(format!(
-                            "Error getting latest balance. Error: {}",
+                    Ok(val) => json(&val),
+                    Err(e) => json(&ErrorReply::new(format!(
+                        "Error getting group balance. Error: {}",
+                        e
+                    ))),
+                }
+            });
+
+        // clone so we can move it to the new runtime
+        let db_bgl = db.clone();
+        let pc_bgl = portfolio_config.clone();
+        let raw_balance_group_latest =
+            raw_balance_group_latest.map(move |g: String, q: CurrencyQuery| -> Json {
+                let kind = match parse_balance_kind(q.currency, default_kind) {
+                    Ok(k) => k,
+                    Err(e) => return e,
+                };
+                let currency = match parse_convert(q.convert) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                // the rate from the last sync is closer to the balances than the one in the config.
+                let config = pc_bgl.for_request(latest_usd_cad(&*db_bgl), currency.as_ref());
+                let date = Local::today().naive_local();
+                match group_balance(&config, &g, |a| db_bgl.get_latest_balance(a, kind, date)) {
+                    Ok(val) => json(&val),
+                    Err(e) => json(&ErrorReply::new(format!(
+                        "Error getting group balance. Error: {}",
+                        e
+                    ))),
+                }
+            });
+
+        // clone so we can move it to the new runtime
+        let db_pgl = db.clone();
+        let pc_pgl = portfolio_config.clone();
+        let raw_position_group_list =
+            raw_position_group_list.map(move |g: String, q: ConvertQuery| -> Json {
+                let currency = match parse_convert(q.convert) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                let config = pc_pgl.for_request(latest_usd_cad(&*db_pgl), currency.as_ref());
+                match group_positions(&*db_pgl, &config, &g, Local::today().naive_local()) {
+                    Ok(val) => json(&val),
+                    Err(e) => json(&ErrorReply::new(format!(
+                        "Error getting group positions. Error: {}",
+                        e
+                    ))),
+                }
+            });
+
+        //  the analytics json api
+        let analytics = warp::path("analytics");
+        let analytics_returns = analytics
+            .and(warp::path!(String / "returns"))
+            .and(warp::path::end())
+            .and(warp::query::<ReturnsQuery>());
+
+        // clone so we can move it to the new runtime
+        let db_ar = db.clone();
+        let analytics_returns = analytics_returns.map(move |a: String, q: ReturnsQuery| -> Json {
+            let (from, to) = match parse_date_range(q.from, q.to) {
+                Ok(r) => r,
+                Err(e) => return e,
+            };
+            let method = match parse_return_method(q.method) {
+                Ok(m) => m,
+                Err(e) => return e,
+            };
+            let kind = match parse_balance_kind(q.currency, default_kind) {
+                Ok(k) => k,
+                Err(e) => return e,
+            };
+            match compute_returns(&*db_ar, &a, kind, from, to, method) {
+                Ok(val) => json(&val),
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting returns. Error: {}",
+                    e
+                ))),
+            }
+        });
+        let analytics_risk = analytics
+            .and(warp::path!(String / "risk"))
+            .and(warp::path::end())
+            .and(warp::query::<RiskQuery>());
+
+        // clone so we can move it to the new runtime
+        let db_ak = db.clone();
+        let risk_free_rate = analytics_config.risk_free_rate;
+        let analytics_risk = analytics_risk.map(move |a: String, q: RiskQuery| -> Json {
+            let (from, to) = match parse_trailing_date_range(q.from, q.to, RISK_DEFAULT_DAYS) {
+                Ok(r) => r,
+                Err(e) => return e,
+            };
+            let kind = match parse_balance_kind(q.currency, default_kind) {
+                Ok(k) => k,
+                Err(e) => return e,
+            };
+            match compute_risk(&*db_ak, &a, kind, from, to, risk_free_rate) {
+                Ok(val) => json(&val),
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting risk metrics. Error: {}",
+                    e
+                ))),
+            }
+        });
+        let analytics_allocation = analytics
+            .and(warp::path!(String / "allocation"))
+            .and(warp::path::end())
+            .and(warp::query::<AllocationQuery>());
+
+        // clone so we can move it to the new runtime
+        let db_aa = db.clone();
+        let ac_aa = analytics_config.clone();
+        let pc_aa = portfolio_config.clone();
+        let analytics_allocation =
+            analytics_allocation.map(move |a: String, q: AllocationQuery| -> Json {
+                let currency = match parse_convert(q.convert) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                let target = match ac_aa.get_target(&*db_aa, &a) {
+                    Ok(t) => t,
+                    Err(e) => {
+                        return json(&ErrorReply::new(format!(
+                            "Error getting allocation. Error: {}",
                             e
-                        ))),
-                        StatusCode::NOT_FOUND,
-                    ),
-                    Err(e) => with_status(
-                        json(&ErrorReply::new(format!(
-                            "Error getting latest balance. Error: {}",
+                        )))
+                    }
+                };
+                // the rate from the last sync is closer to the prices than the one in the config.
+                let config = pc_aa.for_request(latest_usd_cad(&*db_aa), currency.as_ref());
+                let date = Local::today().naive_local();
+                match compute_allocation(&*db_aa, &config, target, currency.as_ref(), date) {
+                    Ok(val) => json(&val),
+                    Err(e) => json(&ErrorReply::new(format!(
+                        "Error getting allocation. Error: {}",
+                        e
+                    ))),
+                }
+            });
+
+        let analytics_dividends = analytics
+            .and(warp::path!(String / "dividends"))
+            .and(warp::path::end())
+            .and(warp::query::<DividendQuery>());
+
+        // clone so we can move it to the new runtime
+        let db_ad = db.clone();
+        let pc_ad = portfolio_config.clone();
+        let analytics_dividends =
+            analytics_dividends.map(move |a: String, q: DividendQuery| -> Json {
+                let today = Local::today().naive_local();
+                let year_start = format!("{}-01-01", today.year());
+                let (from, to) = match parse_date_range(q.from.or(Some(year_start)), q.to) {
+                    Ok(r) => r,
+                    Err(e) => return e,
+                };
+                let currency = match parse_convert(q.convert) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                };
+                let config = pc_ad.for_request(latest_usd_cad(&*db_ad), None);
+                match dividend_report(&*db_ad, &config, &a, currency.as_ref(), from, to, today) {
+                    Ok(val) => json(&val),
+                    Err(e) => json(&ErrorReply::new(format!(
+                        "Error getting dividends. Error: {}",
+                        e
+                    ))),
+                }
+            });
+
+        let analytics_contributions = analytics
+            .and(warp::path("contributions"))
+            .and(warp::path::end())
+            .and(warp::query::<ContributionQuery>());
+
+        // clone so we can move it to the new runtime
+        let db_ac = db.clone();
+        let ac_ac = analytics_config.clone();
+        let usd_cad_rate = portfolio_config.usd_cad_rate;
+        let analytics_contributions =
+            analytics_contributions.map(move |q: ContributionQuery| -> Json {
+                let today = Local::today().naive_local();
+                let year = match q.year.map(|y| parse_year(&y)).transpose() {
+                    Ok(y) => y.unwrap_or_else(|| today.year()),
+                    Err(e) => {
+                        return json(&ErrorReply::new(format!(
+                            "Error getting contributions. Error: {}",
                             e
-                        ))),
-                        StatusCode::NOT_FOUND,
-                    ),
+                        )))
+                    }
+                };
+                match compute_contributions(&*db_ac, &ac_ac, usd_cad_rate, year, today) {
+                    Ok(val) => json(&val),
+                    Err(e) => json(&ErrorReply::new(format!(
+                        "Error getting contributions. Error: {}",
+                        e
+                    ))),
                 }
             });
 
+        //  the alerts api, the history of every alert that fired.
+        let alerts = warp::path("alerts")
+            .and(warp::path::end())
+            .and(warp::query::<AlertQuery>());
+
+        // clone so we can move it to the new runtime
+        let db_al = db.clone();
+        let alerts = alerts.map(move |q: AlertQuery| -> Json {
+            let (from, to) = match parse_date_range(q.from, q.to) {
+                Ok(r) => r,
+                Err(e) => return e,
+            };
+            let account = match q.account.map(|a| db_al.get_account_info(&a)).transpose() {
+                Ok(a) => a.map(|a| a.number),
+                Err(e) => {
+                    return json(&ErrorReply::new(format!(
+                        "Error getting alerts. Error: {}",
+                        e
+                    )))
+                }
+            };
+            match db_al.get_alerts(from, to) {
+                Ok(mut val) => {
+                    if let Some(number) = account {
+                        val.retain(|al| al.account == number);
+                    }
+                    json(&val)
+                }
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting alerts. Error: {}",
+                    e
+                ))),
+            }
+        });
+
+        //  the reports api, the end of day reports that have been saved.
+        let reports = warp::path!("reports" / String).and(warp::query::<ReportQuery>());
+
+        let reports_dir = reports_config.directory.clone();
+        let reports = reports.map(move |d: String, q: ReportQuery| -> Box<dyn warp::Reply> {
+            let date = match parse_date(d) {
+                Ok(d) => d,
+                Err(e) => return Box::new(e),
+            };
+            let format = match ReportFormat::parse(&q.format.unwrap_or_else(|| "html".to_string()))
+            {
+                Ok(f) => f,
+                Err(e) => {
+                    return Box::new(json(&ErrorReply::new(format!(
+                        "Error getting report. Error: {}",
+                        e
+                    ))))
+                }
+            };
+            match read_report(&reports_dir, date, format) {
+                Ok(report) => Box::new(warp::reply::with_header(
+                    report,
+                    "content-type",
+                    format.content_type(),
+                )),
+                Err(e) => Box::new(json(&ErrorReply::new(format!(
+                    "Error getting report. Error: {}",
+                    e
+                )))),
+            }
+        });
+
+        //  the tax api, worked out from every trade stored each time it is asked for.
+        let tax = warp::path("tax");
+        let tax_gains = tax
+            .and(warp::path!(String / "capital-gains"))
+            .and(warp::path::end())
+            .and(warp::query::<TaxQuery>());
+
+        // clone so we can move it to the new runtime
+        let db_tg = db.clone();
+        let usd_cad_rate = portfolio_config.usd_cad_rate;
+        let tax_gains = tax_gains.map(move |y: String, q: TaxQuery| -> Box<dyn warp::Reply> {
+            let error = |e: Box<dyn error::Error>| -> Box<dyn warp::Reply> {
+                Box::new(json(&ErrorReply::new(format!(
+                    "Error getting capital gains. Error: {}",
+                    e
+                ))))
+            };
+            let year = match parse_year(&y) {
+                Ok(y) => y,
+                Err(e) => return error(e),
+            };
+            let format = q.format.unwrap_or_else(|| "json".to_string());
+            let format = match ExportFormat::parse(&format) {
+                Some(f) => f,
+                None => {
+                    return Box::new(json(&ErrorReply::new(format!(
+                        "Error parsing format: {}. Expected json, csv, or ndjson.",
+                        format
+                    ))))
+                }
+            };
+            let report = match build_ledger(&*db_tg, usd_cad_rate, Local::today().naive_local()) {
+                Ok(ledger) => ledger.capital_gains(year),
+                Err(e) => return error(e),
+            };
+            if format == ExportFormat::Json {
+                return Box::new(json(&report));
+            }
+            // the other formats only have room for the sales, the totals are left for the json.
+            let mut body: Vec<u8> = Vec::new();
+            match write_rows(&mut body, format, &report.dispositions) {
+                Ok(()) => Box::new(warp::reply::with_header(
+                    body,
+                    "content-type",
+                    format.content_type(),
+                )),
+                Err(e) => error(e),
+            }
+        });
+        let tax_acb = tax.and(warp::path("acb")).and(warp::path::end());
+
+        // clone so we can move it to the new runtime
+        let db_ta = db.clone();
+        let tax_acb = tax_acb.map(move || -> Json {
+            match build_ledger(&*db_ta, usd_cad_rate, Local::today().naive_local()) {
+                Ok(ledger) => json(&ledger.positions),
+                Err(e) => json(&ErrorReply::new(format!(
+                    "Error getting adjusted cost bases. Error: {}",
+                    e
+                ))),
+            }
+        });
+
+        //  the stream api, a WebSocket if the client asks to upgrade and Server-Sent Events if not.
+        let stream = warp::path("stream").and(warp::path::end());
+        let db_ws = db.clone();
+        let events_ws = events.clone();
+        let stream_ws = stream
+            .and(warp::ws())
+            .and(warp::query::<StreamQuery>())
+            .map(
+                move |ws: warp::ws::Ws, q: StreamQuery| -> Box<dyn warp::Reply> {
+                    match Subscription::parse(&*db_ws, q.account, q.symbol, q.event_type) {
+                        Ok(sub) => Box::new(ws_reply(ws, &events_ws, sub)),
+                        Err(e) => Box::new(with_status(e, StatusCode::BAD_REQUEST)),
+                    }
+                },
+            );
+        let db_sse = db.clone();
+        let stream_sse = stream.and(warp::query::<StreamQuery>()).map(
+            move |q: StreamQuery| -> Box<dyn warp::Reply> {
+                match Subscription::parse(&*db_sse, q.account, q.symbol, q.event_type) {
+                    Ok(sub) => Box::new(sse_reply(&events, sub)),
+                    Err(e) => Box::new(with_status(e, StatusCode::BAD_REQUEST)),
+                }
+            },
+        );
+
         // combine up the baic methods.
-        let raw = raw_account_list
+        // the group paths go first so a group is never read as an account identifier.
+        let raw = raw_balance_group_sod
+            .or(raw_balance_group_latest)
+            .or(raw_position_group_list)
+            .or(raw_account_list)
             .or(raw_account_info)
             .or(raw_balance_sod)
             .or(raw_balance_sod_date)
             .or(raw_balance_latest)
             .or(raw_balance_latest_date)
             .or(raw_balance_date_time)
+            .or(raw_balance_range)
             .or(raw_position_list)
             .or(raw_position_latest)
             .or(raw_position_date_latest)
-            .or(raw_position_date_time);
+            .or(raw_position_date_time)
+            .or(raw_position_range)
+            .or(raw_quote_list)
+            .or(raw_quote_latest)
+            .or(raw_quote_range)
+            .or(raw_symbol_list)
+            .or(raw_symbol_info)
+            .or(raw_order_list)
+            .or(raw_order_id)
+            .or(raw_execution_list)
+            .or(raw_activity);
 
         // combine her up.
-        let routes = warp::get().and(raw.or(statusbar).or(any)).with(log);
+        let routes = warp::get()
+            .and(
+                raw.or(analytics_returns)
+                    .or(analytics_risk)
+                    .or(analytics_allocation)
+                    .or(analytics_dividends)
+                    .or(analytics_contributions)
+                    .or(tax_acb)
+                    .or(tax_gains)
+                    .or(alerts)
+                    .or(reports)
+                    .or(stream_ws)
+                    .or(stream_sse)
+                    .or(statusbar)
+                    .or(any),
+            )
+            .with(log);
         // print it out babyyy.
         info!("Starting HTTP server @ [{}:{}]...", addr, port);
         // here is the actual start of the server..
@@ -0,0 +1,195 @@
+//! Module to push new snapshots out to clients as soon as they are synced.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::{
+        include::{
+            json, ready, select, to_json_string, tokio, warn,
+            warp::{
+                sse,
+                ws::{Message, WebSocket, Ws},
+                Reply,
+            },
+            AccountNumber, FutureExt, Infallible, Json, NaiveDate, Serialize, SinkExt, StreamExt,
+        },
+        storage::{BalanceKind, DBInfoAccountBalance, DBInfoAccountPosition, Storage},
+    },
+    ErrorReply,
+};
+use tokio::sync::broadcast::{self, RecvError};
+
+/// How many events can be waiting on a slow client before it starts missing them.
+const EVENT_BUFFER: usize = 256;
+
+/// The sending half Monitor pushes events into, every client gets its own receiver.
+pub type EventSender = broadcast::Sender<SyncEvent>;
+
+/// Makes the channel shared between Monitor and the http server.
+pub fn event_channel() -> EventSender {
+    let (sender, _) = broadcast::channel(EVENT_BUFFER);
+    sender
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+/// A snapshot that was just stored by a sync.
+pub enum SyncEvent {
+    Balance {
+        account: AccountNumber,
+        date: NaiveDate,
+        kind: BalanceKind,
+        balance: DBInfoAccountBalance,
+    },
+    Position {
+        account: AccountNumber,
+        date: NaiveDate,
+        position: DBInfoAccountPosition,
+    },
+}
+
+impl SyncEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Balance { .. } => "balance",
+            Self::Position { .. } => "position",
+        }
+    }
+    fn account(&self) -> &str {
+        match self {
+            Self::Balance { account, .. } | Self::Position { account, .. } => account,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Which events a client asked for, an empty list lets everything through.
+pub struct Subscription {
+    accounts: Vec<AccountNumber>,
+    symbols: Vec<String>,
+    types: Vec<String>,
+}
+
+// splits a comma seperated list out of a query parameter.
+fn split_list(list: Option<String>) -> Vec<String> {
+    list.map(|l| {
+        l.split(',')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+impl Subscription {
+    /// Builds a subscription from the query parameters, accounts can be given by name or number
+    /// so we look them up once here instead of on every event.
+    pub fn parse(
+        db: &dyn Storage,
+        accounts: Option<String>,
+        symbols: Option<String>,
+        types: Option<String>,
+    ) -> Result<Self, Json> {
+        let mut account_numbers = Vec::new();
+        for account in split_list(accounts).iter() {
+            match db.get_account_info(account) {
+                Ok(info) => account_numbers.push(info.number),
+                Err(e) => {
+                    return Err(json(&ErrorReply::new(format!(
+                        "Error subscribing to account. Error: {}",
+                        e
+                    ))))
+                }
+            }
+        }
+        let types: Vec<String> = split_list(types).iter().map(|t| t.to_lowercase()).collect();
+        if let Some(t) = types.iter().find(|t| *t != "balance" && *t != "position") {
+            return Err(json(&ErrorReply::new(format!(
+                "Could not parse event type: {}. Expected balance or position.",
+                t
+            ))));
+        }
+        Ok(Self {
+            accounts: account_numbers,
+            symbols: split_list(symbols),
+            types,
+        })
+    }
+    /// Asking for symbols only makes sense for positions, so balances are left out.
+    pub fn wants(&self, event: &SyncEvent) -> bool {
+        let symbol_matches = match event {
+            SyncEvent::Balance { .. } => self.symbols.is_empty(),
+            SyncEvent::Position { position, .. } => {
+                self.symbols.is_empty() || self.symbols.contains(&position.symbol)
+            }
+        };
+        symbol_matches
+            && (self.accounts.is_empty() || self.accounts.iter().any(|a| a == event.account()))
+            && (self.types.is_empty() || self.types.iter().any(|t| t == event.name()))
+    }
+}
+
+/// Server-Sent Events reply, each event is named balance or position with the Json as data.
+pub fn sse_reply(events: &EventSender, subscription: Subscription) -> impl Reply {
+    let stream = events.subscribe().into_stream().filter_map(move |event| {
+        ready(match event {
+            Ok(event) if subscription.wants(&event) => Some(Ok::<_, Infallible>((
+                sse::event(event.name()),
+                sse::json(event),
+            ))),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("Stream client is missing events, with error: {}", e);
+                None
+            }
+        })
+    });
+    sse::reply(sse::keep_alive().stream(stream))
+}
+
+/// WebSocket reply, each event is sent as a text message of Json.
+pub fn ws_reply(ws: Ws, events: &EventSender, subscription: Subscription) -> impl Reply {
+    let receiver = events.subscribe();
+    ws.on_upgrade(move |socket| send_events(socket, receiver, subscription))
+}
+
+// runs until the client goes away or the channel closes. The incoming half is read too, so a
+// close from the client is seen right away and pings get their pongs.
+async fn send_events(
+    socket: WebSocket,
+    mut receiver: broadcast::Receiver<SyncEvent>,
+    subscription: Subscription,
+) {
+    let (mut sink, incoming) = socket.split();
+    let mut incoming = incoming.fuse();
+    loop {
+        let received = select! {
+            received = receiver.recv().fuse() => received,
+            message = incoming.next() => match message {
+                Some(Ok(message)) if !message.is_close() => continue,
+                _ => break,
+            },
+        };
+        let event = match received {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("Stream client missed {} events.", missed);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+        if !subscription.wants(&event) {
+            continue;
+        }
+        let text = match to_json_string(&event) {
+            Ok(t) => t,
+            Err(e) => {
+                warn!("Could not encode stream event, with error: {}", e);
+                continue;
+            }
+        };
+        if sink.send(Message::text(text)).await.is_err() {
+            break;
+        }
+    }
+}
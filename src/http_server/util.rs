@@ -4,12 +4,22 @@
 
 use super::{
     super::{
-        include::{json, Json, NaiveDate, NaiveTime},
-        storage::{DBInfoAccountBalance, DBInfoAccountPosition},
+        analytics::{percent_change, ReturnMethod},
+        dividends::{dividend_summary, DividendSummary},
+        fx::FxConverter,
+        include::{self, from_str, json, Currency, Duration, Json, Local, NaiveDate, NaiveTime},
+        portfolio::PortfolioConfig,
+        storage::{
+            ActivityType, BalanceKind, DBInfoAccountBalance, DBInfoAccountPosition, DBInfoQuote,
+            SeriesInterval, Storage,
+        },
     },
     ErrorReply,
 };
 
+/// How many days back the statusbar looks for a quote when there isn't one today yet.
+const QUOTE_FALLBACK_DAYS: i64 = 7;
+
 // Funtion for parsing dates from strings.
 pub fn parse_date(date_str: String) -> Result<NaiveDate, Json> {
     match NaiveDate::parse_from_str(&date_str, "%Y-%m-%d") {
@@ -32,9 +42,216 @@ pub fn parse_time(time_str: String) -> Result<NaiveTime, Json> {
     }
 }
 
+// Function for parsing an optional from & to date pair, missing dates default to today.
+pub fn parse_date_range(
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<(NaiveDate, NaiveDate), Json> {
+    let today = Local::today().naive_local();
+    let from = match from {
+        Some(f) => parse_date(f)?,
+        None => today,
+    };
+    let to = match to {
+        Some(t) => parse_date(t)?,
+        None => today,
+    };
+    if from > to {
+        return Err(json(&ErrorReply::new(format!(
+            "Invalid date range: {} is after {}.",
+            from, to
+        ))));
+    }
+    Ok((from, to))
+}
+
+// Function for parsing a date range that looks back from the to date, which defaults to today.
+// A missing from date is the number of days before it.
+pub fn parse_trailing_date_range(
+    from: Option<String>,
+    to: Option<String>,
+    days: i64,
+) -> Result<(NaiveDate, NaiveDate), Json> {
+    let to = match to {
+        Some(t) => parse_date(t)?,
+        None => Local::today().naive_local(),
+    };
+    let from = match from {
+        Some(f) => parse_date(f)?,
+        None => to - Duration::days(days),
+    };
+    if from > to {
+        return Err(json(&ErrorReply::new(format!(
+            "Invalid date range: {} is after {}.",
+            from, to
+        ))));
+    }
+    Ok((from, to))
+}
+
+// Function for parsing the bucket size of a range api, defaults to an hour.
+pub fn parse_interval(interval_str: Option<String>) -> Result<SeriesInterval, Json> {
+    let interval_str = interval_str.unwrap_or_else(|| "1h".to_string());
+    match SeriesInterval::parse(&interval_str) {
+        Some(i) => Ok(i),
+        None => Err(json(&ErrorReply::new(format!(
+            "Could not parse interval: {}. Expected something like 5m, 1h, or 1d.",
+            interval_str
+        )))),
+    }
+}
+
+// Function for parsing how to measure returns, defaults to time-weighted.
+pub fn parse_return_method(method_str: Option<String>) -> Result<ReturnMethod, Json> {
+    let method_str = method_str.unwrap_or_else(|| "twr".to_string());
+    match ReturnMethod::parse(&method_str) {
+        Some(m) => Ok(m),
+        None => Err(json(&ErrorReply::new(format!(
+            "Could not parse return method: {}. Expected twr or mwr.",
+            method_str
+        )))),
+    }
+}
+
+// Function for parsing which balances to read, defaults to the one picked in the config.
+pub fn parse_balance_kind(
+    kind_str: Option<String>,
+    default: BalanceKind,
+) -> Result<BalanceKind, Json> {
+    let kind_str = match kind_str {
+        Some(k) => k,
+        None => return Ok(default),
+    };
+    match BalanceKind::parse(&kind_str) {
+        Some(k) => Ok(k),
+        None => Err(json(&ErrorReply::new(format!(
+            "Could not parse currency: {}. Expected CAD, USD, or combined.",
+            kind_str
+        )))),
+    }
+}
+
+// Function for parsing the currency to convert values into, None leaves them as stored.
+pub fn parse_convert(convert_str: Option<String>) -> Result<Option<Currency>, Json> {
+    match convert_str.map(|c| c.to_uppercase()).as_deref() {
+        Some("CAD") => Ok(Some(Currency::CAD)),
+        Some("USD") => Ok(Some(Currency::USD)),
+        Some(c) => Err(json(&ErrorReply::new(format!(
+            "Could not parse convert currency: {}. Expected CAD or USD.",
+            c
+        )))),
+        None => Ok(None),
+    }
+}
+
+// Function for parsing the currency to convert values into, then loading the exchange rates
+// recorded between the dates.
+pub fn parse_converter(
+    db: &dyn Storage,
+    convert_str: Option<String>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Option<FxConverter>, Json> {
+    let currency = match parse_convert(convert_str)? {
+        Some(c) => c,
+        None => return Ok(None),
+    };
+    match FxConverter::load(db, from, to, currency) {
+        Ok(c) => Ok(Some(c)),
+        Err(e) => Err(json(&ErrorReply::new(format!(
+            "Error getting exchange rates. Error: {}",
+            e
+        )))),
+    }
+}
+
+// Function for parsing an activity type, it uses the same names as the ActivityType enum.
+pub fn parse_activity_type(type_str: String) -> Result<ActivityType, Json> {
+    match from_str::<ActivityType>(&type_str) {
+        Ok(at) => Ok(at),
+        Err(e) => Err(json(&ErrorReply::new(format!(
+            "Could not parse activity type: {}. Error: {}",
+            type_str, e
+        )))),
+    }
+}
+
+// Function to pull everything the statusbar api needs out of the DB.
+pub fn get_statusbar_info(
+    db: &dyn Storage,
+    acct_ident: &str,
+    kind: BalanceKind,
+) -> include::Result<(
+    Vec<(String, DBInfoAccountPosition)>,
+    DBInfoAccountBalance,
+    DBInfoAccountBalance,
+)> {
+    let today = Local::today().naive_local();
+    let mut position_list = db.get_position_symbols(acct_ident)?;
+    let mut positions: Vec<DBInfoAccountPosition> = position_list
+        .iter()
+        .map(|pn| db.get_latest_position(acct_ident, pn, today).unwrap())
+        .collect();
+    let sod_balance = db.get_start_of_day_balance(acct_ident, kind, today)?;
+    let latest_balance = db.get_latest_balance(acct_ident, kind, today)?;
+    let positions = position_list.drain(..).zip(positions.drain(..)).collect();
+    Ok((positions, sod_balance, latest_balance))
+}
+
+// Function to pull the latest quote of every watchlist symbol the statusbar input uses. Over a
+// weekend or holiday that is the last one from before today.
+pub fn get_statusbar_quotes(db: &dyn Storage, input_string: &str) -> Vec<DBInfoQuote> {
+    // skip the lookup entirely when there is no quote in the input.
+    if !input_string.contains("%quote.") {
+        return Vec::new();
+    }
+    let today = Local::today().naive_local();
+    db.get_quote_symbols()
+        .unwrap_or_default()
+        .iter()
+        .filter(|symbol| input_string.contains(&format!("%quote.{}.", symbol)))
+        .filter_map(|symbol| {
+            db.get_latest_quote(symbol, today).ok().or_else(|| {
+                db.get_quote_days(
+                    symbol,
+                    today - Duration::days(QUOTE_FALLBACK_DAYS),
+                    today.pred(),
+                )
+                .ok()?
+                .pop()?
+                .1
+                .pop()
+            })
+        })
+        .collect()
+}
+
+// Function to work out the dividend totals, only when the statusbar input uses them.
+pub fn get_statusbar_dividends(
+    db: &dyn Storage,
+    config: &PortfolioConfig,
+    acct_ident: &str,
+    convert: Option<&Currency>,
+    input_string: &str,
+) -> Option<DividendSummary> {
+    if !input_string.contains("%div.") {
+        return None;
+    }
+    dividend_summary(
+        db,
+        config,
+        acct_ident,
+        convert,
+        Local::today().naive_local(),
+    )
+    .ok()
+}
+
 // function for parsing the input string on the /statusbar/$account_id/$input_str api
 pub fn api_string_replacement(
     positions: Vec<(String, DBInfoAccountPosition)>,
+    quotes: Vec<DBInfoQuote>,
+    dividends: Option<DividendSummary>,
     sod_balance: DBInfoAccountBalance,
     latest_balance: DBInfoAccountBalance,
     mut input_string: String,
@@ -104,6 +321,59 @@ pub fn api_string_replacement(
             ),
         ),
     ];
+    // the quote keys go first, they carry their own prefix so they never clash with the rest.
+    input_string = quotes.iter().fold(input_string, |acc, quote| {
+        let name = &quote.symbol;
+        let replacement_pairs = [
+            (
+                format!("%quote.{}.lastPrice", name),
+                format!("{:.2}", quote.last_trade_price),
+            ),
+            (
+                format!("%quote.{}.bidPrice", name),
+                format!("{:.2}", quote.bid_price.unwrap_or_default()),
+            ),
+            (
+                format!("%quote.{}.askPrice", name),
+                format!("{:.2}", quote.ask_price.unwrap_or_default()),
+            ),
+            (
+                format!("%quote.{}.openPrice", name),
+                format!("{:.2}", quote.open_price),
+            ),
+            (
+                format!("%quote.{}.highPrice", name),
+                format!("{:.2}", quote.high_price),
+            ),
+            (
+                format!("%quote.{}.lowPrice", name),
+                format!("{:.2}", quote.low_price),
+            ),
+            (
+                format!("%quote.{}.openChange", name),
+                format!(
+                    "{:.2}",
+                    percent_change(quote.open_price, quote.last_trade_price)
+                ),
+            ),
+            (format!("%quote.{}.volume", name), quote.volume.to_string()),
+        ];
+        replacement_pairs
+            .iter()
+            .fold(acc, |ac, (k, v)| ac.replace(k, v))
+    });
+    // the dividend keys have their own prefix too.
+    if let Some(div) = dividends {
+        let replacement_pairs = [
+            ("%div.mtd", format!("{:.2}", div.month_to_date)),
+            ("%div.ytd", format!("{:.2}", div.year_to_date)),
+            ("%div.trailing", format!("{:.2}", div.trailing)),
+            ("%div.projected", format!("{:.2}", div.projected)),
+        ];
+        input_string = replacement_pairs
+            .iter()
+            .fold(input_string, |acc, (k, v)| acc.replace(k, v));
+    }
     // filter throught the start of day keys.
     input_string = sod_balance_replace_pairs
         .iter()
@@ -181,3 +451,69 @@ pub fn api_string_replacement(
     // return whatver the input string was.
     input_string
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailing_date_range_defaults() {
+        let today = Local::today().naive_local();
+        let (from, to) = parse_trailing_date_range(None, None, 365).ok().unwrap();
+        assert_eq!((from, to), (today - Duration::days(365), today));
+        let (from, to) = parse_trailing_date_range(None, Some("2026-03-01".to_string()), 365)
+            .ok()
+            .unwrap();
+        assert_eq!(
+            (from, to),
+            (
+                NaiveDate::from_ymd(2025, 3, 1),
+                NaiveDate::from_ymd(2026, 3, 1)
+            )
+        );
+        assert!(parse_trailing_date_range(
+            Some("2026-03-02".to_string()),
+            Some("2026-03-01".to_string()),
+            365
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn open_change_without_an_open_price() {
+        let balance = DBInfoAccountBalance {
+            currency: Currency::CAD,
+            cash: 100.0,
+            market_value: 0.0,
+            total_equity: 100.0,
+            buying_power: 100.0,
+            maitenance_excess: 100.0,
+            time_retrieved: NaiveTime::from_hms(9, 30, 0),
+        };
+        let quote = |open_price| DBInfoQuote {
+            symbol: "XEQT.TO".to_string(),
+            bid_price: None,
+            ask_price: None,
+            last_trade_price: 30.0,
+            volume: 0,
+            open_price,
+            high_price: 30.0,
+            low_price: 30.0,
+            delay: false,
+            is_halted: false,
+            time_retrieved: NaiveTime::from_hms(9, 30, 0),
+        };
+        let replace = |open_price| {
+            api_string_replacement(
+                Vec::new(),
+                vec![quote(open_price)],
+                None,
+                balance.clone(),
+                balance.clone(),
+                "%quote.XEQT.TO.openChange".to_string(),
+            )
+        };
+        assert_eq!(replace(0.0), "0.00");
+        assert_eq!(replace(25.0), "20.00");
+    }
+}
@@ -0,0 +1,216 @@
+//! Sub module with a small reader for the CSV files Questrade exports.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::include::{NaiveDate, NaiveDateTime, PathBuf, Result},
+    ImportError,
+};
+
+/// Helper functions
+// headers are matched on just their letters and digits, so "Account #" is "account" and
+// "Open P&L" is "openpl".
+fn normalize(header: &str) -> String {
+    header
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+// splits the whole file into records, quoted fields can hold commas, quotes, and line breaks.
+fn records(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push((start_line, std::mem::take(&mut record)));
+                line += 1;
+                start_line = line;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start_line, record));
+    }
+    // blank lines, like the one Excel leaves at the end, aren't rows.
+    records.retain(|(_, r)| r.iter().any(|f| !f.trim().is_empty()));
+    records
+}
+
+/// A CSV file with a header row, read into memory.
+pub struct CsvFile {
+    pub path: PathBuf,
+    header: Vec<String>,
+    rows: Vec<(usize, Vec<String>)>,
+}
+
+impl CsvFile {
+    pub fn parse(path: PathBuf, text: &str) -> Self {
+        let mut rows = records(text);
+        let header = if rows.is_empty() {
+            Vec::new()
+        } else {
+            rows.remove(0).1.iter().map(|h| normalize(h)).collect()
+        };
+        Self { path, header, rows }
+    }
+    /// Index of the first of the names that is a column of the file.
+    pub fn column(&self, names: &[&str]) -> Option<usize> {
+        names
+            .iter()
+            .find_map(|name| self.header.iter().position(|h| h == name))
+    }
+    /// Same as column, but it is an error for the file not to have it.
+    pub fn require(&self, names: &[&str], description: &'static str) -> Result<usize> {
+        self.column(names)
+            .ok_or_else(|| ImportError::MissingColumnError(self.path.clone(), description).into())
+    }
+    pub fn rows(&self) -> impl Iterator<Item = Row<'_>> {
+        self.rows.iter().map(move |(line, fields)| Row {
+            path: &self.path,
+            line: *line,
+            fields,
+        })
+    }
+}
+
+/// One row of a CSV file, with the line it started on for error messages.
+pub struct Row<'a> {
+    path: &'a PathBuf,
+    pub line: usize,
+    fields: &'a [String],
+}
+
+impl<'a> Row<'a> {
+    fn invalid(&self, value: &str) -> Box<dyn std::error::Error> {
+        Box::new(ImportError::InvalidValueError(
+            self.path.clone(),
+            self.line,
+            value.to_string(),
+        ))
+    }
+    /// The trimmed text of a column, empty if the column or the field isn't there.
+    pub fn text(&self, column: Option<usize>) -> String {
+        column
+            .and_then(|c| self.fields.get(c))
+            .map(|f| f.trim().to_string())
+            .unwrap_or_default()
+    }
+    /// A number, dollar signs and thousands seperators are dropped and empty reads as 0.
+    pub fn number(&self, column: Option<usize>) -> Result<f64> {
+        let text: String = self
+            .text(column)
+            .chars()
+            .filter(|c| *c != '$' && *c != ',')
+            .collect();
+        if text.is_empty() {
+            return Ok(0.0);
+        }
+        text.parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .ok_or_else(|| self.invalid(&text))
+    }
+    /// A date in any of the layouts the exports have used, the time of day is ignored.
+    pub fn date(&self, column: Option<usize>) -> Result<NaiveDate> {
+        let text = self.text(column);
+        for format in ["%Y-%m-%d %I:%M:%S %p", "%Y-%m-%d %H:%M:%S"].iter() {
+            if let Ok(dt) = NaiveDateTime::parse_from_str(&text, format) {
+                return Ok(dt.date());
+            }
+        }
+        for format in ["%Y-%m-%d", "%m/%d/%Y", "%d-%b-%Y"].iter() {
+            if let Ok(d) = NaiveDate::parse_from_str(&text, format) {
+                return Ok(d);
+            }
+        }
+        Err(self.invalid(&text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> CsvFile {
+        CsvFile::parse(PathBuf::from("test.csv"), text)
+    }
+
+    #[test]
+    fn quoted_fields_and_bom() {
+        let file = parse(
+            "\u{feff}Symbol,Description,Qty,Account #\r\n\
+             XEQT.TO,\"ISHARES CORE EQUITY ETF PORTFOLIO, ETF\",\"1,000\",123\r\n\
+             VFV.TO,\"VANGUARD \"\"S&P\"\" 500\",5,123\r\n\
+             ZAG.TO,\"two\nlines\",3,123\r\n\
+             XIC.TO,last,1,123\r\n\
+             \r\n",
+        );
+        let symbol = file.column(&["symbol"]);
+        let description = file.column(&["description"]);
+        let quantity = file.column(&["qty"]);
+        assert_eq!(file.column(&["account"]), Some(3));
+        let rows: Vec<Row> = file.rows().collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[0].text(description),
+            "ISHARES CORE EQUITY ETF PORTFOLIO, ETF"
+        );
+        assert_eq!(rows[0].number(quantity).unwrap(), 1000.0);
+        assert_eq!(rows[1].text(description), "VANGUARD \"S&P\" 500");
+        assert_eq!(rows[2].text(description), "two\nlines");
+        // the line a row starts on counts the line breaks inside quotes.
+        let lines: Vec<usize> = rows.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![2, 3, 4, 6]);
+        assert_eq!(rows[3].text(symbol), "XIC.TO");
+        assert_eq!(rows[3].text(Some(9)), "");
+    }
+
+    #[test]
+    fn dates_in_every_layout() {
+        let file = parse(
+            "Date\n\
+             2026-01-05 12:00:00 AM\n\
+             2026-01-05 13:30:00\n\
+             2026-01-05\n\
+             01/05/2026\n\
+             05-Jan-2026\n\
+             2026-13-05\n",
+        );
+        let date = file.column(&["date"]);
+        let dates: Vec<Option<NaiveDate>> = file.rows().map(|r| r.date(date).ok()).collect();
+        let day = Some(NaiveDate::from_ymd(2026, 1, 5));
+        assert_eq!(dates, vec![day, day, day, day, day, None]);
+    }
+
+    #[test]
+    fn numbers() {
+        let file = parse("Net Amount\n\"$1,234.50\"\n\n-5\nabc\nNaN\n \n");
+        let net = file.column(&["netamount"]);
+        let numbers: Vec<Option<f64>> = file.rows().map(|r| r.number(net).ok()).collect();
+        // lines that are empty or only spaces aren't rows.
+        assert_eq!(numbers, vec![Some(1234.5), Some(-5.0), None, None]);
+    }
+}
@@ -0,0 +1,59 @@
+//! Module containing the errors that can come up while importing statements.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{error, fmt, PathBuf};
+
+#[derive(Debug)]
+// Enum representing errors that are possible while reading a Questrade CSV export.
+pub enum ImportError {
+    UnknownFileError(PathBuf),
+    MissingColumnError(PathBuf, &'static str),
+    InvalidValueError(PathBuf, usize, String),
+    MissingAccountError(PathBuf, usize),
+    MissingDateError(PathBuf),
+    InvalidDateError(String),
+}
+
+impl error::Error for ImportError {}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFileError(path) => write!(
+                f,
+                "Could not import {}! It doesn't look like an activity or position export.",
+                path.display()
+            ),
+            Self::MissingColumnError(path, column) => write!(
+                f,
+                "Could not import {}! It has no {} column.",
+                path.display(),
+                column
+            ),
+            Self::InvalidValueError(path, line, value) => write!(
+                f,
+                "Could not import {}! Could not read the value {} on line {}.",
+                path.display(),
+                value,
+                line
+            ),
+            Self::MissingAccountError(path, line) => write!(
+                f,
+                "Could not import {}! Line {} has no account number, pass one with --account.",
+                path.display(),
+                line
+            ),
+            Self::MissingDateError(path) => write!(
+                f,
+                "Could not import {}! Position exports have no date, pass one with --date.",
+                path.display()
+            ),
+            Self::InvalidDateError(date) => write!(
+                f,
+                "Could not parse date: {}. Expected the YYYY-MM-DD format.",
+                date
+            ),
+        }
+    }
+}
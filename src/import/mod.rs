@@ -0,0 +1,999 @@
+//! Module to back-fill history from the CSV exports Questrade lets you download.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    fx::FxConverter,
+    include::{
+        fmt, from_str, read_to_string, Account, AccountActivity, AccountBalance, AccountName,
+        AccountNumber, AccountPosition, AccountStatus, AccountType, ClientAccountType, Currency,
+        DateTime, Duration, HashMap, Local, NaiveDate, Number, PathBuf, Result, Utc,
+    },
+    portfolio::symbol_currency,
+    storage::{
+        api_number, close_snapshot_time, ActivityType, BalanceKind, DBInfoAccountActivity,
+        PositionSource, Storage,
+    },
+};
+
+/// Sub modules
+mod csv;
+mod errors;
+
+/// Re-export sub-modules so we can read from them in other modules.
+pub use errors::*;
+
+use csv::{CsvFile, Row};
+
+/// How many days back we look for the close before an imported day, enough to cover a long
+/// weekend.
+const PREVIOUS_CLOSE_DAYS: i64 = 7;
+
+#[derive(Debug, Clone)]
+/// Everything the import subcommand was asked for.
+pub struct ImportRequest {
+    pub files: Vec<PathBuf>,
+    /// Account for rows that don't have one, by name or number.
+    pub account: Option<String>,
+    /// Name to give the account from --account if it is new.
+    pub name: Option<AccountName>,
+    /// Day the positions in a position export were held, most exports don't say.
+    pub date: Option<NaiveDate>,
+    /// Work everything out and print the report, but don't write anything.
+    pub dry_run: bool,
+}
+
+impl ImportRequest {
+    pub fn new(
+        files: Vec<&str>,
+        account: Option<&str>,
+        name: Option<&str>,
+        date: Option<&str>,
+        dry_run: bool,
+    ) -> Result<Self> {
+        let date = match date {
+            Some(d) => match NaiveDate::parse_from_str(d, "%Y-%m-%d") {
+                Ok(d) => Some(d),
+                Err(_) => return Err(Box::new(ImportError::InvalidDateError(d.to_string()))),
+            },
+            None => None,
+        };
+        Ok(Self {
+            files: files.into_iter().map(PathBuf::from).collect(),
+            account: account.map(str::to_string),
+            name: name.map(str::to_string),
+            date,
+            dry_run,
+        })
+    }
+}
+
+#[derive(Debug)]
+/// An imported row that overlaps what was synced, it is left out so the synced data wins.
+pub struct ImportConflict {
+    pub account: AccountNumber,
+    pub date: NaiveDate,
+    pub detail: String,
+}
+
+impl fmt::Display for ImportConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.account, self.date, self.detail)
+    }
+}
+
+#[derive(Debug, Default)]
+/// What an import did, or would have done on a dry run.
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub accounts: Vec<AccountNumber>,
+    pub activities: usize,
+    pub positions: usize,
+    pub balances: usize,
+    /// Rows that are already stored the same as imported, usually from an earlier import.
+    pub duplicates: usize,
+    pub conflicts: Vec<ImportConflict>,
+    /// Accounts we couldn't rebuild the balances of, since part of their cash history is missing.
+    pub balances_skipped: Vec<ImportConflict>,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dry_run {
+            writeln!(f, "Dry run, nothing was written.")?;
+        }
+        writeln!(f, "Accounts added:     {}", self.accounts.join(", "))?;
+        writeln!(f, "Activities added:   {}", self.activities)?;
+        writeln!(f, "Positions added:    {}", self.positions)?;
+        writeln!(f, "Balances added:     {}", self.balances)?;
+        for skipped in self.balances_skipped.iter() {
+            writeln!(f, "    {}", skipped)?;
+        }
+        writeln!(f, "Already stored:     {}", self.duplicates)?;
+        writeln!(f, "Conflicts skipped:  {}", self.conflicts.len())?;
+        for conflict in self.conflicts.iter() {
+            writeln!(f, "    {}", conflict)?;
+        }
+        Ok(())
+    }
+}
+
+// a position from a position export, with the day it was held.
+struct ImportedPosition {
+    account: AccountNumber,
+    date: NaiveDate,
+    currency: Currency,
+    position: AccountPosition,
+}
+
+// everything read out of the files, before it is checked against the DB.
+#[derive(Default)]
+struct Statements {
+    // account numbers along with the account type the export gave, if it gave one.
+    accounts: Vec<(AccountNumber, String)>,
+    activities: Vec<(AccountNumber, AccountActivity)>,
+    positions: Vec<ImportedPosition>,
+}
+
+/// Helper functions
+fn as_f64(value: &Number) -> f64 {
+    value.as_f64().unwrap_or_default()
+}
+
+// the api sends dates as midnight Eastern, so imported ones are stored the same way.
+fn questrade_date(date: NaiveDate) -> DateTime<Utc> {
+    DateTime::<Utc>::from_utc(date.and_hms(5, 0, 0), Utc)
+}
+
+fn parse_currency(text: &str) -> Option<Currency> {
+    match text.to_uppercase().as_str() {
+        "CAD" => Some(Currency::CAD),
+        "USD" => Some(Currency::USD),
+        _ => None,
+    }
+}
+
+// the exports write the type like "Individual TFSA" or "Joint Margin".
+fn new_account(number: AccountNumber, account_type: &str) -> Account {
+    let words: Vec<String> = account_type
+        .split_whitespace()
+        .map(|w| w.to_uppercase())
+        .collect();
+    let account_type = words
+        .iter()
+        .find_map(|w| match w.as_str() {
+            "CASH" => Some(AccountType::Cash),
+            "MARGIN" => Some(AccountType::Margin),
+            w => from_str::<AccountType>(w).ok(),
+        })
+        .unwrap_or(AccountType::Cash);
+    let client_account_type = match words.iter().any(|w| w == "JOINT") {
+        true => ClientAccountType::Joint,
+        false => ClientAccountType::Individual,
+    };
+    Account {
+        account_type,
+        number,
+        status: AccountStatus::Active,
+        is_primary: false,
+        is_billing: false,
+        client_account_type,
+    }
+}
+
+// the export and the api don't agree on actions, descriptions, or symbol ids, so we only
+// compare the amounts and what they are for.
+fn same_activity(a: &DBInfoAccountActivity, b: &DBInfoAccountActivity) -> bool {
+    a.transaction_date == b.transaction_date
+        && a.activity_type == b.activity_type
+        && a.symbol.eq_ignore_ascii_case(&b.symbol)
+        && a.currency == b.currency
+        && (a.quantity - b.quantity).abs() < 1e-6
+        && (a.net_amount - b.net_amount).abs() < 0.005
+}
+
+impl Statements {
+    fn read_file(
+        &mut self,
+        path: &PathBuf,
+        account: &Option<AccountNumber>,
+        date: Option<NaiveDate>,
+    ) -> Result<()> {
+        let file = CsvFile::parse(path.clone(), &read_to_string(path)?);
+        if file.column(&["transactiondate"]).is_some() && file.column(&["activitytype"]).is_some() {
+            self.read_activities(&file, account)
+        } else if file.column(&["symbol"]).is_some()
+            && file
+                .column(&["currentmarketvalue", "marketvalue"])
+                .is_some()
+        {
+            self.read_positions(&file, account, date)
+        } else {
+            Err(Box::new(ImportError::UnknownFileError(path.clone())))
+        }
+    }
+
+    // the account of a row, from its own column or the one passed on the command line.
+    fn account_of(
+        &mut self,
+        file: &CsvFile,
+        row: &Row,
+        column: Option<usize>,
+        default: &Option<AccountNumber>,
+        account_type: String,
+    ) -> Result<AccountNumber> {
+        let account = match (row.text(column), default) {
+            (a, _) if !a.is_empty() => a,
+            (_, Some(a)) => a.clone(),
+            (_, None) => {
+                return Err(Box::new(ImportError::MissingAccountError(
+                    file.path.clone(),
+                    row.line,
+                )))
+            }
+        };
+        match self.accounts.iter_mut().find(|(a, _)| *a == account) {
+            Some((_, t)) if t.is_empty() => *t = account_type,
+            Some(_) => {}
+            None => self.accounts.push((account.clone(), account_type)),
+        }
+        Ok(account)
+    }
+
+    fn read_activities(&mut self, file: &CsvFile, default: &Option<AccountNumber>) -> Result<()> {
+        let transaction = Some(file.require(&["transactiondate"], "Transaction Date")?);
+        let net = Some(file.require(&["netamount"], "Net Amount")?);
+        let currency = Some(file.require(&["currency"], "Currency")?);
+        let activity_type = Some(file.require(&["activitytype"], "Activity Type")?);
+        let trade = file.column(&["tradedate"]);
+        let settlement = file.column(&["settlementdate"]);
+        let action = file.column(&["action"]);
+        let symbol = file.column(&["symbol"]);
+        let description = file.column(&["description"]);
+        let quantity = file.column(&["quantity"]);
+        let price = file.column(&["price"]);
+        let gross = file.column(&["grossamount"]);
+        let commission = file.column(&["commission"]);
+        let account = file.column(&["accountnumber", "account"]);
+        let account_type = file.column(&["accounttype"]);
+        for row in file.rows() {
+            let acct = self.account_of(file, &row, account, default, row.text(account_type))?;
+            let transaction_date = row.date(transaction)?;
+            // trade and settlement dates fall back to the transaction date when left out.
+            let date_or_transaction = |column: Option<usize>| match row.text(column).is_empty() {
+                true => Ok(transaction_date),
+                false => row.date(column),
+            };
+            let activity = AccountActivity {
+                trade_date: questrade_date(date_or_transaction(trade)?),
+                transaction_date: questrade_date(transaction_date),
+                settlement_date: questrade_date(date_or_transaction(settlement)?),
+                action: row.text(action),
+                symbol: row.text(symbol),
+                symbol_id: 0,
+                description: row.text(description),
+                currency: row.text(currency).to_uppercase(),
+                quantity: api_number(row.number(quantity)?),
+                price: api_number(row.number(price)?),
+                gross_amount: api_number(row.number(gross)?),
+                commission: api_number(row.number(commission)?),
+                net_amount: api_number(row.number(net)?),
+                activity_type: row.text(activity_type),
+            };
+            self.activities.push((acct, activity));
+        }
+        Ok(())
+    }
+
+    fn read_positions(
+        &mut self,
+        file: &CsvFile,
+        default: &Option<AccountNumber>,
+        default_date: Option<NaiveDate>,
+    ) -> Result<()> {
+        let symbol = Some(file.require(&["symbol"], "Symbol")?);
+        let quantity = Some(file.require(&["openquantity", "quantity", "qty"], "Quantity")?);
+        let market_value =
+            Some(file.require(&["currentmarketvalue", "marketvalue"], "Market Value")?);
+        let price = file.column(&["currentprice", "price", "lastprice"]);
+        let average = file.column(&[
+            "averageentryprice",
+            "averagecost",
+            "avgcost",
+            "averageprice",
+        ]);
+        let total_cost = file.column(&["totalcost", "bookvalue"]);
+        let open_pnl = file.column(&["openpnl", "openpl"]);
+        let day_pnl = file.column(&["daypnl", "daypl"]);
+        let closed_pnl = file.column(&["closedpnl", "closedpl"]);
+        let closed_quantity = file.column(&["closedquantity"]);
+        let currency = file.column(&["currency"]);
+        let account = file.column(&["accountnumber", "account"]);
+        let date = file.column(&["date", "asof", "asofdate"]);
+        for row in file.rows() {
+            let acct = self.account_of(file, &row, account, default, String::new())?;
+            let held = match (row.text(date).is_empty(), default_date) {
+                (false, _) => row.date(date)?,
+                (true, Some(d)) => d,
+                (true, None) => {
+                    return Err(Box::new(ImportError::MissingDateError(file.path.clone())))
+                }
+            };
+            let sym = row.text(symbol);
+            let qty = row.number(quantity)?;
+            let value = row.number(market_value)?;
+            // whatever the export leaves out is worked out from what it has.
+            let per_share = |total: f64| if qty == 0.0 { 0.0 } else { total / qty };
+            let (average, total_cost) = match (average, total_cost) {
+                (Some(_), Some(_)) => (row.number(average)?, row.number(total_cost)?),
+                (Some(_), None) => (row.number(average)?, row.number(average)? * qty),
+                (None, Some(_)) => (per_share(row.number(total_cost)?), row.number(total_cost)?),
+                (None, None) => (0.0, 0.0),
+            };
+            let price = match price {
+                Some(_) => row.number(price)?,
+                None => per_share(value),
+            };
+            let open_pnl = match open_pnl {
+                Some(_) => row.number(open_pnl)?,
+                None => value - total_cost,
+            };
+            self.positions.push(ImportedPosition {
+                account: acct,
+                date: held,
+                currency: parse_currency(&row.text(currency))
+                    .unwrap_or_else(|| symbol_currency(&sym.to_uppercase())),
+                position: AccountPosition {
+                    symbol: sym,
+                    symbol_id: 0,
+                    open_quantity: api_number(qty),
+                    closed_quantity: api_number(row.number(closed_quantity)?),
+                    current_market_value: api_number(value),
+                    current_price: api_number(price),
+                    day_profit_and_loss: api_number(row.number(day_pnl)?),
+                    average_entry_price: api_number(average),
+                    closed_profit_and_loss: api_number(row.number(closed_pnl)?),
+                    open_profit_and_loss: api_number(open_pnl),
+                    total_cost: api_number(total_cost),
+                    is_real_time: false,
+                    is_under_reorg: false,
+                },
+            });
+        }
+        Ok(())
+    }
+}
+
+// the balance in one currency, cash is rebuilt from every activity given.
+fn end_of_day_balance(
+    currency: Currency,
+    activities: &[&DBInfoAccountActivity],
+    positions: &[&ImportedPosition],
+) -> AccountBalance {
+    let cash: f64 = activities
+        .iter()
+        .filter(|a| a.currency == currency)
+        .map(|a| a.net_amount)
+        .sum();
+    let market_value: f64 = positions
+        .iter()
+        .filter(|p| p.currency == currency)
+        .map(|p| as_f64(&p.position.current_market_value))
+        .sum();
+    AccountBalance {
+        currency,
+        cash: api_number(cash),
+        market_value: api_number(market_value),
+        total_equity: api_number(cash + market_value),
+        buying_power: api_number(cash),
+        maintenance_excess: api_number(cash),
+        is_real_time: false,
+    }
+}
+
+// the CAD, USD, and combined balances of an account as of a day. The combined one uses the rate
+// synced closest to the day, or the one from the config.
+fn end_of_day_balances(
+    db: &dyn Storage,
+    date: NaiveDate,
+    activities: &[&DBInfoAccountActivity],
+    positions: &[&ImportedPosition],
+    combined: &Currency,
+    usd_cad_rate: f64,
+) -> Vec<(BalanceKind, AccountBalance)> {
+    let cad = end_of_day_balance(Currency::CAD, activities, positions);
+    let usd = end_of_day_balance(Currency::USD, activities, positions);
+    let at = close_snapshot_time(date).naive_local();
+    let fx = FxConverter::load(db, date, date, combined.clone()).ok();
+    let convert = |amount: f64, from: &Currency| match (&fx, from, combined) {
+        (Some(fx), _, _) => fx.convert(amount, from, at),
+        (None, Currency::USD, Currency::CAD) => amount * usd_cad_rate,
+        (None, Currency::CAD, Currency::USD) => amount / usd_cad_rate,
+        _ => amount,
+    };
+    let sum = |field: fn(&AccountBalance) -> &Number| {
+        api_number(
+            convert(as_f64(field(&cad)), &Currency::CAD)
+                + convert(as_f64(field(&usd)), &Currency::USD),
+        )
+    };
+    let total = AccountBalance {
+        currency: combined.clone(),
+        cash: sum(|b| &b.cash),
+        market_value: sum(|b| &b.market_value),
+        total_equity: sum(|b| &b.total_equity),
+        buying_power: sum(|b| &b.buying_power),
+        maintenance_excess: sum(|b| &b.maintenance_excess),
+        is_real_time: false,
+    };
+    vec![
+        (BalanceKind::CAD, cad),
+        (BalanceKind::USD, usd),
+        (BalanceKind::Combined, total),
+    ]
+}
+
+// the last balance stored in the week before a day, read back the way the api sent it.
+fn stored_close(
+    db: &dyn Storage,
+    acct: &AccountNumber,
+    kind: BalanceKind,
+    date: NaiveDate,
+) -> Option<AccountBalance> {
+    let days = db
+        .get_balance_days(
+            acct,
+            kind,
+            date - Duration::days(PREVIOUS_CLOSE_DAYS),
+            date.pred(),
+        )
+        .ok()?;
+    let last = days.last()?;
+    let balance = last
+        .over_day_balances
+        .last()
+        .unwrap_or(&last.start_of_day_bal);
+    Some(AccountBalance {
+        currency: balance.currency.clone(),
+        cash: api_number(balance.cash),
+        market_value: api_number(balance.market_value),
+        total_equity: api_number(balance.total_equity),
+        buying_power: api_number(balance.buying_power),
+        maintenance_excess: api_number(balance.maitenance_excess),
+        is_real_time: false,
+    })
+}
+
+// every activity of an account, as long as they run back to the day it was first funded. Cash
+// rebuilt from part of the history would be off by whatever is missing, so we give up instead
+// and say why.
+fn funded_history(
+    db: &dyn Storage,
+    acct: &AccountNumber,
+    stored: bool,
+    imported: &[(AccountNumber, DBInfoAccountActivity)],
+    imported_through: Option<NaiveDate>,
+) -> std::result::Result<Vec<DBInfoAccountActivity>, String> {
+    let mut history = match stored {
+        true => db
+            .get_activities(
+                acct,
+                NaiveDate::from_ymd(1970, 1, 1),
+                Local::today().naive_local(),
+                None,
+            )
+            .unwrap_or_default(),
+        false => Vec::new(),
+    };
+    history.extend(
+        imported
+            .iter()
+            .filter(|(a, _)| a == acct)
+            .map(|(_, act)| act.clone()),
+    );
+    history.sort_by_key(|a| a.transaction_date);
+    match history.first().map(|a| a.activity_type) {
+        Some(ActivityType::Deposits) | Some(ActivityType::Transfers) => (),
+        _ => {
+            return Err(
+                "no balances made, the activities don't go back to the first deposit.".to_string(),
+            )
+        }
+    }
+    // the synced activities only reach back so far until the backfill is done, the statements
+    // have to meet them.
+    let sync_state = match stored {
+        true => db.get_activity_sync_state(acct).unwrap_or_default(),
+        false => None,
+    };
+    if let Some(state) = sync_state.filter(|s| !s.backfill_complete) {
+        if !matches!(imported_through, Some(d) if d >= state.backfilled_to) {
+            return Err(format!(
+                "no balances made, the activities imported stop before the synced ones start on {}.",
+                state.backfilled_to
+            ));
+        }
+    }
+    Ok(history)
+}
+
+/// Reads the statements and back-fills whatever isn't already stored. Synced data always wins,
+/// anything that overlaps it is left out and listed in the report as a conflict.
+pub fn import_statements(
+    db: &dyn Storage,
+    request: &ImportRequest,
+    combined: &Currency,
+    usd_cad_rate: f64,
+) -> Result<ImportReport> {
+    // --account can be a name, but the rows need a number.
+    let default_account = request
+        .account
+        .as_ref()
+        .map(|a| match db.get_account_info(a) {
+            Ok(acct) => acct.number,
+            Err(_) => a.clone(),
+        });
+    let mut statements = Statements::default();
+    for path in request.files.iter() {
+        statements.read_file(path, &default_account, request.date)?;
+    }
+    let mut report = ImportReport {
+        dry_run: request.dry_run,
+        ..Default::default()
+    };
+
+    // *** Accounts ***
+    let known = db.list_account_numbers().unwrap_or_default();
+    let mut new_accounts = Vec::new();
+    for (number, account_type) in statements.accounts.iter() {
+        if known.contains(number) {
+            continue;
+        }
+        let name = match (&request.name, &default_account) {
+            (Some(name), Some(acct)) if acct == number => name.clone(),
+            _ => number.clone(),
+        };
+        new_accounts.push((name, new_account(number.clone(), account_type)));
+    }
+    report.accounts = new_accounts.iter().map(|(_, a)| a.number.clone()).collect();
+    // nothing is stored for a new account, so there is no point asking the DB about it.
+    let is_new = |number: &AccountNumber| !known.contains(number);
+
+    // *** Activities ***
+    let mut activities: Vec<(AccountNumber, Vec<AccountActivity>)> = Vec::new();
+    let mut accepted_info: Vec<(AccountNumber, DBInfoAccountActivity)> = Vec::new();
+    for (number, _) in statements.accounts.iter() {
+        let imported: Vec<&AccountActivity> = statements
+            .activities
+            .iter()
+            .filter(|(a, _)| a == number)
+            .map(|(_, act)| act)
+            .collect();
+        let dates = imported
+            .iter()
+            .map(|a| DBInfoAccountActivity::new((*a).clone()).transaction_date);
+        let (stored, sync_state) = match (dates.clone().min(), dates.max()) {
+            (Some(from), Some(to)) if !is_new(number) => (
+                db.get_activities(number, from, to, None)
+                    .unwrap_or_default(),
+                db.get_activity_sync_state(number).unwrap_or_default(),
+            ),
+            _ => (Vec::new(), None),
+        };
+        // each stored activity can only be matched once, two identical trades on a day are fine.
+        let mut matched = vec![false; stored.len()];
+        let mut accepted = Vec::new();
+        for activity in imported {
+            let info = DBInfoAccountActivity::new(activity.clone());
+            let duplicate = stored
+                .iter()
+                .enumerate()
+                .position(|(i, s)| !matched[i] && same_activity(s, &info));
+            let synced = matches!(&sync_state, Some(s)
+                if info.transaction_date >= s.backfilled_to
+                    && info.transaction_date <= s.synced_through);
+            if let Some(i) = duplicate {
+                matched[i] = true;
+                report.duplicates += 1;
+            } else if synced {
+                report.conflicts.push(ImportConflict {
+                    account: number.clone(),
+                    date: info.transaction_date,
+                    detail: format!(
+                        "{:?} {} {} {:.2} {:?} is not in the synced activities.",
+                        info.activity_type,
+                        info.action,
+                        info.symbol,
+                        info.net_amount,
+                        info.currency
+                    ),
+                });
+            } else {
+                accepted_info.push((number.clone(), info));
+                accepted.push(activity.clone());
+            }
+        }
+        report.activities += accepted.len();
+        activities.push((number.clone(), accepted));
+    }
+
+    // *** Positions ***
+    let mut positions = Vec::new();
+    for imported in statements.positions.iter() {
+        let stored = match is_new(&imported.account) {
+            true => None,
+            false => db
+                .get_latest_position(&imported.account, &imported.position.symbol, imported.date)
+                .ok(),
+        };
+        let quantity = as_f64(&imported.position.open_quantity);
+        let value = as_f64(&imported.position.current_market_value);
+        match stored {
+            Some(s)
+                if (s.open_quantity - quantity).abs() < 1e-6
+                    && (s.current_market_value - value).abs() < 0.005 =>
+            {
+                report.duplicates += 1
+            }
+            Some(s) => report.conflicts.push(ImportConflict {
+                account: imported.account.clone(),
+                date: imported.date,
+                detail: format!(
+                    "{} is stored as {} shares worth {:.2}, the import has {} worth {:.2}.",
+                    s.symbol, s.open_quantity, s.current_market_value, quantity, value
+                ),
+            }),
+            None => positions.push(imported),
+        }
+    }
+    report.positions = positions.len();
+
+    // *** Balances ***
+    // one end of day snapshot for every account and day there are positions for.
+    let mut days: Vec<(&AccountNumber, NaiveDate)> = statements
+        .positions
+        .iter()
+        .map(|p| (&p.account, p.date))
+        .collect();
+    days.sort();
+    days.dedup();
+    let mut histories: HashMap<&AccountNumber, Option<Vec<DBInfoAccountActivity>>> = HashMap::new();
+    let mut closes: HashMap<(&AccountNumber, BalanceKind), (NaiveDate, AccountBalance)> =
+        HashMap::new();
+    let mut balances = Vec::new();
+    for (acct, date) in days {
+        let kinds = [BalanceKind::CAD, BalanceKind::USD, BalanceKind::Combined];
+        let stored = !is_new(acct)
+            && kinds
+                .iter()
+                .any(|kind| db.get_latest_balance(acct, *kind, date).is_ok());
+        if stored {
+            report.conflicts.push(ImportConflict {
+                account: acct.clone(),
+                date,
+                detail: "already has balances synced for the day.".to_string(),
+            });
+            continue;
+        }
+        let history = histories.entry(acct).or_insert_with(|| {
+            let imported_through = statements
+                .activities
+                .iter()
+                .filter(|(a, _)| a == acct)
+                .map(|(_, act)| DBInfoAccountActivity::new(act.clone()).transaction_date)
+                .max();
+            match funded_history(db, acct, !is_new(acct), &accepted_info, imported_through) {
+                Ok(history) => Some(history),
+                Err(detail) => {
+                    report.balances_skipped.push(ImportConflict {
+                        account: acct.clone(),
+                        date,
+                        detail,
+                    });
+                    None
+                }
+            }
+        });
+        let history = match history {
+            Some(history) => history,
+            None => continue,
+        };
+        let day_positions: Vec<&ImportedPosition> = statements
+            .positions
+            .iter()
+            .filter(|p| p.account == *acct && p.date == date)
+            .collect();
+        let through = |last: NaiveDate| -> Vec<&DBInfoAccountActivity> {
+            history
+                .iter()
+                .filter(|a| a.transaction_date <= last)
+                .collect()
+        };
+        let close = end_of_day_balances(
+            db,
+            date,
+            &through(date),
+            &day_positions,
+            combined,
+            usd_cad_rate,
+        );
+        // when the day before wasn't imported or synced the day's holdings stand in for it, so
+        // only the cash that moved during the day shows up.
+        let rebuilt = end_of_day_balances(
+            db,
+            date.pred(),
+            &through(date.pred()),
+            &day_positions,
+            combined,
+            usd_cad_rate,
+        );
+        for ((kind, balance), (_, rebuilt)) in close.into_iter().zip(rebuilt) {
+            let start_of_day = closes
+                .get(&(acct, kind))
+                .filter(|(d, _)| *d >= date - Duration::days(PREVIOUS_CLOSE_DAYS))
+                .map(|(_, b)| b.clone())
+                .or_else(|| stored_close(db, acct, kind, date))
+                .unwrap_or(rebuilt);
+            closes.insert((acct, kind), (date, balance.clone()));
+            balances.push((acct.clone(), date, kind, balance, start_of_day));
+        }
+    }
+    report.balances = balances.len();
+
+    if request.dry_run {
+        return Ok(report);
+    }
+    for (name, account) in new_accounts {
+        db.insert_account(name, account)?;
+    }
+    // the DB has the final say on which activities are new.
+    report.activities = 0;
+    for (number, acts) in activities.into_iter().filter(|(_, acts)| !acts.is_empty()) {
+        report.activities += db.insert_account_activities(&number, acts)?;
+    }
+    for imported in positions {
+        db.insert_account_position(
+            close_snapshot_time(imported.date),
+            &imported.account,
+            imported.position.clone(),
+            PositionSource::Imported,
+        )?;
+    }
+    for (number, date, kind, balance, start_of_day) in balances {
+        db.insert_account_balance(
+            close_snapshot_time(date),
+            &number,
+            kind,
+            balance,
+            start_of_day,
+        )?;
+    }
+    db.save()?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::include::{from_str, TimeZone};
+    use crate::storage::{memory_db, DBInfoActivitySyncState, DBRef};
+
+    // the layout of the activity export, dates and all.
+    const ACTIVITY_HEADER: &str = "Transaction Date,Settlement Date,Action,Symbol,Description,\
+        Quantity,Price,Gross Amount,Commission,Net Amount,Currency,Account #,Activity Type,\
+        Account Type";
+    const DEPOSIT: &str = "2026-01-05 12:00:00 AM,2026-01-05 12:00:00 AM,CON,,CONTRIBUTION,\
+        0.00000,0.00000000,0.00,0.00,10000.00,CAD,12345678,Deposits,Individual TFSA";
+    const BUY: &str = "2026-01-06 12:00:00 AM,2026-01-07 12:00:00 AM,Buy,XEQT.TO,\
+        \"ISHARES CORE EQUITY ETF PORTFOLIO, ETF\",100.00000,30.00000000,-3000.00,-4.95,\
+        -3004.95,CAD,12345678,Trades,Individual TFSA";
+    const USD_DEPOSIT: &str = "2026-01-07 12:00:00 AM,2026-01-07 12:00:00 AM,CON,,CONTRIBUTION,\
+        0.00000,0.00000000,0.00,0.00,1000.00,USD,12345678,Deposits,Individual TFSA";
+    // the layout of the position export, which has no date or account.
+    const POSITIONS: &str = "Symbol,Description,Currency,Qty,Avg Cost,Price,Book Value,\
+        Market Value,Open P&L\n\
+        XEQT.TO,ISHARES CORE EQUITY ETF PORTFOLIO,CAD,100,30.0495,31.00,3004.95,3100.00,95.05\n";
+
+    fn write(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("qtmon_import_{}.csv", name));
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+    fn request(name: &str, activities: &[&str], dry_run: bool) -> ImportRequest {
+        let text = format!("{}\n{}", ACTIVITY_HEADER, activities.join("\n"));
+        ImportRequest {
+            files: vec![
+                write(&format!("{}_activities", name), &text),
+                write(&format!("{}_positions", name), POSITIONS),
+            ],
+            account: Some("12345678".to_string()),
+            name: Some("tfsa".to_string()),
+            date: Some(NaiveDate::from_ymd(2026, 1, 7)),
+            dry_run,
+        }
+    }
+    fn activities(rows: &[&str]) -> Vec<AccountActivity> {
+        let text = format!("{}\n{}", ACTIVITY_HEADER, rows.join("\n"));
+        let mut statements = Statements::default();
+        statements
+            .read_activities(&CsvFile::parse(PathBuf::from("test.csv"), &text), &None)
+            .unwrap();
+        statements.activities.into_iter().map(|(_, a)| a).collect()
+    }
+    fn import(db: &DBRef, request: &ImportRequest) -> ImportReport {
+        let report = import_statements(&**db, request, &Currency::CAD, 1.35).unwrap();
+        for file in request.files.iter() {
+            std::fs::remove_file(file).unwrap();
+        }
+        report
+    }
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn new_account_from_the_exports() {
+        let db = memory_db();
+        let report = import(&db, &request("new", &[DEPOSIT, BUY, USD_DEPOSIT], false));
+        assert_eq!(report.accounts, vec!["12345678".to_string()]);
+        assert_eq!(report.activities, 3);
+        assert_eq!(report.positions, 1);
+        assert_eq!(report.balances, 3);
+        assert!(report.conflicts.is_empty() && report.balances_skipped.is_empty());
+        let account = db.get_account_info("tfsa").unwrap();
+        assert_eq!(account.account_type, AccountType::TFSA);
+        let day = NaiveDate::from_ymd(2026, 1, 7);
+        let cad = db
+            .get_latest_balance("tfsa", BalanceKind::CAD, day)
+            .unwrap();
+        assert!(close(cad.cash, 6995.05));
+        assert!(close(cad.total_equity, 10095.05));
+        let usd = db
+            .get_latest_balance("tfsa", BalanceKind::USD, day)
+            .unwrap();
+        assert!(close(usd.cash, 1000.0));
+        // no rates synced, so the USD is converted at the one from the config.
+        let combined = db
+            .get_latest_balance("tfsa", BalanceKind::Combined, day)
+            .unwrap();
+        assert!(close(combined.total_equity, 10095.05 + 1350.0));
+        // the day before wasn't imported, so the day starts from its holdings and the cash then.
+        let sod = db
+            .get_start_of_day_balance("tfsa", BalanceKind::Combined, day)
+            .unwrap();
+        assert!(close(sod.total_equity, 10095.05));
+        // a second import finds everything already there.
+        let again = import(&db, &request("again", &[DEPOSIT, BUY, USD_DEPOSIT], true));
+        assert_eq!(again.duplicates, 4);
+        assert_eq!(again.activities + again.positions, 0);
+    }
+
+    #[test]
+    fn balances_need_the_first_deposit() {
+        let db = memory_db();
+        let report = import(&db, &request("unfunded", &[BUY], false));
+        assert_eq!(report.positions, 1);
+        assert_eq!(report.balances, 0);
+        assert_eq!(report.balances_skipped.len(), 1);
+        assert!(report.balances_skipped[0].detail.contains("first deposit"));
+    }
+
+    #[test]
+    fn funded_history_meets_the_synced_activities() {
+        let db = memory_db();
+        let number = "12345678".to_string();
+        db.insert_account("tfsa".to_string(), new_account(number.clone(), "TFSA"))
+            .unwrap();
+        let synced: AccountActivity = from_str(
+            "(tradeDate: \"2026-03-02T05:00:00Z\", transactionDate: \"2026-03-02T05:00:00Z\", \
+             settlementDate: \"2026-03-02T05:00:00Z\", action: \"\", symbol: \"\", symbolId: 0, \
+             description: \"\", currency: \"CAD\", quantity: 0, price: 0, grossAmount: 0, \
+             commission: 0, netAmount: 5, type: \"Interest\")",
+        )
+        .unwrap();
+        db.insert_account_activities(&number, vec![synced]).unwrap();
+        let mut state = DBInfoActivitySyncState::new(NaiveDate::from_ymd(2026, 3, 1));
+        state.synced_through = NaiveDate::from_ymd(2026, 3, 31);
+        db.set_activity_sync_state(&number, state).unwrap();
+        let deposit = DBInfoAccountActivity::new(activities(&[DEPOSIT]).remove(0));
+        let imported = vec![(number.clone(), deposit)];
+        // the synced activities start in March, so January alone leaves February out.
+        let through = Some(NaiveDate::from_ymd(2026, 1, 5));
+        let gap = funded_history(&*db, &number, true, &imported, through).unwrap_err();
+        assert!(gap.contains("2026-03-01"), "{}", gap);
+        let through = Some(NaiveDate::from_ymd(2026, 3, 1));
+        let history = funded_history(&*db, &number, true, &imported, through).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].activity_type, ActivityType::Deposits);
+        // once the backfill is done the stored history is all there is.
+        let mut done = DBInfoActivitySyncState::new(NaiveDate::from_ymd(2026, 3, 1));
+        done.backfill_complete = true;
+        db.set_activity_sync_state(&number, done).unwrap();
+        let unfunded = funded_history(&*db, &number, true, &[], None).unwrap_err();
+        assert!(unfunded.contains("first deposit"));
+    }
+
+    #[test]
+    fn synced_data_wins() {
+        let db = memory_db();
+        let number = "12345678".to_string();
+        db.insert_account("tfsa".to_string(), new_account(number.clone(), "TFSA"))
+            .unwrap();
+        // the deposit was synced, and everything from Jan 1 to the 31st has been pulled.
+        db.insert_account_activities(&number, activities(&[DEPOSIT]))
+            .unwrap();
+        let mut state = DBInfoActivitySyncState::new(NaiveDate::from_ymd(2026, 1, 1));
+        state.synced_through = NaiveDate::from_ymd(2026, 1, 31);
+        db.set_activity_sync_state(&number, state).unwrap();
+        let at = Local.ymd(2026, 1, 7).and_hms(12, 0, 0);
+        let position: AccountPosition = from_str(
+            "(symbol: \"XEQT.TO\", symbolId: 7, openQuantity: 90, closedQuantity: 0, \
+             currentMarketValue: 2790, currentPrice: 31, dayPnl: Some(0), averageEntryPrice: 30, \
+             closedPnl: 0, openPnl: 0, totalCost: 2700, isRealTime: true, isUnderReorg: false)",
+        )
+        .unwrap();
+        db.insert_account_position(at, &number, position, PositionSource::Live)
+            .unwrap();
+        let balance = end_of_day_balance(Currency::CAD, &[], &[]);
+        db.insert_account_balance(at, &number, BalanceKind::CAD, balance.clone(), balance)
+            .unwrap();
+        let report = import(&db, &request("synced", &[DEPOSIT, BUY], false));
+        assert_eq!(report.accounts.len(), 0);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.activities, 0);
+        assert_eq!(report.positions, 0);
+        assert_eq!(report.balances, 0);
+        // the buy isn't in the synced activities, the position and balances differ.
+        let details: Vec<&str> = report.conflicts.iter().map(|c| c.detail.as_str()).collect();
+        assert_eq!(details.len(), 3, "{:?}", details);
+        assert!(details[0].starts_with("Trades Buy XEQT.TO -3004.95"));
+        assert!(details[1].contains("stored as 90 shares"));
+        assert!(details[2].contains("balances synced"));
+        let day = NaiveDate::from_ymd(2026, 1, 7);
+        assert_eq!(
+            db.get_latest_position("tfsa", "XEQT.TO", day)
+                .unwrap()
+                .open_quantity,
+            90.0
+        );
+    }
+
+    #[test]
+    fn combined_balance_uses_the_synced_rate() {
+        let db = memory_db();
+        let day = NaiveDate::from_ymd(2026, 1, 7);
+        let position = |currency: Currency, value: f64| ImportedPosition {
+            account: "12345678".to_string(),
+            date: day,
+            currency,
+            position: AccountPosition {
+                symbol: String::new(),
+                symbol_id: 0,
+                open_quantity: api_number(1.0),
+                closed_quantity: api_number(0.0),
+                current_market_value: api_number(value),
+                current_price: api_number(value),
+                day_profit_and_loss: api_number(0.0),
+                average_entry_price: api_number(value),
+                closed_profit_and_loss: api_number(0.0),
+                open_profit_and_loss: api_number(0.0),
+                total_cost: api_number(value),
+                is_real_time: false,
+                is_under_reorg: false,
+            },
+        };
+        let positions = [
+            position(Currency::CAD, 50.0),
+            position(Currency::USD, 100.0),
+        ];
+        let held: Vec<&ImportedPosition> = positions.iter().collect();
+        let combined = |db: &DBRef| {
+            let balances = end_of_day_balances(&**db, day, &[], &held, &Currency::CAD, 1.35);
+            as_f64(&balances[2].1.total_equity)
+        };
+        assert!(close(combined(&db), 50.0 + 135.0));
+        db.insert_fx_rate(Local.ymd(2026, 1, 7).and_hms(10, 0, 0), 1.25)
+            .unwrap();
+        assert!(close(combined(&db), 50.0 + 125.0));
+    }
+}
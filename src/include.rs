@@ -3,26 +3,43 @@
 //! Started on: November 12, 2020
 
 // Use statements.
-pub use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime};
+pub use base64::encode as base64_encode;
+pub use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc, Weekday,
+};
 pub use clap::{clap_app, AppSettings::ColoredHelp};
 pub use dirs::config_dir;
 pub use flexi_logger::{
     colored_default_format, default_format, AdaptiveFormat, Cleanup, Criterion, Duplicate,
     LevelFilter, LogSpecBuilder, Logger, Naming, ReconfigurationHandle,
 };
-pub use futures::{future::FutureExt, pin_mut, select, try_join};
+pub use futures::{
+    future::{ready, BoxFuture, FutureExt},
+    pin_mut, select, try_join, SinkExt, StreamExt,
+};
 pub use log::{error, info, warn};
+pub use native_tls::TlsConnector as NativeTlsConnector;
 pub use questrade_rs::{
-    Account, AccountBalance, AccountPosition, AccountStatus, AccountType, ApiError,
-    AuthenticationInfo, ClientAccountType, Currency, Questrade,
+    Account, AccountActivity, AccountBalance, AccountBalances, AccountExecution, AccountOrder,
+    AccountPosition, AccountStatus, AccountType, ApiError, AuthenticationInfo, ClientAccountType,
+    Currency, MarketQuote, OrderSide, OrderState, OrderStateFilter, OrderTimeInForce, OrderType,
+    Questrade,
 };
 pub use reqwest::Client;
 pub use ron::{from_str, to_string};
-pub use rustbreak::PathDatabase;
-pub use serde::{Deserialize, Serialize};
+pub use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Row};
+pub use rustbreak::{
+    backend::PathBackend,
+    deser::{Bincode, DeSerializer, Ron, Yaml},
+    PathDatabase,
+};
+pub use serde::{de::DeserializeOwned, Deserialize, Serialize};
+pub use serde_json::{to_string as to_json_string, Number};
 pub use std::{
     cell::RefCell,
-    collections::{hash_map, HashMap},
+    collections::{hash_map, HashMap, HashSet},
+    convert::Infallible,
     error, fmt,
     fs::{read_to_string, DirBuilder, OpenOptions},
     io::{self, Read, Write},
@@ -32,20 +49,13 @@ pub use std::{
     time::Instant,
 };
 pub use tokio;
+pub use tokio_tls::TlsConnector as TokioTlsConnector;
 pub use warp::{
     self,
     http::StatusCode,
     reply::{json, with_status, Json},
 };
 
-// Feature specfic use statements.
-#[cfg(feature = "bincode")]
-pub use rustbreak::deser::Bincode;
-#[cfg(feature = "default")]
-pub use rustbreak::deser::Ron;
-#[cfg(feature = "yaml")]
-pub use rustbreak::deser::Yaml;
-
 // Typedefs.
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 pub type AccountName = String;
@@ -5,22 +5,70 @@
 //! Started on: November 8, 2020
 
 // Local modules to store the real workhorse code.
+mod alerts;
+mod analytics;
+mod backfill;
 mod config;
+mod dividends;
+mod export;
+mod fx;
 mod http_server;
+mod import;
 mod include;
 mod monitor;
+mod notify;
+mod portfolio;
+mod reports;
+mod scheduler;
 mod storage;
+mod symbols;
+mod tax;
+mod watchlist;
 
 // Local use statements.
 use config::Config;
-use include::{tokio, Result};
+use export::export_history;
+use import::import_statements;
+use include::{info, tokio, Result};
 use monitor::Monitor;
+use storage::{migrate_db, open_db, open_db_read_only, BalanceKind};
 
 #[tokio::main]
 // And now we do the main function, wrapped with tokio so it can be async.
 async fn main() -> Result<()> {
     // Reads CLI args and a config file encoded in Ron to generate config.
     let conf = Config::generate()?;
+    // A migration is a one-shot job, so we copy the old DB over and exit.
+    if let Some(from) = &conf.migrate_from {
+        info!("Migrating DataBase from [{}]...", from.display());
+        migrate_db(&conf, from)?;
+        info!("DataBase migration complete.");
+        return Ok(());
+    }
+    // So is an export, it only reads the DB so it can run while the monitor is going.
+    if let Some(request) = &conf.export {
+        let db = open_db_read_only(&conf)?;
+        let kind = BalanceKind::from(&conf.settings.account_balance_currency);
+        let rows = export_history(&*db, request, kind, conf.settings.portfolio.usd_cad_rate)?;
+        info!("Exported {} rows.", rows);
+        return Ok(());
+    }
+    // An import writes to the DB, so on the file backends the monitor should be stopped first.
+    if let Some(request) = &conf.import {
+        let db = open_db(&conf)?;
+        let report = import_statements(
+            &*db,
+            request,
+            &conf.settings.account_balance_currency,
+            conf.settings.portfolio.usd_cad_rate,
+        )?;
+        info!(
+            "Import finished, {} conflicts skipped.",
+            report.conflicts.len()
+        );
+        print!("{}", report);
+        return Ok(());
+    }
     // This creates a new interface to use for the app,
     // it also makes sure that all auth info is valid.
     let mut mon = Monitor::new(conf).await?;
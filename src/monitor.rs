@@ -3,19 +3,73 @@
 //! Started on: November 8, 2020
 
 use super::{
+    alerts::AlertEngine,
+    analytics::{compute_contributions, ContributionStatus},
+    backfill::{backfill_positions, BackfillError, QuestradeCandles},
     config::{AuthInfo, Config},
-    http_server::HTTPServer,
+    fx::RateSource,
+    http_server::{event_channel, EventSender, HTTPServer, SyncEvent},
     include::{
-        error, info, tokio, try_join, warn, AccountNumber, ApiError, Client, Local, Questrade,
-        Result,
+        error, info, tokio, try_join, warn, AccountBalance, AccountNumber, ApiError, Client,
+        DateTime, Datelike, Duration, HashMap, Local, Mutex, NaiveDate, OrderState,
+        OrderStateFilter, PositionSymbol, Questrade, Result, Utc,
     },
-    storage::{DBRef, DB},
+    notify::{Notification, NotifyDispatcher, NotifyEvent},
+    reports::generate_report,
+    scheduler::{ScheduledSync, Scheduler, SyncReason},
+    storage::{
+        open_db, BalanceKind, DBInfoAccountBalance, DBInfoAccountPosition, DBInfoActivitySyncState,
+        DBRef, PositionSource,
+    },
+    symbols::{refresh_symbols, search_symbol_id, tracked_symbols, QuestradeSymbols, SymbolError},
 };
 
+/// How many days back we go for orders and executions, at most.
+const HISTORY_BACKFILL_DAYS: i64 = 30;
+/// Questrade only lets us ask for 31 days of activities at a time.
+const ACTIVITY_WINDOW_DAYS: i64 = 30;
+/// Activities can post a few days late so we always re-check this many days back.
+const ACTIVITY_OVERLAP_DAYS: i64 = 7;
+/// How many windows of history we pull back each sync while backfilling.
+const ACTIVITY_BACKFILL_WINDOWS_PER_SYNC: u32 = 12;
+/// After this many empty windows in a row we decide we've reached the account opening.
+const ACTIVITY_EMPTY_WINDOWS_DONE: u32 = 12;
+
+/// Helper functions
+// Questrade activity dates are midnight Eastern, so windows on UTC midnight never split a day.
+fn utc_midnight(date: NaiveDate) -> DateTime<Utc> {
+    DateTime::<Utc>::from_utc(date.and_hms(0, 0, 0), Utc)
+}
+// orders that can still fill, we keep asking for them until they close.
+fn order_is_open(state: &OrderState) -> bool {
+    !matches!(
+        state,
+        OrderState::Failed
+            | OrderState::Rejected
+            | OrderState::Canceled
+            | OrderState::PartialCanceled
+            | OrderState::Executed
+            | OrderState::Replaced
+            | OrderState::Expired
+    )
+}
+
 pub struct Monitor {
     config: Config,
     db: DBRef,
     qtrade: Questrade,
+    scheduler: Scheduler,
+    fx_source: Box<dyn RateSource>,
+    events: EventSender,
+    alerts: AlertEngine,
+    notify: NotifyDispatcher,
+    // symbol ids of the watchlist, they never change so each is only looked up once.
+    quote_ids: HashMap<PositionSymbol, u32>,
+    // when the last order and execution syncs of each account that went through started.
+    orders_synced: Mutex<HashMap<AccountNumber, DateTime<Local>>>,
+    executions_synced: Mutex<HashMap<AccountNumber, DateTime<Local>>>,
+    // the contribution status of each registered account, and the day it was last warned about.
+    contributions_warned: Mutex<HashMap<AccountNumber, (ContributionStatus, NaiveDate)>>,
     _http: HTTPServer,
 }
 
@@ -30,6 +84,8 @@ impl Monitor {
             init_date_time.time().format("%H:%M:%S"),
             init_date_time.date().format("%Y-%m-%d"),
         );
+        // Set up the notification sinks first so they can tell someone if the auth fails.
+        let notify = NotifyDispatcher::new(&config.settings.notify);
         // Set up our qtrade variable to be set in the match statement.
         info!("Loading questrade authentication info..");
         let qtrade: Questrade = match &config.auth {
@@ -39,7 +95,14 @@ impl Monitor {
                 info!("Attempting initial auth with token: {}", rt);
                 match qtrade.authenticate(rt, false).await {
                     Ok(_) => info!("Initial auth successful!"),
-                    Err(e) => error!("Initial auth failed, with error: {}", e),
+                    Err(e) => {
+                        error!("Initial auth failed, with error: {}", e);
+                        notify.notify(Notification::new(
+                            NotifyEvent::AuthFailure,
+                            "Questrade authentication failed".to_string(),
+                            format!("Initial auth failed, with error: {}", e),
+                        ));
+                    }
                 }
                 config.save_new_auth_info(qtrade.get_auth_info().unwrap())?;
                 qtrade
@@ -53,10 +116,10 @@ impl Monitor {
             }
         };
         info!("Questrade load complete.");
-        // Set up the Rustbreak DB. Depending on enabled features it can have a Ron, Yaml, or
-        // Bincode backend for data storage. No matter what it is a Path database.
+        // Set up the DB. Depending on the config it can have a Ron, Yaml, or Bincode backend
+        // for data storage.
         info!("Loading DataBase...");
-        let db = DBRef::new(match DB::new(&config) {
+        let db: DBRef = match open_db(&config) {
             Ok(db) => {
                 info!("DataBase load complete.");
                 db
@@ -65,18 +128,42 @@ impl Monitor {
                 error!("FATAL ERROR! Could not load DataBase.");
                 return Err(e);
             }
-        });
+        };
+        // Set up the scheduler so we know when the markets are open.
+        info!("Loading market schedule...");
+        let scheduler = Scheduler::new(&config.settings.schedule)?;
+        info!("Market schedule load complete.");
+        // Where the exchange rate we record at each sync comes from.
+        let fx_source = config.settings.fx.rate_source();
+        // Every snapshot we store gets pushed through here to the stream api.
+        let events = event_channel();
+        // the alert rules get checked against what we stored at the end of every sync.
+        let alerts = AlertEngine::new(&config.settings.alerts);
         // Start the http server.
         let _http = HTTPServer::new(
             config.settings.http_bind_addr,
             config.settings.http_port,
             db.clone(),
+            &config.settings.analytics,
+            &config.settings.portfolio,
+            &config.settings.reports,
+            BalanceKind::from(&config.settings.account_balance_currency),
+            events.clone(),
         );
         // Return the created Monitor.
         let mut result = Self {
             config,
             db,
             qtrade,
+            scheduler,
+            fx_source,
+            events,
+            alerts,
+            notify,
+            quote_ids: HashMap::new(),
+            orders_synced: Mutex::new(HashMap::new()),
+            executions_synced: Mutex::new(HashMap::new()),
+            contributions_warned: Mutex::new(HashMap::new()),
             _http,
         };
         // make sure we have valid tokens when we create it.
@@ -89,68 +176,215 @@ impl Monitor {
         // Returned the created interface.
         Ok(result)
     }
-    /// Main event loop of the program. Runs our different async functions
-    /// with timeouts to make sure that we retry on the delay given by user.
+    /// Main event loop of the program. Asks the scheduler when the next sync should happen,
+    /// then runs our different async functions with timeouts to make sure one slow round
+    /// doesn't hold up the rest.
     pub async fn execute_runtime(&mut self) -> Result<()> {
+        // the first round always syncs right away so we know everything is working.
+        let mut next_sync = ScheduledSync::startup();
         loop {
-            let loop_date_time = Local::now();
-            // announce beginning of the loop
-            info!(
-                "Beginning exectution loop @ [{}] on [{}]:",
-                loop_date_time.time().format("%H:%M:%S"),
-                loop_date_time.date().format("%Y-%m-%d"),
-            );
-            // calculate the next timeout based on the delay set by user
-            let timeout = tokio::time::Instant::now()
-                + tokio::time::Duration::from_secs(self.config.settings.delay);
-            // announce start of account sync
-            info!("Starting account sync...");
-            // if the timeout triggers we get and Err so we announce that the timeout triggered,
-            // if not the we get Ok. either way we just announce what happened and move on
-            match tokio::time::timeout_at(timeout, self.sync_accounts()).await {
-                Ok(Ok(_)) => info!("Account sync successful."),
-                Ok(Err(e)) => warn!("Error during account sync: {}", e),
-                Err(_) => warn!("Account sync was not completed within 5 minutes."),
-            }
-            // announce the start of next syncs.
-            info!("Starting balance and position sync...");
-            // run our balance and position syncs together so if there is a delay in either we use
-            // that time to start the next request.
-            match try_join!(
-                tokio::time::timeout_at(timeout, self.sync_account_balances()),
-                tokio::time::timeout_at(timeout, self.sync_account_positions())
-            ) {
-                Ok((Ok(_), Ok(_))) => info!("Balance and position sync successful."),
-                Ok((Err(e), Ok(_))) => {
-                    warn!("Error during balance sync: {}", e);
-                    info!("Position sync successful.");
+            // wait until the scheduler says there is something to do.
+            if let Ok(wait) = (next_sync.at - Local::now()).to_std() {
+                tokio::time::delay_for(wait).await;
+            }
+            if self.scheduler.should_sync(&next_sync) {
+                self.execute_sync().await?;
+                // the first sync closes off any gap from while we weren't running.
+                if next_sync.reason == SyncReason::Startup {
+                    self.backfill_positions().await;
                 }
-                Ok((Ok(_), Err(e))) => {
-                    warn!("Error during position sync: {}", e);
-                    info!("Balance sync successful.");
+                // the sync right after the close has the numbers for the whole day.
+                if next_sync.reason == SyncReason::MarketClose {
+                    self.save_daily_report();
+                    self.send_daily_summary();
                 }
-                Ok((Err(e1), Err(e2))) => error!(
+            } else {
+                info!(
+                    "Polling is off for the {:?} session, skipping sync.",
+                    self.scheduler.session_at(Local::now())
+                );
+            }
+            // ask the scheduler when we should wake up next.
+            next_sync = self.scheduler.next_wake(Local::now());
+            info!(
+                "Next sync ({:?}) scheduled @ [{}] on [{}].",
+                next_sync.reason,
+                next_sync.at.time().format("%H:%M:%S"),
+                next_sync.at.date().format("%Y-%m-%d"),
+            );
+        }
+    }
+
+    // *** Private functions ***
+    /// A single round of syncing.
+    async fn execute_sync(&mut self) -> Result<()> {
+        let loop_date_time = Local::now();
+        // announce beginning of the loop
+        info!(
+            "Beginning exectution loop @ [{}] on [{}] during the {:?} session:",
+            loop_date_time.time().format("%H:%M:%S"),
+            loop_date_time.date().format("%Y-%m-%d"),
+            self.scheduler.session_at(loop_date_time),
+        );
+        // anything that goes wrong is collected here and sent out as one notification at the end.
+        let mut sync_errors: Vec<String> = Vec::new();
+        // calculate the timeout for this round based on the delay set by user
+        let timeout = tokio::time::Instant::now()
+            + tokio::time::Duration::from_secs(self.config.settings.delay);
+        // announce start of account sync
+        info!("Starting account sync...");
+        // if the timeout triggers we get and Err so we announce that the timeout triggered,
+        // if not the we get Ok. either way we just announce what happened and move on
+        match tokio::time::timeout_at(timeout, self.sync_accounts()).await {
+            Ok(Ok(_)) => info!("Account sync successful."),
+            Ok(Err(e)) => {
+                warn!("Error during account sync: {}", e);
+                sync_errors.push(format!("Account sync error: {}", e));
+            }
+            Err(_) => {
+                warn!(
+                    "Account sync was not completed within {} seconds.",
+                    self.config.settings.delay
+                );
+                sync_errors.push("Account sync timeout.".to_string());
+            }
+        }
+        // announce the start of next syncs.
+        info!("Starting balance and position sync...");
+        // run our balance and position syncs together so if there is a delay in either we use
+        // that time to start the next request.
+        match try_join!(
+            tokio::time::timeout_at(timeout, self.sync_account_balances()),
+            tokio::time::timeout_at(timeout, self.sync_account_positions())
+        ) {
+            Ok((Ok(_), Ok(_))) => info!("Balance and position sync successful."),
+            Ok((Err(e), Ok(_))) => {
+                warn!("Error during balance sync: {}", e);
+                info!("Position sync successful.");
+                sync_errors.push(format!("Balance sync error: {}", e));
+            }
+            Ok((Ok(_), Err(e))) => {
+                warn!("Error during position sync: {}", e);
+                info!("Balance sync successful.");
+                sync_errors.push(format!("Position sync error: {}", e));
+            }
+            Ok((Err(e1), Err(e2))) => {
+                error!(
                     "Error during balance and position sync.\n\
                     Balance error: {} Position error: {}",
                     e1, e2
-                ),
-                Err(_) => warn!("Balance and position sync timeout."),
-            }
-            // once we are done all of the syncing we save the info,
-            // currently the only way to exit this loop is this function failing
-            info!("Saving DB...");
-            self.save_db()?;
-            info!("DB save successful.");
-            // if we still have time to wait we announce it
-            if tokio::time::Instant::now() < timeout {
-                info!("Waiting for next execution...");
-            }
-            // finally we delay here until there is something to do
-            tokio::time::delay_until(timeout).await;
+                );
+                sync_errors.push(format!("Balance sync error: {}", e1));
+                sync_errors.push(format!("Position sync error: {}", e2));
+            }
+            Err(_) => {
+                warn!("Balance and position sync timeout.");
+                sync_errors.push("Balance and position sync timeout.".to_string());
+            }
+        }
+        // announce the start of the trade history syncs.
+        info!("Starting order and execution sync...");
+        match try_join!(
+            tokio::time::timeout_at(timeout, self.sync_account_orders()),
+            tokio::time::timeout_at(timeout, self.sync_account_executions())
+        ) {
+            Ok((Ok(_), Ok(_))) => info!("Order and execution sync successful."),
+            Ok((Err(e), Ok(_))) => {
+                warn!("Error during order sync: {}", e);
+                info!("Execution sync successful.");
+                sync_errors.push(format!("Order sync error: {}", e));
+            }
+            Ok((Ok(_), Err(e))) => {
+                warn!("Error during execution sync: {}", e);
+                info!("Order sync successful.");
+                sync_errors.push(format!("Execution sync error: {}", e));
+            }
+            Ok((Err(e1), Err(e2))) => {
+                error!(
+                    "Error during order and execution sync.\n\
+                    Order error: {} Execution error: {}",
+                    e1, e2
+                );
+                sync_errors.push(format!("Order sync error: {}", e1));
+                sync_errors.push(format!("Execution sync error: {}", e2));
+            }
+            Err(_) => {
+                warn!("Order and execution sync timeout.");
+                sync_errors.push("Order and execution sync timeout.".to_string());
+            }
+        }
+        // announce the start of the activity sync.
+        info!("Starting activity sync...");
+        match tokio::time::timeout_at(timeout, self.sync_account_activities()).await {
+            Ok(Ok(_)) => {
+                info!("Activity sync successful.");
+                self.check_contributions();
+            }
+            Ok(Err(e)) => {
+                warn!("Error during activity sync: {}", e);
+                sync_errors.push(format!("Activity sync error: {}", e));
+            }
+            Err(_) => {
+                warn!("Activity sync timeout.");
+                sync_errors.push("Activity sync timeout.".to_string());
+            }
         }
+        // quotes of the watchlist, only if there is anything on it.
+        if !self.config.settings.watchlist.symbols.is_empty() {
+            info!("Starting quote sync...");
+            match tokio::time::timeout_at(timeout, self.sync_quotes()).await {
+                Ok(Ok(_)) => info!("Quote sync successful."),
+                Ok(Err(e)) => {
+                    warn!("Error during quote sync: {}", e);
+                    sync_errors.push(format!("Quote sync error: {}", e));
+                }
+                Err(_) => {
+                    warn!("Quote sync timeout.");
+                    sync_errors.push("Quote sync timeout.".to_string());
+                }
+            }
+        }
+        // look up anything new we are holding or watching.
+        if self.config.settings.symbols.enabled {
+            info!("Starting symbol info refresh...");
+            match tokio::time::timeout_at(timeout, self.sync_symbols()).await {
+                Ok(Ok(stored)) => info!("Symbol info refresh successful, {} updated.", stored),
+                Ok(Err(e)) => {
+                    warn!("Error during symbol info refresh: {}", e);
+                    sync_errors.push(format!("Symbol info refresh error: {}", e));
+                }
+                Err(_) => {
+                    warn!("Symbol info refresh timeout.");
+                    sync_errors.push("Symbol info refresh timeout.".to_string());
+                }
+            }
+        }
+        if !sync_errors.is_empty() {
+            self.notify.notify(Notification::new(
+                NotifyEvent::SyncError,
+                format!("{} error(s) during sync", sync_errors.len()),
+                sync_errors.join("\n"),
+            ));
+        }
+        // now that everything is stored we see if any of the alert rules should go off.
+        info!("Checking alerts...");
+        let kind = BalanceKind::from(&self.config.settings.account_balance_currency);
+        let fired = self.alerts.evaluate(&*self.db, kind);
+        info!("Alert check complete, {} fired.", fired.len());
+        for alert in fired.iter() {
+            self.notify.notify(Notification::new(
+                NotifyEvent::Alert,
+                format!("Alert on account {}", alert.account),
+                format!("{} (value: {:.2})", alert.rule, alert.value),
+            ));
+        }
+        // once we are done all of the syncing we save the info,
+        // currently the only way to exit the main loop is this function failing
+        info!("Saving DB...");
+        self.save_db()?;
+        info!("DB save successful.");
+        Ok(())
     }
-
-    // *** Private functions ***
     async fn renew_auth(&mut self) -> Result<()> {
         info!("Renewing Questrade authentication...");
         // Here we make the request to the questrade server to get new auth info.
@@ -160,7 +394,14 @@ impl Monitor {
             .await
         {
             Ok(_) => info!("Authentication renewal successful!"),
-            Err(e) => error!("Authentication renewal failed, with error: {}.", e),
+            Err(e) => {
+                error!("Authentication renewal failed, with error: {}.", e);
+                self.notify.notify(Notification::new(
+                    NotifyEvent::AuthFailure,
+                    "Questrade authentication failed".to_string(),
+                    format!("Authentication renewal failed, with error: {}.", e),
+                ));
+            }
         }
         // Here we save it to the config object and the local auth file.
         self.config
@@ -190,86 +431,440 @@ impl Monitor {
                 .iter()
                 .find(|ats| ats.check_account_match(&acc))
             {
-                Some(an) => (*self.db).db.write(|db_info| -> Result<()> {
+                Some(an) => {
                     let name = if an.name_is_empty() {
                         acc.number.clone()
                     } else {
                         an.name()
                     };
-                    db_info.insert_account(name, acc)?;
-                    Ok(())
-                })?,
+                    self.db.insert_account(name, acc)?;
+                }
                 None => continue,
-            }?;
+            }
         }
         Ok(())
     }
 
     async fn sync_account_balances(&self) -> Result<()> {
-        for acct_num in (*self.db)
-            .db
-            .read(|db_info| {
-                db_info
-                    .iter_accounts()
-                    .map(|dbi| dbi.number.clone())
-                    .collect::<Vec<AccountNumber>>()
-            })?
-            .drain(..)
-        {
+        // the combined balances come back in every currency, we only keep the one in the config.
+        let combined_currency = &self.config.settings.account_balance_currency;
+        // every account gives the same rate, so we only record it once per sync.
+        let mut usd_cad: Option<f64> = None;
+        for acct_num in self.db.list_account_numbers()?.drain(..) {
             let balances = match self.qtrade.account_balance(&acct_num).await {
                 Ok(acct_bals) => acct_bals,
                 Err(e) => return Err(e),
             };
-            (*self.db).db.write(|db_info| -> Result<()> {
-                db_info.insert_account_balance(
-                    Local::now(),
+            let now = Local::now();
+            // each balance is paired with the sod balance of the same currency, if Questrade
+            // didn't send one the balance stands in for it.
+            let sod_of = |sods: &[AccountBalance], balance: &AccountBalance| {
+                sods.iter()
+                    .find(|bl| bl.currency == balance.currency)
+                    .unwrap_or(balance)
+                    .clone()
+            };
+            for balance in balances.per_currency_balances.iter() {
+                self.store_balance(
+                    now,
                     &acct_num,
-                    balances
-                        .per_currency_balances
-                        .iter()
-                        .find(|bl| bl.currency == self.config.settings.account_balance_currency)
-                        .unwrap_or(&balances.per_currency_balances[0])
-                        .clone(),
-                    balances
-                        .sod_per_currency_balances
-                        .iter()
-                        .find(|bl| bl.currency == self.config.settings.account_balance_currency)
-                        .unwrap_or(&balances.per_currency_balances[0])
-                        .clone(),
+                    BalanceKind::from(&balance.currency),
+                    balance.clone(),
+                    sod_of(&balances.sod_per_currency_balances, balance),
                 )?;
-                Ok(())
-            })??;
+            }
+            if let Some(balance) = balances
+                .combined_balances
+                .iter()
+                .find(|bl| bl.currency == *combined_currency)
+            {
+                self.store_balance(
+                    now,
+                    &acct_num,
+                    BalanceKind::Combined,
+                    balance.clone(),
+                    sod_of(&balances.sod_combined_balances, balance),
+                )?;
+            }
+            if usd_cad.is_none() {
+                usd_cad = self.fx_source.usd_cad(&balances);
+            }
+        }
+        match usd_cad {
+            Some(rate) => self.db.insert_fx_rate(Local::now(), rate)?,
+            None => warn!("Could not work out the USD/CAD rate this sync."),
         }
         Ok(())
     }
 
+    // stores a balance and lets anyone on the stream api know about it.
+    fn store_balance(
+        &self,
+        now: DateTime<Local>,
+        acct_num: &AccountNumber,
+        kind: BalanceKind,
+        balance: AccountBalance,
+        sod: AccountBalance,
+    ) -> Result<()> {
+        self.db
+            .insert_account_balance(now, acct_num, kind, balance.clone(), sod)?;
+        self.publish(SyncEvent::Balance {
+            account: acct_num.clone(),
+            date: now.date().naive_local(),
+            kind,
+            balance: DBInfoAccountBalance::new(balance, now.time()),
+        });
+        Ok(())
+    }
+    // nobody listening is the normal case, so a send with no receivers isn't an error.
+    fn publish(&self, event: SyncEvent) {
+        let _ = self.events.send(event);
+    }
+
     async fn sync_account_positions(&self) -> Result<()> {
-        for acct_num in (*self.db)
-            .db
-            .read(|db_info| {
-                db_info
-                    .iter_accounts()
-                    .map(|dbi| dbi.number.clone())
-                    .collect::<Vec<AccountNumber>>()
-            })?
-            .drain(..)
-        {
+        for acct_num in self.db.list_account_numbers()?.drain(..) {
             let positions = match self.qtrade.account_positions(&acct_num).await {
                 Ok(acct_poss) => acct_poss,
                 Err(e) => return Err(e),
             };
             for pos in positions {
-                (*self.db).db.write(|db_info| -> Result<()> {
-                    db_info.insert_account_position(Local::now(), &acct_num, pos)?;
-                    Ok(())
-                })??;
+                let now = Local::now();
+                self.db.insert_account_position(
+                    now,
+                    &acct_num,
+                    pos.clone(),
+                    PositionSource::Live,
+                )?;
+                self.publish(SyncEvent::Position {
+                    account: acct_num.clone(),
+                    date: now.date().naive_local(),
+                    position: DBInfoAccountPosition::new(pos, now.time(), PositionSource::Live),
+                });
             }
         }
         Ok(())
     }
 
+    async fn sync_account_orders(&self) -> Result<()> {
+        for acct_num in self.db.list_account_numbers()?.drain(..) {
+            let now = Local::now();
+            let earliest =
+                (Local::today() - Duration::days(HISTORY_BACKFILL_DAYS)).and_hms(0, 0, 0);
+            // an account that never traded has nothing stored.
+            let stored = self
+                .db
+                .get_orders(
+                    &acct_num,
+                    earliest.naive_local().date(),
+                    now.naive_local().date(),
+                )
+                .unwrap_or_default();
+            // pick up where the last sync that went through started. Right after a restart the
+            // newest order stored stands in for it, so the days we were down get fetched too.
+            let last_sync = self.orders_synced.lock().unwrap().get(&acct_num).copied();
+            let mut start = last_sync
+                .or_else(|| stored.iter().map(|o| o.update_time).max())
+                .unwrap_or(earliest);
+            // orders are found by when they were placed, so go back far enough to see the
+            // open ones again.
+            if let Some(placed) = stored
+                .iter()
+                .filter(|o| order_is_open(&o.state))
+                .map(|o| o.creation_time)
+                .min()
+            {
+                start = start.min(placed);
+            }
+            let start = start.max(earliest);
+            let orders = match self
+                .qtrade
+                .account_orders(
+                    &acct_num,
+                    Some(start.with_timezone(&Utc)),
+                    Some(now.with_timezone(&Utc)),
+                    Some(OrderStateFilter::All),
+                )
+                .await
+            {
+                Ok(acct_ords) => acct_ords,
+                Err(e) => return Err(e),
+            };
+            for order in orders {
+                self.db.insert_account_order(&acct_num, order)?;
+            }
+            self.orders_synced.lock().unwrap().insert(acct_num, now);
+        }
+        Ok(())
+    }
+
+    async fn sync_account_executions(&self) -> Result<()> {
+        for acct_num in self.db.list_account_numbers()?.drain(..) {
+            // same as the orders, from the last sync or the newest execution stored.
+            let now = Local::now();
+            let earliest =
+                (Local::today() - Duration::days(HISTORY_BACKFILL_DAYS)).and_hms(0, 0, 0);
+            let last_sync = self
+                .executions_synced
+                .lock()
+                .unwrap()
+                .get(&acct_num)
+                .copied();
+            let start = last_sync
+                .or_else(|| {
+                    self.db
+                        .get_executions(
+                            &acct_num,
+                            earliest.naive_local().date(),
+                            now.naive_local().date(),
+                        )
+                        .unwrap_or_default()
+                        .iter()
+                        .map(|e| e.timestamp)
+                        .max()
+                })
+                .unwrap_or(earliest)
+                .max(earliest);
+            let executions = match self
+                .qtrade
+                .account_executions(
+                    &acct_num,
+                    Some(start.with_timezone(&Utc)),
+                    Some(now.with_timezone(&Utc)),
+                )
+                .await
+            {
+                Ok(acct_execs) => acct_execs,
+                Err(e) => return Err(e),
+            };
+            for execution in executions {
+                self.db.insert_account_execution(&acct_num, execution)?;
+            }
+            self.executions_synced.lock().unwrap().insert(acct_num, now);
+        }
+        Ok(())
+    }
+
+    async fn sync_account_activities(&self) -> Result<()> {
+        for acct_num in self.db.list_account_numbers()?.drain(..) {
+            let today = Local::today().naive_local();
+            // a new account starts one window back, the backfill takes care of the rest.
+            let mut state = self
+                .db
+                .get_activity_sync_state(&acct_num)?
+                .unwrap_or_else(|| {
+                    DBInfoActivitySyncState::new(today - Duration::days(ACTIVITY_WINDOW_DAYS))
+                });
+            // first bring us up to today, one window at a time.
+            let mut start = state.synced_through - Duration::days(ACTIVITY_OVERLAP_DAYS);
+            while start <= today {
+                let end =
+                    (start + Duration::days(ACTIVITY_WINDOW_DAYS)).min(today + Duration::days(1));
+                let activities = self
+                    .qtrade
+                    .account_activity(&acct_num, utc_midnight(start), utc_midnight(end))
+                    .await?;
+                self.db.insert_account_activities(&acct_num, activities)?;
+                start = end;
+            }
+            state.synced_through = today;
+            self.db.set_activity_sync_state(&acct_num, state.clone())?;
+            // then keep walking back until we run into a long stretch of nothing, which means
+            // we've gone past the day the account was opened.
+            let was_complete = state.backfill_complete;
+            for _ in 0..ACTIVITY_BACKFILL_WINDOWS_PER_SYNC {
+                if state.backfill_complete {
+                    break;
+                }
+                let end = state.backfilled_to;
+                let start = end - Duration::days(ACTIVITY_WINDOW_DAYS);
+                let activities = self
+                    .qtrade
+                    .account_activity(&acct_num, utc_midnight(start), utc_midnight(end))
+                    .await?;
+                if activities.is_empty() {
+                    state.empty_windows += 1;
+                    state.backfill_complete = state.empty_windows >= ACTIVITY_EMPTY_WINDOWS_DONE;
+                } else {
+                    state.empty_windows = 0;
+                    self.db.insert_account_activities(&acct_num, activities)?;
+                }
+                state.backfilled_to = start;
+                self.db.set_activity_sync_state(&acct_num, state.clone())?;
+            }
+            if !was_complete && state.backfill_complete {
+                info!(
+                    "Activity history for account {} is complete back to {}.",
+                    acct_num, state.backfilled_to
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn sync_quotes(&mut self) -> Result<()> {
+        let client = Client::new();
+        for symbol in self.config.settings.watchlist.symbols.iter() {
+            if self.quote_ids.contains_key(symbol) {
+                continue;
+            }
+            let auth = match self.qtrade.get_auth_info() {
+                Some(auth) => auth,
+                None => return Err(Box::new(SymbolError::NotAuthenticatedError)),
+            };
+            // one symbol that can't be looked up shouldn't hold back the quotes of the rest.
+            match search_symbol_id(&client, &auth, symbol).await {
+                Ok(Some(id)) => {
+                    self.quote_ids.insert(symbol.clone(), id);
+                }
+                Ok(None) => warn!(
+                    "Could not find a Questrade symbol id for {}, skipping its quotes.",
+                    symbol
+                ),
+                Err(e) => warn!(
+                    "Could not look up the Questrade symbol id for {}, skipping its quotes. Error: {}",
+                    symbol, e
+                ),
+            }
+        }
+        if self.quote_ids.is_empty() {
+            return Ok(());
+        }
+        let ids: Vec<u32> = self.quote_ids.values().copied().collect();
+        let now = Local::now();
+        for quote in self.qtrade.market_quote(&ids).await? {
+            self.db.insert_quote(now, quote)?;
+        }
+        Ok(())
+    }
+
+    async fn sync_symbols(&self) -> Result<usize> {
+        let source = match self.qtrade.get_auth_info() {
+            Some(auth) => QuestradeSymbols::new(auth),
+            None => return Err(Box::new(SymbolError::NotAuthenticatedError)),
+        };
+        let today = Local::today().naive_local();
+        let symbols = tracked_symbols(&*self.db, today)?;
+        refresh_symbols(
+            &*self.db,
+            &source,
+            &self.config.settings.symbols,
+            &symbols,
+            today,
+        )
+        .await
+    }
+
+    // rebuilds the positions of days we missed, it can be run again so errors are only logged.
+    async fn backfill_positions(&self) {
+        let backfill = &self.config.settings.backfill;
+        if !backfill.enabled {
+            return;
+        }
+        info!("Backfilling missed positions...");
+        let candles = match self.qtrade.get_auth_info() {
+            Some(auth) => QuestradeCandles::new(auth),
+            None => {
+                warn!(
+                    "Could not backfill, with error: {}",
+                    BackfillError::NotAuthenticatedError
+                );
+                return;
+            }
+        };
+        let today = Local::today().naive_local();
+        match backfill_positions(&*self.db, &candles, backfill, today).await {
+            Ok(stored) => {
+                info!("Backfill complete, {} position(s) stored.", stored);
+                if let Err(e) = self.save_db() {
+                    warn!("Could not save backfilled positions, with error: {}", e);
+                }
+            }
+            Err(e) => warn!("Could not backfill, with error: {}", e),
+        }
+    }
+    // warns about any registered account close to or over its contribution room this year.
+    fn check_contributions(&self) {
+        let analytics = &self.config.settings.analytics;
+        if analytics.contribution_room.is_empty() {
+            return;
+        }
+        let today = Local::today().naive_local();
+        let usd_cad_rate = self.config.settings.portfolio.usd_cad_rate;
+        match compute_contributions(&*self.db, analytics, usd_cad_rate, today.year(), today) {
+            Ok(report) => {
+                // the same warning every poll is noise, so it only comes back when the status of
+                // the account changes or on a new day.
+                let mut warned = self.contributions_warned.lock().unwrap();
+                for account in report.accounts.iter() {
+                    let last = warned.insert(account.number.clone(), (account.status, today));
+                    if last == Some((account.status, today)) {
+                        continue;
+                    }
+                    if let Some(warning) = account.warning(report.year) {
+                        warn!("{}", warning);
+                    }
+                }
+            }
+            Err(e) => warn!("Could not check contribution room, with error: {}", e),
+        }
+    }
+    // the full report of the day, saved to the reports directory.
+    fn save_daily_report(&self) {
+        let reports = &self.config.settings.reports;
+        if !reports.enabled {
+            return;
+        }
+        info!("Generating daily report...");
+        let kind = BalanceKind::from(&self.config.settings.account_balance_currency);
+        match generate_report(&*self.db, reports, Local::today().naive_local(), kind) {
+            Ok(files) => info!("Daily report saved to {} file(s).", files.len()),
+            Err(e) => warn!("Could not generate the daily report, with error: {}", e),
+        }
+    }
+    // a short rundown of each account at the close, plus how many alerts went off today.
+    fn send_daily_summary(&self) {
+        if !self.notify.wants(NotifyEvent::DailySummary) {
+            return;
+        }
+        match self.daily_summary() {
+            Ok(message) => self.notify.notify(Notification::new(
+                NotifyEvent::DailySummary,
+                format!("Daily summary for {}", Local::today().format("%Y-%m-%d")),
+                message,
+            )),
+            Err(e) => warn!("Could not build the daily summary, with error: {}", e),
+        }
+    }
+    fn daily_summary(&self) -> Result<String> {
+        let today = Local::today().naive_local();
+        let kind = BalanceKind::from(&self.config.settings.account_balance_currency);
+        let mut lines = Vec::new();
+        for acct_num in self.db.list_account_numbers()? {
+            let sod = self.db.get_start_of_day_balance(&acct_num, kind, today);
+            let latest = self.db.get_latest_balance(&acct_num, kind, today);
+            match (sod, latest) {
+                (Ok(sod), Ok(latest)) => {
+                    let change = latest.total_equity - sod.total_equity;
+                    let percent = if sod.total_equity == 0.0 {
+                        0.0
+                    } else {
+                        change / sod.total_equity * 100.0
+                    };
+                    lines.push(format!(
+                        "{}: total equity {:.2} {:?}, {:+.2} ({:+.2}%) today",
+                        acct_num, latest.total_equity, latest.currency, change, percent
+                    ));
+                }
+                _ => lines.push(format!("{}: no balances stored today", acct_num)),
+            }
+        }
+        let alerts = self.db.get_alerts(today, today)?;
+        lines.push(format!("{} alert(s) fired today.", alerts.len()));
+        Ok(lines.join("\n"))
+    }
+
     fn save_db(&self) -> Result<()> {
-        (*self.db).db.save()?;
+        self.db.save()?;
         Ok(())
     }
 }
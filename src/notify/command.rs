@@ -0,0 +1,46 @@
+//! Sub module with the notifier that runs a local command, like notify-send.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::include::{tokio, BoxFuture, FutureExt, Result},
+    Notification, Notifier, NotifyError,
+};
+
+/// Runs a program with the variables filled into its arguments.
+pub struct CommandNotifier {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandNotifier {
+    pub fn new(program: String, args: Vec<String>) -> Self {
+        Self { program, args }
+    }
+}
+
+impl Notifier for CommandNotifier {
+    fn describe(&self) -> String {
+        format!("Command {}", self.program)
+    }
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let status = tokio::process::Command::new(&self.program)
+                .args(
+                    self.args
+                        .iter()
+                        .map(|arg| notification.render(arg, str::to_string)),
+                )
+                // if the dispatcher gives up on us the program shouldn't hang around.
+                .kill_on_drop(true)
+                .status()
+                .await?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(NotifyError::CommandStatusError(self.program.clone(), status.code()).into())
+            }
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,53 @@
+//! Module containing the errors that can come up while sending notifications.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{error, fmt};
+
+#[derive(Debug)]
+// Enum representing errors that are possible while handing a notification to a sink.
+pub enum NotifyError {
+    WebhookStatusError(u16),
+    SmtpReplyError(String, String),
+    SmtpClosedError,
+    CommandStatusError(String, Option<i32>),
+    TimeoutError(u64),
+}
+
+impl error::Error for NotifyError {}
+
+impl fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WebhookStatusError(status) => write!(
+                f,
+                "Could not send notification! Webhook replied with status {}.",
+                status
+            ),
+            Self::SmtpReplyError(command, reply) => write!(
+                f,
+                "Could not send notification! SMTP server did not accept {}, replied: {}",
+                command, reply
+            ),
+            Self::SmtpClosedError => write!(
+                f,
+                "Could not send notification! SMTP server closed the connection."
+            ),
+            Self::CommandStatusError(program, Some(code)) => write!(
+                f,
+                "Could not send notification! Command {} exited with code {}.",
+                program, code
+            ),
+            Self::CommandStatusError(program, None) => write!(
+                f,
+                "Could not send notification! Command {} was killed.",
+                program
+            ),
+            Self::TimeoutError(secs) => write!(
+                f,
+                "Could not send notification! Sink did not finish within {} seconds.",
+                secs
+            ),
+        }
+    }
+}
@@ -0,0 +1,416 @@
+//! Module to send notifications out to webhooks, email, and local commands.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::include::{
+    to_json_string, tokio, warn, Arc, BoxFuture, DateTime, Deserialize, Local, OpenOptions,
+    PathBuf, Result, Serialize, Write,
+};
+
+/// Sub modules
+mod command;
+mod errors;
+mod smtp;
+mod webhook;
+
+/// Re-export sub-modules so we can read from them in other modules.
+pub use command::*;
+pub use errors::*;
+pub use smtp::*;
+pub use webhook::*;
+
+/// How long a sink gets to send one notification before we count it as a failure.
+const SEND_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+/// What a notification is about, each sink can pick which of these it gets.
+pub enum NotifyEvent {
+    SyncError,
+    AuthFailure,
+    DailySummary,
+    Alert,
+}
+
+fn all_events() -> Vec<NotifyEvent> {
+    vec![
+        NotifyEvent::SyncError,
+        NotifyEvent::AuthFailure,
+        NotifyEvent::DailySummary,
+        NotifyEvent::Alert,
+    ]
+}
+fn default_webhook_body() -> String {
+    DEFAULT_WEBHOOK_BODY.to_string()
+}
+fn default_smtp_port() -> u16 {
+    25
+}
+
+#[derive(Debug, Serialize, Clone)]
+/// A single notification, the same one is handed to every sink.
+pub struct Notification {
+    pub event: NotifyEvent,
+    pub title: String,
+    pub message: String,
+    pub time: DateTime<Local>,
+}
+
+impl Notification {
+    pub fn new(event: NotifyEvent, title: String, message: String) -> Self {
+        Self {
+            event,
+            title,
+            message,
+            time: Local::now(),
+        }
+    }
+    /// Fills in the %event, %title, %message, and %time variables of a template, with each
+    /// value passed through the escape function first. The template is read once, so a value
+    /// holding a variable name is left as is.
+    pub fn render(&self, template: &str, escape: fn(&str) -> String) -> String {
+        let values = [
+            ("%event", format!("{:?}", self.event)),
+            ("%title", self.title.clone()),
+            ("%message", self.message.clone()),
+            ("%time", self.time.to_rfc3339()),
+        ];
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(index) = rest.find('%') {
+            rendered.push_str(&rest[..index]);
+            rest = &rest[index..];
+            match values.iter().find(|(name, _)| rest.starts_with(name)) {
+                Some((name, value)) => {
+                    rendered.push_str(&escape(value));
+                    rest = &rest[name.len()..];
+                }
+                None => {
+                    rendered.push('%');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+}
+
+/// Anything that can pass a notification on to a person.
+pub trait Notifier: Send + Sync {
+    /// Short description for the logs and the dead-letter file.
+    fn describe(&self) -> String;
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>>;
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// One of the sinks in the notify section of the config, each has the list of events it gets.
+pub enum SinkConfig {
+    Webhook {
+        url: String,
+        #[serde(default = "default_webhook_body")]
+        body: String,
+        #[serde(default)]
+        headers: Vec<(String, String)>,
+        #[serde(default = "all_events")]
+        events: Vec<NotifyEvent>,
+    },
+    Smtp {
+        host: String,
+        #[serde(default = "default_smtp_port")]
+        port: u16,
+        #[serde(default)]
+        starttls: bool,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+        #[serde(default = "all_events")]
+        events: Vec<NotifyEvent>,
+    },
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default = "all_events")]
+        events: Vec<NotifyEvent>,
+    },
+}
+
+impl SinkConfig {
+    fn build(&self) -> Sink {
+        match self.clone() {
+            Self::Webhook {
+                url,
+                body,
+                headers,
+                events,
+            } => Sink {
+                notifier: Box::new(WebhookNotifier::new(url, body, headers)),
+                events,
+            },
+            Self::Smtp {
+                host,
+                port,
+                starttls,
+                username,
+                password,
+                from,
+                to,
+                events,
+            } => Sink {
+                notifier: Box::new(SmtpNotifier::new(
+                    host,
+                    port,
+                    starttls,
+                    username.zip(password),
+                    from,
+                    to,
+                )),
+                events,
+            },
+            Self::Command {
+                program,
+                args,
+                events,
+            } => Sink {
+                notifier: Box::new(CommandNotifier::new(program, args)),
+                events,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// The notify section of the config file.
+pub struct NotifyConfig {
+    pub sinks: Vec<SinkConfig>,
+    /// How many more times a failed send is tried before it goes to the dead-letter file.
+    pub retries: u32,
+    /// Seconds to wait before the first retry, doubled for each one after.
+    pub retry_delay: u64,
+    /// Notifications no sink could take are written here, one Json object per line.
+    pub dead_letter_file: PathBuf,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            sinks: Vec::new(),
+            retries: 3,
+            retry_delay: 10,
+            dead_letter_file: PathBuf::from("dead_letter.log"),
+        }
+    }
+}
+
+struct Sink {
+    notifier: Box<dyn Notifier>,
+    events: Vec<NotifyEvent>,
+}
+
+#[derive(Serialize)]
+// a line of the dead-letter file.
+struct DeadLetter<'a> {
+    sink: String,
+    error: String,
+    notification: &'a Notification,
+}
+
+#[derive(Clone)]
+/// Hands notifications to every sink that wants them, in the background so a slow sink never
+/// holds up a sync.
+pub struct NotifyDispatcher {
+    sinks: Arc<Vec<Sink>>,
+    retries: u32,
+    retry_delay: u64,
+    dead_letter_file: PathBuf,
+}
+
+impl NotifyDispatcher {
+    pub fn new(config: &NotifyConfig) -> Self {
+        Self {
+            sinks: Arc::new(config.sinks.iter().map(SinkConfig::build).collect()),
+            retries: config.retries,
+            retry_delay: config.retry_delay,
+            dead_letter_file: config.dead_letter_file.clone(),
+        }
+    }
+    /// Lets callers skip building a notification nobody is going to get.
+    pub fn wants(&self, event: NotifyEvent) -> bool {
+        self.sinks.iter().any(|s| s.events.contains(&event))
+    }
+    pub fn notify(&self, notification: Notification) {
+        for index in 0..self.sinks.len() {
+            if self.sinks[index].events.contains(&notification.event) {
+                tokio::spawn(self.clone().deliver(index, notification.clone()));
+            }
+        }
+    }
+
+    // tries the sink until it works or we run out of retries.
+    async fn deliver(self, index: usize, notification: Notification) {
+        let notifier = &self.sinks[index].notifier;
+        let mut delay = self.retry_delay;
+        let mut attempt = 0;
+        loop {
+            let error = match tokio::time::timeout(
+                tokio::time::Duration::from_secs(SEND_TIMEOUT_SECS),
+                notifier.send(&notification),
+            )
+            .await
+            {
+                Ok(Ok(())) => return,
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => NotifyError::TimeoutError(SEND_TIMEOUT_SECS).to_string(),
+            };
+            if attempt >= self.retries {
+                warn!(
+                    "Giving up on notification to {}, with error: {}",
+                    notifier.describe(),
+                    error
+                );
+                self.dead_letter(notifier.describe(), error, &notification);
+                return;
+            }
+            warn!(
+                "Notification to {} failed, retrying in {} seconds. Error: {}",
+                notifier.describe(),
+                delay,
+                error
+            );
+            tokio::time::delay_for(tokio::time::Duration::from_secs(delay)).await;
+            delay *= 2;
+            attempt += 1;
+        }
+    }
+    fn dead_letter(&self, sink: String, error: String, notification: &Notification) {
+        let line = DeadLetter {
+            sink,
+            error,
+            notification,
+        };
+        if let Err(e) = self.write_dead_letter(&line) {
+            warn!("Could not write to the dead-letter file, with error: {}", e);
+        }
+    }
+    fn write_dead_letter(&self, line: &DeadLetter) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.dead_letter_file)?;
+        file.write_all(format!("{}\n", to_json_string(line)?).as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::include::FutureExt, *};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    // fails the first few sends, then takes everything after.
+    struct FlakyNotifier {
+        failures: u32,
+        attempts: Arc<AtomicU32>,
+    }
+
+    impl Notifier for FlakyNotifier {
+        fn describe(&self) -> String {
+            "Flaky".to_string()
+        }
+        fn send<'a>(&'a self, _: &'a Notification) -> BoxFuture<'a, Result<()>> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt < self.failures {
+                    Err(NotifyError::WebhookStatusError(503).into())
+                } else {
+                    Ok(())
+                }
+            }
+            .boxed()
+        }
+    }
+
+    // a dispatcher with one flaky sink and no wait between retries.
+    fn dispatcher(failures: u32, retries: u32, file: &str) -> (NotifyDispatcher, Arc<AtomicU32>) {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let dead_letter_file = std::env::temp_dir().join(file);
+        let _ = std::fs::remove_file(&dead_letter_file);
+        let sink = Sink {
+            notifier: Box::new(FlakyNotifier {
+                failures,
+                attempts: attempts.clone(),
+            }),
+            events: all_events(),
+        };
+        let dispatcher = NotifyDispatcher {
+            sinks: Arc::new(vec![sink]),
+            retries,
+            retry_delay: 0,
+            dead_letter_file,
+        };
+        (dispatcher, attempts)
+    }
+    fn notification() -> Notification {
+        Notification::new(
+            NotifyEvent::SyncError,
+            "Sync failed".to_string(),
+            "body".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn retries_until_the_send_works() {
+        let (dispatcher, attempts) = dispatcher(2, 3, "qtmon_notify_retry.log");
+        let file = dispatcher.dead_letter_file.clone();
+        dispatcher.deliver(0, notification()).await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert!(!file.exists());
+    }
+
+    #[tokio::test]
+    async fn gives_up_to_the_dead_letter_file() {
+        let (dispatcher, attempts) = dispatcher(10, 2, "qtmon_notify_dead_letter.log");
+        let file = dispatcher.dead_letter_file.clone();
+        dispatcher.clone().deliver(0, notification()).await;
+        dispatcher.deliver(0, notification()).await;
+        // the first send and both retries, for each of the two notifications.
+        assert_eq!(attempts.load(Ordering::SeqCst), 6);
+        let contents = std::fs::read_to_string(&file).unwrap();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["sink"], "Flaky");
+        assert_eq!(
+            lines[0]["error"],
+            NotifyError::WebhookStatusError(503).to_string().as_str()
+        );
+        assert_eq!(lines[0]["notification"]["title"], "Sync failed");
+        assert_eq!(lines[0]["notification"]["event"], "SyncError");
+    }
+
+    #[test]
+    fn render_fills_each_variable_once() {
+        let notification = Notification::new(
+            NotifyEvent::Alert,
+            "Price %message".to_string(),
+            "100% up %title".to_string(),
+        );
+        assert_eq!(
+            notification.render("[%event] %title: %message %%time%", str::to_string),
+            format!(
+                "[Alert] Price %message: 100% up %title %{}%",
+                notification.time.to_rfc3339()
+            )
+        );
+        assert_eq!(
+            notification.render("%titles", |v| v.to_uppercase()),
+            "PRICE %MESSAGEs"
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! Sub module with the notifier that sends email straight to an SMTP server.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::include::{
+        base64_encode, tokio, BoxFuture, FutureExt, NativeTlsConnector, Result, TokioTlsConnector,
+    },
+    Notification, Notifier, NotifyError,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+/// Sends each notification as a plain text email, the title is the subject.
+pub struct SmtpNotifier {
+    host: String,
+    port: u16,
+    starttls: bool,
+    login: Option<(String, String)>,
+    from: String,
+    to: Vec<String>,
+}
+
+impl SmtpNotifier {
+    pub fn new(
+        host: String,
+        port: u16,
+        starttls: bool,
+        login: Option<(String, String)>,
+        from: String,
+        to: Vec<String>,
+    ) -> Self {
+        Self {
+            host,
+            port,
+            starttls,
+            login,
+            from,
+            to,
+        }
+    }
+
+    // connects and says hello, upgrading to TLS first if the config asks for it.
+    async fn deliver(&self, notification: &Notification) -> Result<()> {
+        let mut conn = BufReader::new(TcpStream::connect((self.host.as_str(), self.port)).await?);
+        expect_reply(&mut conn, "connect", 2).await?;
+        command(&mut conn, "EHLO", "EHLO qtmon", 2).await?;
+        if self.starttls {
+            command(&mut conn, "STARTTLS", "STARTTLS", 2).await?;
+            let tls = TokioTlsConnector::from(NativeTlsConnector::new()?)
+                .connect(&self.host, conn.into_inner())
+                .await?;
+            let mut conn = BufReader::new(tls);
+            command(&mut conn, "EHLO", "EHLO qtmon", 2).await?;
+            self.transaction(&mut conn, notification).await
+        } else {
+            self.transaction(&mut conn, notification).await
+        }
+    }
+
+    // everything after the hello is the same with or without TLS.
+    async fn transaction<S>(
+        &self,
+        conn: &mut BufReader<S>,
+        notification: &Notification,
+    ) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send,
+    {
+        if let Some((username, password)) = &self.login {
+            let token = base64_encode(format!("\0{}\0{}", username, password));
+            command(conn, "AUTH", &format!("AUTH PLAIN {}", token), 2).await?;
+        }
+        command(conn, "MAIL", &format!("MAIL FROM:<{}>", self.from), 2).await?;
+        for to in self.to.iter() {
+            command(conn, "RCPT", &format!("RCPT TO:<{}>", to), 2).await?;
+        }
+        command(conn, "DATA", "DATA", 3).await?;
+        command(conn, "message", &self.message(notification), 2).await?;
+        command(conn, "QUIT", "QUIT", 2).await
+    }
+
+    // the headers and body of the email, ending with the lone dot.
+    fn message(&self, notification: &Notification) -> String {
+        let mut message = format!(
+            "From: <{}>\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\n\
+             Content-Type: text/plain; charset=utf-8\r\n\r\n",
+            self.from,
+            self.to
+                .iter()
+                .map(|to| format!("<{}>", to))
+                .collect::<Vec<String>>()
+                .join(", "),
+            header_value(&notification.title),
+            notification.time.to_rfc2822(),
+        );
+        for line in notification.message.lines() {
+            // a line starting with a dot gets another one so it isn't read as the end.
+            if line.starts_with('.') {
+                message.push('.');
+            }
+            message.push_str(line);
+            message.push_str("\r\n");
+        }
+        message.push('.');
+        message
+    }
+}
+
+// a line break would end the header early and let the rest pass as headers of its own, so
+// every line of the value is folded onto one.
+fn header_value(value: &str) -> String {
+    value
+        .split(&['\r', '\n'][..])
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+// sends a line and checks the reply starts with the digit we expect.
+async fn command<S>(conn: &mut BufReader<S>, name: &str, line: &str, expect: u16) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    conn.get_mut()
+        .write_all(format!("{}\r\n", line).as_bytes())
+        .await?;
+    expect_reply(conn, name, expect).await
+}
+
+// replies can run over several lines, a dash after the code means there is more to come.
+async fn expect_reply<S>(conn: &mut BufReader<S>, name: &str, expect: u16) -> Result<()>
+where
+    S: AsyncRead + Unpin + Send,
+{
+    let mut reply = String::new();
+    loop {
+        let mut line = String::new();
+        if conn.read_line(&mut line).await? == 0 {
+            return Err(NotifyError::SmtpClosedError.into());
+        }
+        reply.push_str(&line);
+        if line.as_bytes().get(3) != Some(&b'-') {
+            break;
+        }
+    }
+    match reply.get(0..3).and_then(|code| code.parse::<u16>().ok()) {
+        Some(code) if code / 100 == expect => Ok(()),
+        _ => {
+            Err(NotifyError::SmtpReplyError(name.to_string(), reply.trim_end().to_string()).into())
+        }
+    }
+}
+
+impl Notifier for SmtpNotifier {
+    fn describe(&self) -> String {
+        format!("Smtp {}:{}", self.host, self.port)
+    }
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        self.deliver(notification).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::NotifyEvent, *};
+    use tokio::{net::TcpListener, task::JoinHandle};
+
+    // a pretend SMTP server that takes one connection, says yes to every command that doesn't
+    // start with reject, and hands back every line it was sent.
+    async fn fake_server(reject: Option<&'static str>) -> (u16, JoinHandle<Vec<String>>) {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut conn = BufReader::new(stream);
+            conn.get_mut().write_all(b"220 fake\r\n").await.unwrap();
+            let mut lines: Vec<String> = Vec::new();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if conn.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end_matches("\r\n").to_string();
+                let reply: &[u8] = if in_data {
+                    if line != "." {
+                        lines.push(line);
+                        continue;
+                    }
+                    in_data = false;
+                    b"250 queued\r\n"
+                } else if matches!(reject, Some(r) if line.starts_with(r)) {
+                    b"550 no thanks\r\n"
+                } else if line.starts_with("EHLO") {
+                    b"250-fake\r\n250 AUTH PLAIN\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line == "QUIT" {
+                    b"221 bye\r\n"
+                } else {
+                    b"250 ok\r\n"
+                };
+                lines.push(line);
+                conn.get_mut().write_all(reply).await.unwrap();
+            }
+            lines
+        });
+        (port, handle)
+    }
+
+    fn notifier() -> SmtpNotifier {
+        notifier_on(25)
+    }
+    fn notifier_on(port: u16) -> SmtpNotifier {
+        SmtpNotifier::new(
+            "127.0.0.1".to_string(),
+            port,
+            false,
+            Some(("user".to_string(), "pass".to_string())),
+            "qtmon@example.com".to_string(),
+            vec!["me@example.com".to_string()],
+        )
+    }
+
+    #[test]
+    fn subject_stays_on_one_line() {
+        let notification = Notification::new(
+            NotifyEvent::Alert,
+            "Price\r\nBcc: someone@example.com\n".to_string(),
+            "body".to_string(),
+        );
+        let message = notifier().message(&notification);
+        let headers: Vec<&str> = message
+            .split("\r\n\r\n")
+            .next()
+            .unwrap()
+            .split("\r\n")
+            .collect();
+        assert!(headers.contains(&"Subject: Price Bcc: someone@example.com"));
+        assert!(!headers.iter().any(|h| h.starts_with("Bcc")));
+    }
+
+    #[tokio::test]
+    async fn sends_the_whole_exchange() {
+        let (port, server) = fake_server(None).await;
+        let notification = Notification::new(
+            NotifyEvent::SyncError,
+            "Sync failed".to_string(),
+            "first line\n.starts with a dot\n..two dots".to_string(),
+        );
+        notifier_on(port).send(&notification).await.unwrap();
+        let lines = server.await.unwrap();
+        assert_eq!(
+            lines[..5],
+            [
+                "EHLO qtmon".to_string(),
+                format!("AUTH PLAIN {}", base64_encode("\0user\0pass")),
+                "MAIL FROM:<qtmon@example.com>".to_string(),
+                "RCPT TO:<me@example.com>".to_string(),
+                "DATA".to_string(),
+            ]
+        );
+        assert!(lines.contains(&"Subject: Sync failed".to_string()));
+        // the body keeps its lines, with a dot in front of any that started with one.
+        let body = lines.iter().position(|l| l.is_empty()).unwrap() + 1;
+        assert_eq!(
+            lines[body..],
+            [
+                "first line",
+                "..starts with a dot",
+                "...two dots",
+                ".",
+                "QUIT"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn error_reply_fails_the_send() {
+        let (port, server) = fake_server(Some("RCPT")).await;
+        let notification =
+            Notification::new(NotifyEvent::Alert, "title".to_string(), "body".to_string());
+        let error = notifier_on(port)
+            .send(&notification)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("RCPT"));
+        assert!(error.contains("550 no thanks"));
+        // nothing after the refused recipient is sent.
+        assert_eq!(
+            server.await.unwrap().last().unwrap(),
+            "RCPT TO:<me@example.com>"
+        );
+    }
+}
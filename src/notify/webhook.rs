@@ -0,0 +1,154 @@
+//! Sub module with the notifier that POSTs Json to a webhook.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::include::{to_json_string, BoxFuture, Client, FutureExt, Result},
+    Notification, Notifier, NotifyError,
+};
+
+/// The body sent when the config doesn't give one.
+pub const DEFAULT_WEBHOOK_BODY: &str =
+    r#"{"event": "%event", "title": "%title", "message": "%message", "time": "%time"}"#;
+
+/// POSTs the body template to a url, with the variables escaped so the body stays valid Json.
+pub struct WebhookNotifier {
+    client: Client,
+    url: String,
+    body: String,
+    headers: Vec<(String, String)>,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String, body: String, headers: Vec<(String, String)>) -> Self {
+        Self {
+            client: Client::new(),
+            url,
+            body,
+            headers,
+        }
+    }
+}
+
+// escapes a value so it can go between the quotes of a Json string.
+fn json_escape(value: &str) -> String {
+    // only the outer quotes come off, an escaped one at the end has to stay.
+    let quoted = to_json_string(value).unwrap_or_default();
+    quoted
+        .get(1..quoted.len().saturating_sub(1))
+        .unwrap_or_default()
+        .to_string()
+}
+
+impl Notifier for WebhookNotifier {
+    fn describe(&self) -> String {
+        format!("Webhook {}", self.url)
+    }
+    fn send<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let mut request = self
+                .client
+                .post(&self.url)
+                .header("Content-Type", "application/json")
+                .body(notification.render(&self.body, json_escape));
+            for (name, value) in self.headers.iter() {
+                request = request.header(name.as_str(), value.as_str());
+            }
+            let status = request.send().await?.status();
+            if status.is_success() {
+                Ok(())
+            } else {
+                Err(NotifyError::WebhookStatusError(status.as_u16()).into())
+            }
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{super::include::tokio, NotifyEvent},
+        *,
+    };
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    // a pretend HTTP server that takes one request, answers it with the status given, and hands
+    // back the body it was sent.
+    async fn fake_server(status: &'static str) -> (String, JoinHandle<String>) {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut conn = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                conn.read_line(&mut line).await.unwrap();
+                let line = line.trim_end().to_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(value) = line.strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            conn.read_exact(&mut body).await.unwrap();
+            conn.get_mut()
+                .write_all(
+                    format!(
+                        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        status
+                    )
+                    .as_bytes(),
+                )
+                .await
+                .unwrap();
+            String::from_utf8(body).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn body_stays_valid_json() {
+        let (url, server) = fake_server("200 OK").await;
+        let notification = Notification::new(
+            NotifyEvent::Alert,
+            "XEQT.TO \"crossed\" C:\\ 100%".to_string(),
+            "line one\nline\ttwo %title".to_string(),
+        );
+        WebhookNotifier::new(url, DEFAULT_WEBHOOK_BODY.to_string(), Vec::new())
+            .send(&notification)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_str(&server.await.unwrap()).unwrap();
+        assert_eq!(body["event"], "Alert");
+        assert_eq!(body["title"], notification.title.as_str());
+        assert_eq!(body["message"], notification.message.as_str());
+        assert_eq!(body["time"], notification.time.to_rfc3339().as_str());
+    }
+
+    #[tokio::test]
+    async fn error_status_fails_the_send() {
+        let (url, server) = fake_server("503 Service Unavailable").await;
+        let notification = Notification::new(
+            NotifyEvent::SyncError,
+            "title".to_string(),
+            "body".to_string(),
+        );
+        let error = WebhookNotifier::new(url, DEFAULT_WEBHOOK_BODY.to_string(), Vec::new())
+            .send(&notification)
+            .await
+            .unwrap_err();
+        server.await.unwrap();
+        assert_eq!(
+            error.to_string(),
+            NotifyError::WebhookStatusError(503).to_string()
+        );
+    }
+}
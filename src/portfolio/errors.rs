@@ -0,0 +1,31 @@
+//! Module containing the errors that can come up while building portfolio group views.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{error, fmt};
+
+#[derive(Debug)]
+// Enum representing errors that are possible while combining accounts into a group.
+pub enum PortfolioError {
+    GroupNotFoundError(String),
+    GroupEmptyError(String),
+}
+
+impl error::Error for PortfolioError {}
+
+impl fmt::Display for PortfolioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GroupNotFoundError(name) => write!(
+                f,
+                "Could not build portfolio group! No group named {} in the config.",
+                name
+            ),
+            Self::GroupEmptyError(name) => write!(
+                f,
+                "Could not build portfolio group! Group {} has no accounts.",
+                name
+            ),
+        }
+    }
+}
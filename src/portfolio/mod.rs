@@ -0,0 +1,285 @@
+//! Module to combine several synced accounts into a single portfolio group view.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    include::{warn, Currency, Deserialize, NaiveDate, PositionSymbol, Result, Serialize},
+    storage::{DBInfoAccountBalance, DBInfoAccountPosition, DBInfoSymbol, Storage},
+};
+
+/// Sub modules
+mod errors;
+
+/// Re-export sub-modules so we can read from them in other modules.
+pub use errors::*;
+
+/// Symbol suffixes Questrade uses for Canadian listings, anything without one is a US listing.
+const CANADIAN_SUFFIXES: [&str; 4] = [".TO", ".VN", ".CN", ".NE"];
+
+/// Works out the currency a symbol trades in from where it is listed.
+pub fn symbol_currency(symbol: &str) -> Currency {
+    if CANADIAN_SUFFIXES.iter().any(|s| symbol.ends_with(s)) {
+        Currency::CAD
+    } else {
+        Currency::USD
+    }
+}
+
+/// The currency a symbol trades in, the looked up one if we have it.
+pub fn listing_currency(db: &dyn Storage, symbol: &str) -> Currency {
+    match db.get_symbol(symbol) {
+        Ok(info) => info.currency,
+        Err(_) => symbol_currency(symbol),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// A named set of AccountToSync names, along with the currency to report the group in.
+pub struct PortfolioGroup(String, Vec<String>, Currency);
+
+impl PortfolioGroup {
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+    pub fn accounts(&self) -> &[String] {
+        &self.1
+    }
+    pub fn currency(&self) -> &Currency {
+        &self.2
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// The portfolio section of the config file.
+pub struct PortfolioConfig {
+    pub groups: Vec<PortfolioGroup>,
+    /// How many CAD one USD buys, used to convert accounts into the group currency until a
+    /// rate has been recorded.
+    pub usd_cad_rate: f64,
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self {
+            groups: Vec::new(),
+            usd_cad_rate: 1.35,
+        }
+    }
+}
+
+impl PortfolioConfig {
+    pub fn get_group(&self, name: &str) -> Result<&PortfolioGroup> {
+        match self.groups.iter().find(|g| g.name() == name) {
+            Some(g) if g.accounts().is_empty() => {
+                Err(Box::new(PortfolioError::GroupEmptyError(name.to_string())))
+            }
+            Some(g) => Ok(g),
+            None => Err(Box::new(PortfolioError::GroupNotFoundError(
+                name.to_string(),
+            ))),
+        }
+    }
+    /// A copy of the config for a single request, with the rate swapped for the one recorded
+    /// most recently and every group optionally reported in another currency.
+    pub fn for_request(&self, usd_cad_rate: Option<f64>, currency: Option<&Currency>) -> Self {
+        let mut result = self.clone();
+        if let Some(rate) = usd_cad_rate {
+            result.usd_cad_rate = rate;
+        }
+        if let Some(currency) = currency {
+            for group in result.groups.iter_mut() {
+                group.2 = currency.clone();
+            }
+        }
+        result
+    }
+    /// Converts an amount between CAD and USD.
+    pub fn convert(&self, amount: f64, from: &Currency, to: &Currency) -> f64 {
+        match (from, to) {
+            (Currency::USD, Currency::CAD) => amount * self.usd_cad_rate,
+            (Currency::CAD, Currency::USD) => amount / self.usd_cad_rate,
+            _ => amount,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+/// The balances of every account in a group added up in the group currency.
+pub struct GroupBalance {
+    pub group: String,
+    pub currency: Currency,
+    pub accounts: Vec<String>,
+    pub cash: f64,
+    pub market_value: f64,
+    pub total_equity: f64,
+    pub buying_power: f64,
+    pub maitenance_excess: f64,
+}
+
+impl GroupBalance {
+    fn new(group: &PortfolioGroup) -> Self {
+        Self {
+            group: group.name().to_string(),
+            currency: group.currency().clone(),
+            accounts: Vec::new(),
+            cash: 0.0,
+            market_value: 0.0,
+            total_equity: 0.0,
+            buying_power: 0.0,
+            maitenance_excess: 0.0,
+        }
+    }
+    fn add(&mut self, account: &str, balance: &DBInfoAccountBalance, config: &PortfolioConfig) {
+        let convert = |amount: f64| config.convert(amount, &balance.currency, &self.currency);
+        let (cash, market_value, total_equity, buying_power, maitenance_excess) = (
+            convert(balance.cash),
+            convert(balance.market_value),
+            convert(balance.total_equity),
+            convert(balance.buying_power),
+            convert(balance.maitenance_excess),
+        );
+        self.accounts.push(account.to_string());
+        self.cash += cash;
+        self.market_value += market_value;
+        self.total_equity += total_equity;
+        self.buying_power += buying_power;
+        self.maitenance_excess += maitenance_excess;
+    }
+}
+
+/// Adds up a balance from every account in the group, balance_of picks which stored balance to
+/// use for each account.
+pub fn group_balance<F>(
+    config: &PortfolioConfig,
+    group_name: &str,
+    balance_of: F,
+) -> Result<GroupBalance>
+where
+    F: Fn(&str) -> Result<DBInfoAccountBalance>,
+{
+    let group = config.get_group(group_name)?;
+    let mut result = GroupBalance::new(group);
+    for account in group.accounts().iter() {
+        // a group total missing an account would look right but be wrong, so we pass it up.
+        let balance = balance_of(account)?;
+        result.add(account, &balance, config);
+    }
+    Ok(result)
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+/// One symbol held across the accounts of a group, the money values are in the group currency.
+pub struct GroupPosition {
+    pub symbol: PositionSymbol,
+    pub listing_currency: Currency,
+    pub accounts: Vec<String>,
+    pub open_quantity: f64,
+    pub closed_quantity: f64,
+    pub current_market_value: f64,
+    pub current_price: f64,
+    pub average_entry_price: f64,
+    pub closed_pnl: f64,
+    pub day_pnl: f64,
+    pub open_pnl: f64,
+    pub total_cost: f64,
+    pub symbol_info: Option<DBInfoSymbol>,
+}
+
+impl GroupPosition {
+    // the looked up currency wins over the one worked out from the symbol.
+    fn new(symbol: &str, symbol_info: Option<DBInfoSymbol>) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            listing_currency: match &symbol_info {
+                Some(info) => info.currency.clone(),
+                None => symbol_currency(symbol),
+            },
+            accounts: Vec::new(),
+            open_quantity: 0.0,
+            closed_quantity: 0.0,
+            current_market_value: 0.0,
+            current_price: 0.0,
+            average_entry_price: 0.0,
+            closed_pnl: 0.0,
+            day_pnl: 0.0,
+            open_pnl: 0.0,
+            total_cost: 0.0,
+            symbol_info,
+        }
+    }
+    fn add(
+        &mut self,
+        account: &str,
+        position: &DBInfoAccountPosition,
+        config: &PortfolioConfig,
+        currency: &Currency,
+    ) {
+        let convert = |amount: f64| config.convert(amount, &self.listing_currency, currency);
+        let (current_market_value, current_price, entry_price) = (
+            convert(position.current_market_value),
+            convert(position.current_price),
+            convert(position.average_entry_price),
+        );
+        let (closed_pnl, day_pnl, open_pnl, total_cost) = (
+            convert(position.closed_pnl),
+            convert(position.day_pnl),
+            convert(position.open_pnl),
+            convert(position.total_cost),
+        );
+        // the entry price is weighted by how many shares each account holds.
+        let open_quantity = self.open_quantity + position.open_quantity;
+        if open_quantity != 0.0 {
+            self.average_entry_price = (self.average_entry_price * self.open_quantity
+                + entry_price * position.open_quantity)
+                / open_quantity;
+        }
+        self.accounts.push(account.to_string());
+        self.open_quantity = open_quantity;
+        self.closed_quantity += position.closed_quantity;
+        self.current_market_value += current_market_value;
+        self.current_price = current_price;
+        self.closed_pnl += closed_pnl;
+        self.day_pnl += day_pnl;
+        self.open_pnl += open_pnl;
+        self.total_cost += total_cost;
+    }
+}
+
+/// Merges the positions held on a date by every account in the group, by symbol.
+pub fn group_positions(
+    db: &dyn Storage,
+    config: &PortfolioConfig,
+    group_name: &str,
+    date: NaiveDate,
+) -> Result<Vec<GroupPosition>> {
+    let group = config.get_group(group_name)?;
+    let mut result: Vec<GroupPosition> = Vec::new();
+    for account in group.accounts().iter() {
+        // an account holding only cash never has positions synced.
+        let symbols = match db.get_position_symbols(account) {
+            Ok(s) => s,
+            Err(e) => {
+                warn!("Skipping positions of {}, with error: {}", account, e);
+                continue;
+            }
+        };
+        for symbol in symbols.iter() {
+            // symbols that were sold off before the date have nothing to merge.
+            let position = match db.get_latest_position(account, symbol, date) {
+                Ok(p) => p,
+                Err(_) => continue,
+            };
+            let index = match result.iter().position(|gp| gp.symbol == *symbol) {
+                Some(i) => i,
+                None => {
+                    result.push(GroupPosition::new(symbol, db.get_symbol(symbol).ok()));
+                    result.len() - 1
+                }
+            };
+            result[index].add(account, &position, config, group.currency());
+        }
+    }
+    result.sort_unstable_by(|a, b| a.symbol.cmp(&b.symbol));
+    Ok(result)
+}
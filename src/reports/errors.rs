@@ -0,0 +1,38 @@
+//! Module containing the errors that can come up while making or reading reports.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{error, fmt, NaiveDate};
+
+#[derive(Debug)]
+// Enum representing errors that are possible with the daily reports.
+pub enum ReportError {
+    ReportNotFoundError(NaiveDate, String),
+    UnknownFormatError(String),
+    CommandStatusError(String, Option<i32>),
+}
+
+impl error::Error for ReportError {}
+
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReportNotFoundError(date, ext) => write!(
+                f,
+                "No report has been saved for {} in the {} format.",
+                date, ext
+            ),
+            Self::UnknownFormatError(format) => write!(
+                f,
+                "Could not parse report format: {}. Expected md, html, or txt.",
+                format
+            ),
+            Self::CommandStatusError(command, Some(code)) => {
+                write!(f, "Report command {} exited with code {}.", command, code)
+            }
+            Self::CommandStatusError(command, None) => {
+                write!(f, "Report command {} was killed.", command)
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Module to put together a report of each account at the end of the trading day.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    include::{
+        read_to_string, tokio, warn, AccountNumber, Deserialize, DirBuilder, Duration, NaiveDate,
+        OpenOptions, PathBuf, Result, Serialize, Write,
+    },
+    storage::{
+        ActivityType, BalanceKind, DBInfoAccountActivity, DBInfoAccountBalance,
+        DBInfoAccountPosition, Storage,
+    },
+};
+
+/// Sub modules
+mod errors;
+mod render;
+
+/// Re-export sub-modules so we can read from them in other modules.
+pub use errors::*;
+
+/// How far back we look for the last day with positions when working out what is new.
+const PREVIOUS_DAY_LOOKBACK_DAYS: i64 = 10;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+/// The formats a report can be saved in.
+pub enum ReportFormat {
+    Markdown,
+    Html,
+    Text,
+}
+
+impl ReportFormat {
+    /// Reads the short names used by the reports api, md, html, or txt.
+    pub fn parse(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "md" | "markdown" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            "txt" | "text" => Ok(Self::Text),
+            _ => Err(Box::new(ReportError::UnknownFormatError(
+                format.to_string(),
+            ))),
+        }
+    }
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Text => "txt",
+        }
+    }
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Html => "text/html; charset=utf-8",
+            Self::Text => "text/plain; charset=utf-8",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// The reports section of the config file.
+pub struct ReportsConfig {
+    pub enabled: bool,
+    /// Where the reports are saved, one file per day and format.
+    pub directory: PathBuf,
+    pub formats: Vec<ReportFormat>,
+    /// How many positions to list as the top movers of each account.
+    pub top_movers: usize,
+    /// Shell command the report is piped into once it is saved.
+    pub command: Option<String>,
+    pub command_format: ReportFormat,
+}
+
+impl Default for ReportsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            directory: PathBuf::from("reports"),
+            formats: vec![
+                ReportFormat::Markdown,
+                ReportFormat::Html,
+                ReportFormat::Text,
+            ],
+            top_movers: 5,
+            command: None,
+            command_format: ReportFormat::Text,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+/// The day of a single account.
+pub struct AccountReport {
+    pub account: AccountNumber,
+    pub start_of_day: Option<DBInfoAccountBalance>,
+    pub close: Option<DBInfoAccountBalance>,
+    /// Positions with the biggest day P&L either way, biggest first.
+    pub top_movers: Vec<DBInfoAccountPosition>,
+    pub opened: Vec<DBInfoAccountPosition>,
+    pub closed: Vec<DBInfoAccountPosition>,
+    /// Everything but the trades, since those already show up in the positions.
+    pub cash_activity: Vec<DBInfoAccountActivity>,
+}
+
+impl AccountReport {
+    fn build(
+        db: &dyn Storage,
+        account: AccountNumber,
+        date: NaiveDate,
+        kind: BalanceKind,
+        top_movers: usize,
+    ) -> Self {
+        let start_of_day = db.get_start_of_day_balance(&account, kind, date).ok();
+        let close = db.get_latest_balance(&account, kind, date).ok();
+        let mut today = Vec::new();
+        let mut opened = Vec::new();
+        let mut closed = Vec::new();
+        let mut has_history = false;
+        for symbol in db.get_position_symbols(&account).unwrap_or_default() {
+            let current = db.get_latest_position(&account, &symbol, date).ok();
+            let previous = db
+                .get_position_days(
+                    &account,
+                    &symbol,
+                    date - Duration::days(PREVIOUS_DAY_LOOKBACK_DAYS),
+                    date - Duration::days(1),
+                )
+                .ok()
+                .and_then(|mut days| days.pop())
+                .and_then(|(_, mut positions)| positions.pop());
+            has_history |= previous.is_some();
+            let was_open = matches!(&previous, Some(p) if p.open_quantity > 0.0);
+            match current {
+                Some(pos) if pos.open_quantity > 0.0 => {
+                    if !was_open {
+                        opened.push(pos.clone());
+                    }
+                    today.push(pos);
+                }
+                Some(pos) => {
+                    if was_open {
+                        closed.push(pos.clone());
+                    }
+                    today.push(pos);
+                }
+                None => {
+                    if let Some(pos) = previous.filter(|_| was_open) {
+                        closed.push(pos);
+                    }
+                }
+            }
+        }
+        // the first day we have for an account would show every position as new.
+        if !has_history {
+            opened.clear();
+        }
+        today.sort_by(|a, b| {
+            b.day_pnl
+                .abs()
+                .partial_cmp(&a.day_pnl.abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        today.truncate(top_movers);
+        let mut cash_activity = db
+            .get_activities(&account, date, date, None)
+            .unwrap_or_default();
+        cash_activity.retain(|act| act.activity_type != ActivityType::Trades);
+        Self {
+            account,
+            start_of_day,
+            close,
+            top_movers: today,
+            opened,
+            closed,
+            cash_activity,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+/// Everything that goes into the report for one day.
+pub struct DailyReport {
+    pub date: NaiveDate,
+    pub accounts: Vec<AccountReport>,
+}
+
+impl DailyReport {
+    pub fn build(
+        db: &dyn Storage,
+        date: NaiveDate,
+        kind: BalanceKind,
+        top_movers: usize,
+    ) -> Result<Self> {
+        let accounts = db
+            .list_account_numbers()?
+            .drain(..)
+            .map(|account| AccountReport::build(db, account, date, kind, top_movers))
+            .collect();
+        Ok(Self { date, accounts })
+    }
+}
+
+/// Helper functions
+fn report_path(directory: &PathBuf, date: NaiveDate, format: ReportFormat) -> PathBuf {
+    directory.join(format!("{}.{}", date, format.extension()))
+}
+
+// feeds the report to the command on stdin, we don't wait around for it in the sync.
+async fn pipe_report(command: String, report: String) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .stdin(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(report.as_bytes()).await?;
+    }
+    let status = child.await?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Box::new(ReportError::CommandStatusError(
+            command,
+            status.code(),
+        )))
+    }
+}
+
+/// Builds the report for the day, saves it in every format from the config, and hands it to
+/// the command if there is one. Returns the files that were written.
+pub fn generate_report(
+    db: &dyn Storage,
+    config: &ReportsConfig,
+    date: NaiveDate,
+    kind: BalanceKind,
+) -> Result<Vec<PathBuf>> {
+    let report = DailyReport::build(db, date, kind, config.top_movers)?;
+    DirBuilder::new()
+        .recursive(true)
+        .create(&config.directory)?;
+    let mut written = Vec::new();
+    for format in config.formats.iter() {
+        let path = report_path(&config.directory, date, *format);
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?
+            .write_all(report.render(*format).as_bytes())?;
+        written.push(path);
+    }
+    if let Some(command) = &config.command {
+        let body = report.render(config.command_format);
+        let command = command.clone();
+        tokio::spawn(async move {
+            if let Err(e) = pipe_report(command, body).await {
+                warn!("Could not pipe the daily report, with error: {}", e);
+            }
+        });
+    }
+    Ok(written)
+}
+
+/// Reads a saved report back, used by the reports api.
+pub fn read_report(directory: &PathBuf, date: NaiveDate, format: ReportFormat) -> Result<String> {
+    let path = report_path(directory, date, format);
+    if !path.exists() {
+        return Err(Box::new(ReportError::ReportNotFoundError(
+            date,
+            format.extension().to_string(),
+        )));
+    }
+    Ok(read_to_string(path)?)
+}
@@ -0,0 +1,182 @@
+//! Sub module to turn a daily report into Markdown, HTML, or plain text.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::{
+        analytics::percent_change,
+        storage::{DBInfoAccountActivity, DBInfoAccountBalance, DBInfoAccountPosition},
+    },
+    AccountReport, DailyReport, ReportFormat,
+};
+
+// a titled list of lines, every format is just a different way of printing these.
+struct Section {
+    title: &'static str,
+    items: Vec<String>,
+}
+
+impl Section {
+    fn new(title: &'static str, mut items: Vec<String>) -> Self {
+        if items.is_empty() {
+            items.push("None.".to_string());
+        }
+        Self { title, items }
+    }
+}
+
+/// Helper functions
+fn balance_items(
+    sod: &Option<DBInfoAccountBalance>,
+    close: &Option<DBInfoAccountBalance>,
+) -> Vec<String> {
+    match (sod, close) {
+        (Some(sod), Some(close)) => vec![
+            format!(
+                "Total equity: {:.2} -> {:.2} {:?} ({:+.2}, {:+.2}%)",
+                sod.total_equity,
+                close.total_equity,
+                close.currency,
+                close.total_equity - sod.total_equity,
+                percent_change(sod.total_equity, close.total_equity)
+            ),
+            format!("Cash: {:.2} -> {:.2}", sod.cash, close.cash),
+            format!(
+                "Market value: {:.2} -> {:.2}",
+                sod.market_value, close.market_value
+            ),
+        ],
+        _ => vec!["No balances were stored for this day.".to_string()],
+    }
+}
+
+fn mover_item(position: &DBInfoAccountPosition) -> String {
+    format!(
+        "{}: {:+.2} today, {:.2} market value",
+        position.symbol, position.day_pnl, position.current_market_value
+    )
+}
+
+fn opened_item(position: &DBInfoAccountPosition) -> String {
+    format!(
+        "{}: {} @ {:.2}",
+        position.symbol, position.open_quantity, position.average_entry_price
+    )
+}
+
+fn closed_item(position: &DBInfoAccountPosition) -> String {
+    format!(
+        "{}: {:+.2} closed P&L",
+        position.symbol, position.closed_pnl
+    )
+}
+
+fn activity_item(activity: &DBInfoAccountActivity) -> String {
+    let item = format!(
+        "{:?}: {:+.2} {:?}",
+        activity.activity_type, activity.net_amount, activity.currency
+    );
+    match activity.description.trim() {
+        "" => item,
+        description => format!("{}, {}", item, description),
+    }
+}
+
+fn sections(account: &AccountReport) -> Vec<Section> {
+    vec![
+        Section::new(
+            "Balance",
+            balance_items(&account.start_of_day, &account.close),
+        ),
+        Section::new(
+            "Top movers",
+            account.top_movers.iter().map(mover_item).collect(),
+        ),
+        Section::new(
+            "New positions",
+            account.opened.iter().map(opened_item).collect(),
+        ),
+        Section::new(
+            "Closed positions",
+            account.closed.iter().map(closed_item).collect(),
+        ),
+        Section::new(
+            "Cash activity",
+            account.cash_activity.iter().map(activity_item).collect(),
+        ),
+    ]
+}
+
+// only the characters that would break the markup need replacing.
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn markdown(report: &DailyReport) -> String {
+    let mut out = format!("# Daily report for {}\n", report.date);
+    for account in report.accounts.iter() {
+        out.push_str(&format!("\n## Account {}\n", account.account));
+        for section in sections(account) {
+            out.push_str(&format!("\n### {}\n\n", section.title));
+            for item in section.items {
+                out.push_str(&format!("- {}\n", item));
+            }
+        }
+    }
+    out
+}
+
+fn html(report: &DailyReport) -> String {
+    let title = html_escape(&format!("Daily report for {}", report.date));
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n\
+         </head>\n<body>\n<h1>{0}</h1>\n",
+        title
+    );
+    for account in report.accounts.iter() {
+        out.push_str(&format!(
+            "<h2>Account {}</h2>\n",
+            html_escape(&account.account)
+        ));
+        for section in sections(account) {
+            out.push_str(&format!("<h3>{}</h3>\n<ul>\n", section.title));
+            for item in section.items {
+                out.push_str(&format!("<li>{}</li>\n", html_escape(&item)));
+            }
+            out.push_str("</ul>\n");
+        }
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn text(report: &DailyReport) -> String {
+    let title = format!("Daily report for {}", report.date);
+    let mut out = format!("{}\n{}\n", title, "=".repeat(title.len()));
+    for account in report.accounts.iter() {
+        let heading = format!("Account {}", account.account);
+        out.push_str(&format!("\n{}\n{}\n", heading, "-".repeat(heading.len())));
+        for section in sections(account) {
+            out.push_str(&format!("{}:\n", section.title));
+            for item in section.items {
+                out.push_str(&format!("  {}\n", item));
+            }
+        }
+    }
+    out
+}
+
+impl DailyReport {
+    /// The whole report as one document in the format asked for.
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => markdown(self),
+            ReportFormat::Html => html(self),
+            ReportFormat::Text => text(self),
+        }
+    }
+}
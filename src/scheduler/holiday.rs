@@ -0,0 +1,41 @@
+//! Sub module to load and query the market holiday calendar.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::include::{
+        from_str, read_to_string, Deserialize, NaiveDate, PathBuf, Result, Serialize,
+    },
+    Exchange,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// A single day that one or more exchanges are closed for.
+pub struct Holiday {
+    pub date: NaiveDate,
+    #[serde(default)]
+    pub name: String,
+    pub exchanges: Vec<Exchange>,
+}
+
+#[derive(Debug, Default)]
+/// The list of holidays loaded from the user's holiday file.
+pub struct HolidayCalendar {
+    holidays: Vec<Holiday>,
+}
+
+impl HolidayCalendar {
+    /// Loads a RON encoded list of Holiday objects from the file provided.
+    pub fn load(file: &PathBuf) -> Result<Self> {
+        let input = read_to_string(file)?;
+        Ok(Self {
+            holidays: from_str::<Vec<Holiday>>(&input)?,
+        })
+    }
+
+    pub fn is_holiday(&self, exchange: Exchange, date: NaiveDate) -> bool {
+        self.holidays
+            .iter()
+            .any(|h| h.date == date && h.exchanges.contains(&exchange))
+    }
+}
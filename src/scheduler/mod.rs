@@ -0,0 +1,498 @@
+//! Market hours aware scheduler to decide when the monitor should sync.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::include::{
+    error, info, DateTime, Datelike, Deserialize, Duration, FixedOffset, Local, NaiveDate,
+    NaiveTime, PathBuf, Result, Serialize, TimeZone, Utc, Weekday,
+};
+
+/// Sub modules
+mod holiday;
+
+/// Re-export sub-modules so we can read from them in other modules.
+pub use holiday::*;
+
+/// How many days ahead we look for the next session change before giving up.
+const BOUNDARY_SEARCH_DAYS: i64 = 10;
+
+/// Helper functions
+// Both the TSX and NYSE keep Eastern time, so we work out the offset ourselves using the
+// North American DST rules. Starts the second Sunday in March and ends the first Sunday in
+// November, both at 2am local.
+fn eastern_offset(utc: DateTime<Utc>) -> FixedOffset {
+    let naive = utc.naive_utc();
+    let dst_start =
+        NaiveDate::from_weekday_of_month(naive.year(), 3, Weekday::Sun, 2).and_hms(7, 0, 0);
+    let dst_end =
+        NaiveDate::from_weekday_of_month(naive.year(), 11, Weekday::Sun, 1).and_hms(6, 0, 0);
+    if naive >= dst_start && naive < dst_end {
+        FixedOffset::west(4 * 3600)
+    } else {
+        FixedOffset::west(5 * 3600)
+    }
+}
+fn eastern_to_utc(date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    eastern_datetime(date, time).with_timezone(&Utc)
+}
+
+/// A time of day in Eastern time, with the offset of that day. The DST switch is at 2am on a
+/// Sunday, so the offset at noon is the one of the whole trading day.
+pub fn eastern_datetime(date: NaiveDate, time: NaiveTime) -> DateTime<FixedOffset> {
+    let offset = eastern_offset(Utc.from_utc_datetime(&date.and_hms(12, 0, 0)));
+    offset.from_local_datetime(&date.and_time(time)).unwrap()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+/// The exchanges that we know the trading sessions of.
+pub enum Exchange {
+    TSX,
+    NYSE,
+}
+
+impl Exchange {
+    /// Session boundaries in Eastern time, in the order:
+    /// pre-market open, regular open, regular close, after hours close.
+    fn session_hours(&self) -> [NaiveTime; 4] {
+        match self {
+            Self::TSX => [
+                NaiveTime::from_hms(7, 0, 0),
+                NaiveTime::from_hms(9, 30, 0),
+                NaiveTime::from_hms(16, 0, 0),
+                NaiveTime::from_hms(17, 0, 0),
+            ],
+            Self::NYSE => [
+                NaiveTime::from_hms(4, 0, 0),
+                NaiveTime::from_hms(9, 30, 0),
+                NaiveTime::from_hms(16, 0, 0),
+                NaiveTime::from_hms(20, 0, 0),
+            ],
+        }
+    }
+    fn is_trading_day(&self, date: NaiveDate, holidays: &HolidayCalendar) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !holidays.is_holiday(*self, date)
+    }
+    fn session_at(
+        &self,
+        date: NaiveDate,
+        time: NaiveTime,
+        holidays: &HolidayCalendar,
+    ) -> MarketSession {
+        if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            return MarketSession::Weekend;
+        }
+        if holidays.is_holiday(*self, date) {
+            return MarketSession::Holiday;
+        }
+        let [pre_open, open, close, after_close] = self.session_hours();
+        if time < pre_open {
+            MarketSession::Closed
+        } else if time < open {
+            MarketSession::PreMarket
+        } else if time < close {
+            MarketSession::Regular
+        } else if time < after_close {
+            MarketSession::AfterHours
+        } else {
+            MarketSession::Closed
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+/// The different parts of the trading week.
+pub enum MarketSession {
+    PreMarket,
+    Regular,
+    AfterHours,
+    Closed,
+    Weekend,
+    Holiday,
+}
+
+impl MarketSession {
+    // used to pick the most active session when watching more than one exchange.
+    fn activity(&self) -> u8 {
+        match self {
+            Self::Regular => 5,
+            Self::PreMarket | Self::AfterHours => 4,
+            Self::Closed => 3,
+            Self::Holiday => 2,
+            Self::Weekend => 1,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+/// How often to poll the Api during a session.
+pub enum PollCadence {
+    Off,
+    Every(u64),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// The schedule section of the config file.
+pub struct ScheduleConfig {
+    pub exchanges: Vec<Exchange>,
+    pub pre_market: PollCadence,
+    pub regular: PollCadence,
+    pub after_hours: PollCadence,
+    pub closed: PollCadence,
+    pub weekend: PollCadence,
+    pub holiday: PollCadence,
+    pub holiday_file: Option<PathBuf>,
+    pub anchor_delay: u64,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            exchanges: vec![Exchange::TSX, Exchange::NYSE],
+            pre_market: PollCadence::Every(900),
+            regular: PollCadence::Every(300),
+            after_hours: PollCadence::Every(900),
+            closed: PollCadence::Every(3600),
+            weekend: PollCadence::Every(3600),
+            holiday: PollCadence::Every(3600),
+            holiday_file: None,
+            anchor_delay: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Why a sync was scheduled.
+pub enum SyncReason {
+    Startup,
+    Cadence,
+    MarketOpen,
+    MarketClose,
+    SessionChange,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The next time the monitor should wake up and why.
+pub struct ScheduledSync {
+    pub at: DateTime<Local>,
+    pub reason: SyncReason,
+}
+
+impl ScheduledSync {
+    pub fn new(at: DateTime<Local>, reason: SyncReason) -> Self {
+        Self { at, reason }
+    }
+    pub fn startup() -> Self {
+        Self::new(Local::now(), SyncReason::Startup)
+    }
+}
+
+/// Works out the current market session and when the next sync should happen.
+pub struct Scheduler {
+    config: ScheduleConfig,
+    holidays: HolidayCalendar,
+}
+
+impl Scheduler {
+    /// Builds the scheduler and loads the holiday calendar if the config has one.
+    pub fn new(config: &ScheduleConfig) -> Result<Self> {
+        let holidays = match &config.holiday_file {
+            Some(file) => match HolidayCalendar::load(file) {
+                Ok(hc) => {
+                    info!("Holiday calendar load complete.");
+                    hc
+                }
+                Err(e) => {
+                    error!(
+                        "Could not load holiday file @ [{}]. Error: {}",
+                        file.to_str().unwrap_or_default(),
+                        e
+                    );
+                    return Err(e);
+                }
+            },
+            None => HolidayCalendar::default(),
+        };
+        Ok(Self {
+            config: config.clone(),
+            holidays,
+        })
+    }
+
+    /// The most active session across all of the exchanges we watch.
+    pub fn session_at(&self, datetime: DateTime<Local>) -> MarketSession {
+        let utc = datetime.with_timezone(&Utc);
+        let eastern = utc.with_timezone(&eastern_offset(utc)).naive_local();
+        self.config
+            .exchanges
+            .iter()
+            .map(|ex| ex.session_at(eastern.date(), eastern.time(), &self.holidays))
+            .max_by_key(|session| session.activity())
+            .unwrap_or(MarketSession::Closed)
+    }
+
+    pub fn cadence_at(&self, datetime: DateTime<Local>) -> PollCadence {
+        match self.session_at(datetime) {
+            MarketSession::PreMarket => self.config.pre_market,
+            MarketSession::Regular => self.config.regular,
+            MarketSession::AfterHours => self.config.after_hours,
+            MarketSession::Closed => self.config.closed,
+            MarketSession::Weekend => self.config.weekend,
+            MarketSession::Holiday => self.config.holiday,
+        }
+    }
+
+    /// Whether a wake up should actually hit the Api. Open and close anchors always sync,
+    /// session changes only sync if the new session is being polled.
+    pub fn should_sync(&self, scheduled: &ScheduledSync) -> bool {
+        match scheduled.reason {
+            SyncReason::SessionChange => self.cadence_at(scheduled.at) != PollCadence::Off,
+            _ => true,
+        }
+    }
+
+    /// Finds the next time to wake up. That is the sooner of the current session's cadence
+    /// and the next session boundary, so we always get a snapshot right after open and close.
+    pub fn next_wake(&self, now: DateTime<Local>) -> ScheduledSync {
+        let mut next = match self.cadence_at(now) {
+            PollCadence::Every(secs) => {
+                ScheduledSync::new(now + Duration::seconds(secs as i64), SyncReason::Cadence)
+            }
+            // nothing to do this session so check back in a day at the latest.
+            PollCadence::Off => {
+                ScheduledSync::new(now + Duration::days(1), SyncReason::SessionChange)
+            }
+        };
+        let now_utc = now.with_timezone(&Utc);
+        let today = now_utc
+            .with_timezone(&eastern_offset(now_utc))
+            .date()
+            .naive_local();
+        let anchor_delay = Duration::seconds(self.config.anchor_delay as i64);
+        for day in (0..BOUNDARY_SEARCH_DAYS).map(|d| today + Duration::days(d)) {
+            for exchange in self
+                .config
+                .exchanges
+                .iter()
+                .filter(|ex| ex.is_trading_day(day, &self.holidays))
+            {
+                let [pre_open, open, close, after_close] = exchange.session_hours();
+                let boundaries = [
+                    (eastern_to_utc(day, pre_open), SyncReason::SessionChange),
+                    (
+                        eastern_to_utc(day, open) + anchor_delay,
+                        SyncReason::MarketOpen,
+                    ),
+                    (
+                        eastern_to_utc(day, close) + anchor_delay,
+                        SyncReason::MarketClose,
+                    ),
+                    (eastern_to_utc(day, after_close), SyncReason::SessionChange),
+                ];
+                for (at, reason) in boundaries.iter() {
+                    let at = at.with_timezone(&Local);
+                    if at > now && at < next.at {
+                        next = ScheduledSync::new(at, *reason);
+                    }
+                }
+            }
+            // boundaries only get later each day, so once we are before tomorrow we can stop.
+            if next.at.with_timezone(&Utc)
+                < eastern_to_utc(day + Duration::days(1), NaiveTime::from_hms(0, 0, 0))
+            {
+                break;
+            }
+        }
+        // the lookups for today need something stored today, so polled sessions also get a
+        // sync right after midnight.
+        if let Some(midnight) = (now.date() + Duration::days(1)).and_hms_opt(0, 0, 0) {
+            let at = midnight + anchor_delay;
+            if at < next.at && self.cadence_at(at) != PollCadence::Off {
+                next = ScheduledSync::new(at, SyncReason::SessionChange);
+            }
+        }
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every time in these tests is given in UTC so they pass no matter the local timezone.
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        Utc.ymd(y, m, d).and_hms(h, min, 0).with_timezone(&Local)
+    }
+    fn scheduler(config: ScheduleConfig) -> Scheduler {
+        Scheduler::new(&config).unwrap()
+    }
+    // the midnight sync lands on local time, so tests of the quiet sessions turn them all off.
+    fn quiet_off() -> ScheduleConfig {
+        ScheduleConfig {
+            closed: PollCadence::Off,
+            weekend: PollCadence::Off,
+            holiday: PollCadence::Off,
+            ..ScheduleConfig::default()
+        }
+    }
+
+    #[test]
+    fn eastern_offset_follows_dst() {
+        // 2026 springs forward March 8 at 07:00 UTC and falls back November 1 at 06:00 UTC.
+        let est = FixedOffset::west(5 * 3600);
+        let edt = FixedOffset::west(4 * 3600);
+        assert_eq!(eastern_offset(Utc.ymd(2026, 3, 8).and_hms(6, 59, 59)), est);
+        assert_eq!(eastern_offset(Utc.ymd(2026, 3, 8).and_hms(7, 0, 0)), edt);
+        assert_eq!(eastern_offset(Utc.ymd(2026, 11, 1).and_hms(5, 59, 59)), edt);
+        assert_eq!(eastern_offset(Utc.ymd(2026, 11, 1).and_hms(6, 0, 0)), est);
+        // a summer day starts at midnight EDT.
+        let midnight = NaiveTime::from_hms(0, 0, 0);
+        assert_eq!(
+            eastern_datetime(NaiveDate::from_ymd(2026, 7, 6), midnight).to_rfc3339(),
+            "2026-07-06T00:00:00-04:00"
+        );
+        assert_eq!(
+            eastern_datetime(NaiveDate::from_ymd(2026, 12, 7), midnight).to_rfc3339(),
+            "2026-12-07T00:00:00-05:00"
+        );
+    }
+
+    #[test]
+    fn open_moves_with_dst() {
+        let s = scheduler(ScheduleConfig::default());
+        // the Friday before the switch opens at 14:30 UTC, the Monday after at 13:30 UTC.
+        assert_eq!(
+            s.session_at(utc(2026, 3, 6, 14, 29)),
+            MarketSession::PreMarket
+        );
+        assert_eq!(
+            s.session_at(utc(2026, 3, 6, 14, 30)),
+            MarketSession::Regular
+        );
+        assert_eq!(
+            s.session_at(utc(2026, 3, 9, 13, 29)),
+            MarketSession::PreMarket
+        );
+        assert_eq!(
+            s.session_at(utc(2026, 3, 9, 13, 30)),
+            MarketSession::Regular
+        );
+        // and back again in November.
+        assert_eq!(
+            s.session_at(utc(2026, 10, 30, 13, 30)),
+            MarketSession::Regular
+        );
+        assert_eq!(
+            s.session_at(utc(2026, 11, 2, 13, 30)),
+            MarketSession::PreMarket
+        );
+        assert_eq!(
+            s.session_at(utc(2026, 11, 2, 14, 30)),
+            MarketSession::Regular
+        );
+        // the close anchor on the Monday after the switch lands an hour earlier in UTC.
+        let next = s.next_wake(utc(2026, 3, 9, 19, 58));
+        assert_eq!(next.reason, SyncReason::MarketClose);
+        assert_eq!(
+            next.at.with_timezone(&Utc),
+            Utc.ymd(2026, 3, 9).and_hms(20, 1, 0)
+        );
+    }
+
+    #[test]
+    fn holiday_file_day() {
+        let file = std::env::temp_dir().join("qtmon_scheduler_holidays.ron");
+        std::fs::write(
+            &file,
+            "[(date: \"2026-12-25\", name: \"Christmas\", exchanges: [TSX, NYSE]), \
+             (date: \"2026-11-26\", name: \"Thanksgiving\", exchanges: [NYSE])]",
+        )
+        .unwrap();
+        let s = scheduler(ScheduleConfig {
+            holiday_file: Some(file.clone()),
+            ..quiet_off()
+        });
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(
+            s.session_at(utc(2026, 12, 25, 16, 0)),
+            MarketSession::Holiday
+        );
+        assert_eq!(s.cadence_at(utc(2026, 12, 25, 16, 0)), PollCadence::Off);
+        // only the NYSE is shut, the TSX still trades.
+        assert_eq!(
+            s.session_at(utc(2026, 11, 26, 16, 0)),
+            MarketSession::Regular
+        );
+        // nothing is polled on the holiday, so we check back a day later on the Saturday.
+        let next = s.next_wake(utc(2026, 12, 25, 16, 0));
+        assert_eq!(next.reason, SyncReason::SessionChange);
+        assert_eq!(
+            next.at.with_timezone(&Utc),
+            Utc.ymd(2026, 12, 26).and_hms(16, 0, 0)
+        );
+    }
+
+    #[test]
+    fn off_cadence() {
+        let s = scheduler(ScheduleConfig {
+            regular: PollCadence::Off,
+            ..quiet_off()
+        });
+        let now = utc(2026, 1, 7, 16, 0);
+        assert_eq!(s.cadence_at(now), PollCadence::Off);
+        // no cadence during the session, but the close anchor still gets a sync.
+        let next = s.next_wake(now);
+        assert_eq!(next.reason, SyncReason::MarketClose);
+        assert!(s.should_sync(&next));
+        // a session change into a session that is off is skipped.
+        let change = ScheduledSync::new(utc(2026, 1, 10, 12, 0), SyncReason::SessionChange);
+        assert!(!s.should_sync(&change));
+        let change = ScheduledSync::new(utc(2026, 1, 7, 22, 0), SyncReason::SessionChange);
+        assert!(s.should_sync(&change));
+    }
+
+    #[test]
+    fn next_wake_across_weekend() {
+        let s = scheduler(quiet_off());
+        // the NYSE after hours close on Friday is 01:00 UTC Saturday in January.
+        let friday = utc(2026, 1, 10, 0, 40);
+        let next = s.next_wake(friday);
+        assert_eq!(next.reason, SyncReason::Cadence);
+        assert_eq!(next.at, friday + Duration::minutes(15));
+        let next = s.next_wake(utc(2026, 1, 10, 0, 55));
+        assert_eq!(next.reason, SyncReason::SessionChange);
+        assert_eq!(
+            next.at.with_timezone(&Utc),
+            Utc.ymd(2026, 1, 10).and_hms(1, 0, 0)
+        );
+        // the weekend is off, so we only check back once a day until the Monday pre-market.
+        let saturday = utc(2026, 1, 10, 12, 0);
+        assert_eq!(s.session_at(saturday), MarketSession::Weekend);
+        let next = s.next_wake(saturday);
+        assert_eq!(next.reason, SyncReason::SessionChange);
+        assert_eq!(next.at, saturday + Duration::days(1));
+        let next = s.next_wake(next.at);
+        assert_eq!(next.reason, SyncReason::SessionChange);
+        assert_eq!(
+            next.at.with_timezone(&Utc),
+            Utc.ymd(2026, 1, 12).and_hms(9, 0, 0)
+        );
+        assert!(s.should_sync(&next));
+    }
+
+    #[test]
+    fn quiet_sessions_poll_by_default() {
+        let s = scheduler(ScheduleConfig::default());
+        let saturday = utc(2026, 1, 10, 12, 0);
+        assert_eq!(s.cadence_at(saturday), PollCadence::Every(3600));
+        let next = s.next_wake(saturday);
+        assert_eq!(next.reason, SyncReason::Cadence);
+        assert_eq!(next.at, saturday + Duration::hours(1));
+        // the last hourly sync before midnight is followed by one right after it.
+        let midnight = (saturday.date() + Duration::days(1)).and_hms(0, 0, 0);
+        let next = s.next_wake(midnight - Duration::minutes(30));
+        assert_eq!(next.reason, SyncReason::SessionChange);
+        assert_eq!(next.at, midnight + Duration::minutes(1));
+        assert!(s.should_sync(&next));
+    }
+}
@@ -0,0 +1,109 @@
+//! Sub module to contain the account activity specific info.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{
+    AccountActivity, Currency, Deserialize, NaiveDate, PositionSymbol, Serialize,
+};
+
+/// pub type def for the saved activities of an account.
+pub type DBInfoAccountActivityCollection = Vec<DBInfoAccountActivity>;
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+/// The kinds of activity Questrade reports. Anything we don't know about ends up in Other.
+pub enum ActivityType {
+    Trades,
+    Dividends,
+    DividendReinvestment,
+    Deposits,
+    Withdrawals,
+    Fees,
+    Interest,
+    Transfers,
+    FXConversion,
+    CorporateActions,
+    Other,
+}
+
+impl ActivityType {
+    /// Maps the type string Questrade sends us to our enum.
+    pub fn from_questrade(activity_type: &str) -> Self {
+        match activity_type.to_lowercase().as_str() {
+            "trades" => Self::Trades,
+            "dividends" => Self::Dividends,
+            "dividend reinvestment" => Self::DividendReinvestment,
+            "deposits" => Self::Deposits,
+            "withdrawals" => Self::Withdrawals,
+            "fees and rebates" | "fees" => Self::Fees,
+            "interest" => Self::Interest,
+            "transfers" => Self::Transfers,
+            "fx conversion" => Self::FXConversion,
+            "corporate actions" => Self::CorporateActions,
+            _ => Self::Other,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+/// This is the wrapper for our activities, the cash events that explain balance changes.
+pub struct DBInfoAccountActivity {
+    pub activity_type: ActivityType,
+    pub action: String,
+    pub symbol: PositionSymbol,
+    pub symbol_id: u32,
+    pub description: String,
+    pub currency: Currency,
+    pub quantity: f64,
+    pub price: f64,
+    pub gross_amount: f64,
+    pub commission: f64,
+    pub net_amount: f64,
+    pub trade_date: NaiveDate,
+    pub transaction_date: NaiveDate,
+    pub settlement_date: NaiveDate,
+}
+
+impl DBInfoAccountActivity {
+    pub fn new(activity: AccountActivity) -> Self {
+        Self {
+            activity_type: ActivityType::from_questrade(&activity.activity_type),
+            action: activity.action,
+            symbol: activity.symbol,
+            symbol_id: activity.symbol_id,
+            description: activity.description,
+            currency: match activity.currency.as_str() {
+                "USD" => Currency::USD,
+                _ => Currency::CAD,
+            },
+            quantity: activity.quantity.as_f64().unwrap(),
+            price: activity.price.as_f64().unwrap(),
+            gross_amount: activity.gross_amount.as_f64().unwrap(),
+            commission: activity.commission.as_f64().unwrap(),
+            net_amount: activity.net_amount.as_f64().unwrap(),
+            // Questrade dates are midnight Eastern, so the UTC date is the one we want.
+            trade_date: activity.trade_date.naive_utc().date(),
+            transaction_date: activity.transaction_date.naive_utc().date(),
+            settlement_date: activity.settlement_date.naive_utc().date(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+/// Keeps track of how far forward and back we have pulled activities for an account.
+pub struct DBInfoActivitySyncState {
+    pub synced_through: NaiveDate,
+    pub backfilled_to: NaiveDate,
+    pub empty_windows: u32,
+    pub backfill_complete: bool,
+}
+
+impl DBInfoActivitySyncState {
+    pub fn new(start: NaiveDate) -> Self {
+        Self {
+            synced_through: start,
+            backfilled_to: start,
+            empty_windows: 0,
+            backfill_complete: false,
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Sub module to contain the history of alerts that have fired.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{
+    AccountNumber, Deserialize, NaiveDate, NaiveDateTime, NaiveTime, Serialize,
+};
+
+/// pub type def for the saved alerts, kept sorted from earliest to latest.
+pub type DBInfoAlertCollection = Vec<DBInfoAlert>;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+/// An alert rule that fired, along with the value that set it off.
+pub struct DBInfoAlert {
+    pub date: NaiveDate,
+    pub time_triggered: NaiveTime,
+    pub account: AccountNumber,
+    pub rule: String,
+    pub value: f64,
+}
+
+impl DBInfoAlert {
+    pub fn new(
+        date: NaiveDate,
+        time_triggered: NaiveTime,
+        account: AccountNumber,
+        rule: String,
+        value: f64,
+    ) -> Self {
+        Self {
+            date,
+            time_triggered,
+            account,
+            rule,
+            value,
+        }
+    }
+    pub fn datetime(&self) -> NaiveDateTime {
+        self.date.and_time(self.time_triggered)
+    }
+}
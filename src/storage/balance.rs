@@ -4,13 +4,51 @@
 
 use super::{
     super::include::{
-        AccountBalance, Currency, Deserialize, NaiveDate, NaiveTime, Result, Serialize,
+        AccountBalance, Currency, Deserialize, HashMap, NaiveDate, NaiveTime, Result, Serialize,
     },
     errors::DBInsertError,
 };
 
 /// pub type def for the vector of saved day info
 pub type DBInfoAccountBalanceCollection = Vec<DBInfoAccountBalanceDay>;
+/// pub type def for the saved days of every kind of balance on an account
+pub type DBInfoAccountBalanceKinds = HashMap<BalanceKind, DBInfoAccountBalanceCollection>;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+/// Which of the balance lists Questrade sends back a balance came from. CAD and USD are the per
+/// currency balances, Combined is the whole account converted into one currency.
+pub enum BalanceKind {
+    CAD,
+    USD,
+    Combined,
+}
+
+impl BalanceKind {
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind.to_lowercase().as_str() {
+            "cad" => Some(Self::CAD),
+            "usd" => Some(Self::USD),
+            "combined" => Some(Self::Combined),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CAD => "CAD",
+            Self::USD => "USD",
+            Self::Combined => "Combined",
+        }
+    }
+}
+
+impl From<&Currency> for BalanceKind {
+    fn from(currency: &Currency) -> Self {
+        match currency {
+            Currency::CAD => Self::CAD,
+            Currency::USD => Self::USD,
+        }
+    }
+}
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct DBInfoAccountBalance {
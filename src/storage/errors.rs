@@ -13,6 +13,12 @@ pub enum DBInsertError {
     InsertAccountBalanceNoAccountError,
     InsertAccountPositionDuplicateError,
     InsertAccountPositionNoAccountError,
+    InsertAccountOrderNoAccountError,
+    InsertAccountExecutionNoAccountError,
+    InsertAccountActivityNoAccountError,
+    InsertFxRateDuplicateError,
+    InsertAlertNoAccountError,
+    InsertQuoteDuplicateError,
 }
 
 impl error::Error for DBInsertError {}
@@ -44,6 +50,30 @@ impl fmt::Display for DBInsertError {
                 f,
                 "Could not insert Account Position into DataBase! Account that position belongs to does not exist."
             ),
+            Self::InsertAccountOrderNoAccountError => write!(
+                f,
+                "Could not insert Account Order into DataBase! Account that order belongs to does not exist."
+            ),
+            Self::InsertAccountExecutionNoAccountError => write!(
+                f,
+                "Could not insert Account Execution into DataBase! Account that execution belongs to does not exist."
+            ),
+            Self::InsertAccountActivityNoAccountError => write!(
+                f,
+                "Could not insert Account Activity into DataBase! Account that activity belongs to does not exist."
+            ),
+            Self::InsertFxRateDuplicateError => write!(
+                f,
+                "Could not insert Exchange Rate into DataBase! Duplicate already in DB."
+            ),
+            Self::InsertAlertNoAccountError => write!(
+                f,
+                "Could not insert Alert into DataBase! Account that alert belongs to does not exist."
+            ),
+            Self::InsertQuoteDuplicateError => write!(
+                f,
+                "Could not insert Quote into DataBase! Duplicate already in DB."
+            ),
         }
     }
 }
@@ -60,6 +90,15 @@ pub enum DBRetrieveError {
     RetrieveAccountBalanceNotSyncedError,
     RetrieveAccountBalanceNotSyncedDayError(NaiveDate),
     RetrieveAccountPositionNotSyncedDayError(String, NaiveDate),
+    RetrieveAccountOrderAllNotSyncedError,
+    RetrieveAccountOrderNotSyncedError(u32),
+    RetrieveAccountExecutionAllNotSyncedError,
+    RetrieveAccountActivityAllNotSyncedError,
+    RetrieveFxRateNotSyncedError,
+    RetrieveQuoteAllNotSyncedError,
+    RetrieveQuoteNotSyncedError(String),
+    RetrieveQuoteNotSyncedDayError(String, NaiveDate),
+    RetrieveSymbolNotSyncedError(String),
 }
 
 impl error::Error for DBRetrieveError {}
@@ -106,6 +145,45 @@ impl fmt::Display for DBRetrieveError {
                 "Could not find balance! No balances synced for account date: {}.",
                 date
             ),
+            Self::RetrieveAccountOrderAllNotSyncedError => write!(
+                f,
+                "Could not find orders! No orders at all synced for account."
+            ),
+            Self::RetrieveAccountOrderNotSyncedError(id) => write!(
+                f,
+                "Could not find order: {{{}}}. Not synced for account.",
+                id
+            ),
+            Self::RetrieveAccountExecutionAllNotSyncedError => write!(
+                f,
+                "Could not find executions! No executions at all synced for account."
+            ),
+            Self::RetrieveAccountActivityAllNotSyncedError => write!(
+                f,
+                "Could not find activities! No activities at all synced for account."
+            ),
+            Self::RetrieveFxRateNotSyncedError => write!(
+                f,
+                "Could not find exchange rates! No exchange rates synced yet."
+            ),
+            Self::RetrieveQuoteAllNotSyncedError => {
+                write!(f, "Could not find quotes! No quotes synced yet.")
+            }
+            Self::RetrieveQuoteNotSyncedError(s) => write!(
+                f,
+                "Could not find quote: {{{}}}. Not on the watchlist or not synced yet.",
+                s
+            ),
+            Self::RetrieveQuoteNotSyncedDayError(s, date) => write!(
+                f,
+                "Could not find quote! No {{{}}} quotes synced for date: {}.",
+                s, date
+            ),
+            Self::RetrieveSymbolNotSyncedError(s) => write!(
+                f,
+                "Could not find symbol info: {{{}}}. Not looked up yet.",
+                s
+            ),
         }
     }
 }
@@ -0,0 +1,29 @@
+//! Sub module to contain the exchange rates recorded at each sync.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{Deserialize, NaiveDate, NaiveDateTime, NaiveTime, Serialize};
+
+/// pub type def for the saved exchange rates, kept sorted from earliest to latest.
+pub type DBInfoFxRateCollection = Vec<DBInfoFxRate>;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+/// How many CAD one USD bought at the time of a sync.
+pub struct DBInfoFxRate {
+    pub date: NaiveDate,
+    pub time_retrieved: NaiveTime,
+    pub usd_cad: f64,
+}
+
+impl DBInfoFxRate {
+    pub fn new(date: NaiveDate, time_retrieved: NaiveTime, usd_cad: f64) -> Self {
+        Self {
+            date,
+            time_retrieved,
+            usd_cad,
+        }
+    }
+    pub fn datetime(&self) -> NaiveDateTime {
+        self.date.and_time(self.time_retrieved)
+    }
+}
@@ -1,31 +1,44 @@
-//! Module to set up and wrap the Rustbreak db we are using.
+//! Module to set up and wrap the storage backend we are using.
 //! By: Curtis Jones <mail@curtisjones.ca>
 //! Started on: November 8, 2020
 
-#[cfg(feature = "bincode")]
-use super::include::Bincode;
-#[cfg(feature = "default")]
-use super::include::Ron;
-#[cfg(feature = "yaml")]
-use super::include::Yaml;
 use super::{
     config::Config,
     include::{
-        error, hash_map, warn, Account, AccountBalance, AccountName, AccountNumber,
-        AccountPosition, Arc, DateTime, Deserialize, Duration, HashMap, Local, NaiveDate,
-        NaiveTime, PathBuf, PathDatabase, Result, Serialize,
+        error, hash_map, warn, Account, AccountActivity, AccountBalance, AccountExecution,
+        AccountName, AccountNumber, AccountOrder, AccountPosition, Arc, Bincode, DateTime,
+        Deserialize, Duration, HashMap, Local, MarketQuote, NaiveDate, NaiveTime, Number, PathBuf,
+        Result, Ron, Serialize, TimeZone, Yaml,
     },
 };
 
 /// Sub modules
+mod activity;
+mod alert;
 mod balance;
 mod errors;
+mod fx_rate;
+mod order;
 mod position;
+mod quote;
+mod rustbreak_db;
+mod series;
+mod sqlite_db;
+mod symbol;
 
 /// Re-export sub-modules so we can read from them in other modules.
+pub use activity::*;
+pub use alert::*;
 pub use balance::*;
 pub use errors::*;
+pub use fx_rate::*;
+pub use order::*;
 pub use position::*;
+pub use quote::*;
+use rustbreak_db::RustbreakStorage;
+pub use series::*;
+use sqlite_db::SqliteStorage;
+pub use symbol::*;
 
 /// Helper functions
 fn make_dateime_naive(datetime: DateTime<Local>) -> (NaiveDate, NaiveTime) {
@@ -40,48 +53,345 @@ fn duration_abs(dur: Duration) -> Duration {
     }
 }
 
-pub type DBRef = Arc<DB>;
+/// Hour of the day that positions and balances we rebuild ourselves are stored at, right after
+/// the close.
+pub const CLOSE_SNAPSHOT_HOUR: u32 = 16;
 
-#[derive(Debug)]
-#[cfg(feature = "default")]
-/// Only need to make one of these.
-/// The big abstraction to store and retriveve Data.
-/// Depending on the features it can have a Ron storage backend,
-/// a Yaml storage backend, or a Bincode backend.
-pub struct DB {
-    pub db: PathDatabase<DBInfo, Ron>,
+/// When a day we rebuilt ourselves is stored, so it sorts after anything synced during it.
+pub fn close_snapshot_time(date: NaiveDate) -> DateTime<Local> {
+    Local
+        .from_local_datetime(&date.and_hms(CLOSE_SNAPSHOT_HOUR, 0, 0))
+        .earliest()
+        .unwrap_or_else(Local::now)
 }
-#[derive(Debug)]
-#[cfg(feature = "yaml")]
-/// To enable this you would have to disable default and enable 'yaml'.
-pub struct DB {
-    pub db: PathDatabase<DBInfo, Yaml>,
+
+/// Turns a value back into the Number the api types hold, anything that isn't finite is 0.
+pub fn api_number(value: f64) -> Number {
+    Number::from_f64(value).unwrap_or_else(|| Number::from(0))
 }
-#[derive(Debug)]
-#[cfg(feature = "bincode")]
-/// To enable this you would have to disable default and enable 'bincode'.
-pub struct DB {
-    pub db: PathDatabase<DBInfo, Bincode>,
+
+pub type DBRef = Arc<dyn Storage>;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+/// The storage backends that can be picked in the config file.
+pub enum DBBackend {
+    Ron,
+    Yaml,
+    Bincode,
+    Sqlite,
 }
 
-impl DB {
-    /// Should only be called once. Loads a path database at the path provided.
-    pub fn new(config: &Config) -> Result<Self> {
-        Ok(DB {
-            db: PathDatabase::load_from_path_or_default(PathBuf::from(
-                &config.settings.db_file_path,
-            ))?,
-        })
+impl Default for DBBackend {
+    fn default() -> Self {
+        Self::Ron
     }
 }
 
+/// Only need to make one of these.
+/// The big abstraction to store and retriveve Data. Every backend has to be able to do
+/// everything DBInfo does, the rest of the program only ever talks to this trait.
+pub trait Storage: Send + Sync {
+    // *** Insert Functions ***
+    fn insert_account(&self, name: AccountName, account: Account) -> Result<()>;
+    fn insert_account_balance(
+        &self,
+        datetime: DateTime<Local>,
+        number: &AccountNumber,
+        kind: BalanceKind,
+        balance: AccountBalance,
+        sod: AccountBalance,
+    ) -> Result<()>;
+    fn insert_account_position(
+        &self,
+        datetime: DateTime<Local>,
+        number: &AccountNumber,
+        position: AccountPosition,
+        source: PositionSource,
+    ) -> Result<()>;
+    fn insert_account_order(&self, number: &AccountNumber, order: AccountOrder) -> Result<()>;
+    fn insert_account_execution(
+        &self,
+        number: &AccountNumber,
+        execution: AccountExecution,
+    ) -> Result<()>;
+    fn insert_account_activities(
+        &self,
+        number: &AccountNumber,
+        activities: Vec<AccountActivity>,
+    ) -> Result<usize>;
+    fn set_activity_sync_state(
+        &self,
+        number: &AccountNumber,
+        state: DBInfoActivitySyncState,
+    ) -> Result<()>;
+    fn insert_fx_rate(&self, datetime: DateTime<Local>, usd_cad: f64) -> Result<()>;
+    fn insert_alert(
+        &self,
+        datetime: DateTime<Local>,
+        number: &AccountNumber,
+        rule: String,
+        value: f64,
+    ) -> Result<()>;
+    fn insert_quote(&self, datetime: DateTime<Local>, quote: MarketQuote) -> Result<()>;
+    /// Replaces whatever was stored for the symbol before.
+    fn insert_symbol(&self, info: DBInfoSymbol) -> Result<()>;
+
+    // *** Reading functions. ***
+    fn list_account_numbers(&self) -> Result<Vec<AccountNumber>>;
+    fn get_account_list(&self) -> Result<Vec<String>>;
+    fn get_account_info(&self, account_identifier: &str) -> Result<Account>;
+    fn get_start_of_day_balance(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        date: NaiveDate,
+    ) -> Result<DBInfoAccountBalance>;
+    fn get_latest_balance(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        date: NaiveDate,
+    ) -> Result<DBInfoAccountBalance>;
+    fn get_closest_balance(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        date: NaiveDate,
+        time: NaiveTime,
+    ) -> Result<DBInfoAccountBalance>;
+    fn get_balance_days(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountBalanceDay>>;
+    fn get_position_symbols(&self, acct_ident: &str) -> Result<Vec<String>>;
+    fn get_latest_position(
+        &self,
+        acct_ident: &str,
+        position_symbol: &str,
+        date: NaiveDate,
+    ) -> Result<DBInfoAccountPosition>;
+    fn get_closest_position(
+        &self,
+        acct_ident: &str,
+        position_symbol: &str,
+        date: NaiveDate,
+        time: NaiveTime,
+    ) -> Result<DBInfoAccountPosition>;
+    fn get_position_days(
+        &self,
+        acct_ident: &str,
+        position_symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Vec<DBInfoAccountPosition>)>>;
+    fn get_orders(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountOrder>>;
+    fn get_order(&self, acct_ident: &str, order_id: u32) -> Result<DBInfoAccountOrder>;
+    fn get_executions(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountExecution>>;
+    fn get_activity_sync_state(&self, acct_num: &str) -> Result<Option<DBInfoActivitySyncState>>;
+    fn get_activities(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        activity_type: Option<ActivityType>,
+    ) -> Result<Vec<DBInfoAccountActivity>>;
+    /// The rates recorded between two dates, plus the closest one on either side so every
+    /// moment in the range has a rate to use.
+    fn get_fx_rates(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<DBInfoFxRate>>;
+    /// The alerts that fired between two dates, inclusive. No alerts is not an error.
+    fn get_alerts(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<DBInfoAlert>>;
+    /// The last time a rule fired, if it ever has.
+    fn get_last_alert(&self, rule: &str) -> Result<Option<DBInfoAlert>>;
+    /// Every symbol with at least one quote stored.
+    fn get_quote_symbols(&self) -> Result<Vec<String>>;
+    fn get_latest_quote(&self, symbol: &str, date: NaiveDate) -> Result<DBInfoQuote>;
+    /// Every day of quotes for one symbol between two dates, inclusive, earliest first.
+    fn get_quote_days(
+        &self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Vec<DBInfoQuote>)>>;
+    fn get_symbol(&self, symbol: &str) -> Result<DBInfoSymbol>;
+    /// The info of every symbol looked up so far. None at all is not an error.
+    fn get_symbols(&self) -> Result<Vec<DBInfoSymbol>>;
+
+    // *** Persistence ***
+    /// Copies everything from a loaded DBInfo into this backend. Used for migrations.
+    fn import(&self, db_info: DBInfo) -> Result<()>;
+    /// Makes sure everything inserted so far is written out to long-term storage.
+    fn save(&self) -> Result<()>;
+}
+
+/// Should only be called once. Opens the backend picked in the config at the path provided.
+pub fn open_db(config: &Config) -> Result<DBRef> {
+    let path = PathBuf::from(&config.settings.db_file_path);
+    Ok(match config.settings.db_backend {
+        DBBackend::Ron => Arc::new(RustbreakStorage::<Ron>::load(path)?),
+        DBBackend::Yaml => Arc::new(RustbreakStorage::<Yaml>::load(path)?),
+        DBBackend::Bincode => Arc::new(RustbreakStorage::<Bincode>::load(path)?),
+        DBBackend::Sqlite => Arc::new(SqliteStorage::open(path)?),
+    })
+}
+
+/// Opens the DB set in the config for reading only, used by the export command so it can run
+/// alongside the monitor.
+pub fn open_db_read_only(config: &Config) -> Result<DBRef> {
+    let path = PathBuf::from(&config.settings.db_file_path);
+    Ok(match config.settings.db_backend {
+        DBBackend::Ron => Arc::new(RustbreakStorage::<Ron>::open_read_only(path)?),
+        DBBackend::Yaml => Arc::new(RustbreakStorage::<Yaml>::open_read_only(path)?),
+        DBBackend::Bincode => Arc::new(RustbreakStorage::<Bincode>::open_read_only(path)?),
+        DBBackend::Sqlite => Arc::new(SqliteStorage::open_read_only(path)?),
+    })
+}
+
+/// One-shot migration. Reads an old Rustbreak DB file, guessing the encoding from the file
+/// extension, and copies everything in it into the backend picked in the config.
+pub fn migrate_db(config: &Config, from: &PathBuf) -> Result<()> {
+    let db_info = match from.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml") | Some("yml") => RustbreakStorage::<Yaml>::read_file(from)?,
+        Some("bin") | Some("bincode") => RustbreakStorage::<Bincode>::read_file(from)?,
+        _ => RustbreakStorage::<Ron>::read_file(from)?,
+    };
+    let db = open_db(config)?;
+    db.import(db_info)?;
+    db.save()
+}
+
+#[cfg(test)]
+/// An empty SQLite DB held in memory, for testing the modules that read from storage.
+pub fn memory_db() -> DBRef {
+    Arc::new(SqliteStorage::open(PathBuf::from(":memory:")).unwrap())
+}
+
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 /// This is the struct that represents the actual database.
 /// The abstraction above is what gives all the Read/Write protection.
+/// Bincode doesn't write field names, so `#[serde(default)]` only helps Ron and Yaml files. Any
+/// change to the layout needs a legacy struct to read the old one, like the ones below.
 pub struct DBInfo {
+    accounts: HashMap<AccountName, Account>,
+    account_balances: HashMap<AccountNumber, DBInfoAccountBalanceKinds>,
+    account_positions: HashMap<AccountNumber, DBInfoAccountPositionCollection>,
+    #[serde(default)]
+    account_orders: HashMap<AccountNumber, DBInfoAccountOrderCollection>,
+    #[serde(default)]
+    account_executions: HashMap<AccountNumber, DBInfoAccountExecutionCollection>,
+    #[serde(default)]
+    account_activities: HashMap<AccountNumber, DBInfoAccountActivityCollection>,
+    #[serde(default)]
+    account_activity_sync: HashMap<AccountNumber, DBInfoActivitySyncState>,
+    #[serde(default)]
+    fx_rates: DBInfoFxRateCollection,
+    #[serde(default)]
+    alerts: DBInfoAlertCollection,
+    #[serde(default)]
+    quotes: DBInfoQuoteCollection,
+    #[serde(default)]
+    symbols: DBInfoSymbolCollection,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// The layout of the database from before balances were kept for every currency. Only around
+/// so files written by older versions can still be opened.
+pub struct LegacyDBInfo {
     accounts: HashMap<AccountName, Account>,
     account_balances: HashMap<AccountNumber, DBInfoAccountBalanceCollection>,
     account_positions: HashMap<AccountNumber, DBInfoAccountPositionCollection>,
+    #[serde(default)]
+    account_orders: HashMap<AccountNumber, DBInfoAccountOrderCollection>,
+    #[serde(default)]
+    account_executions: HashMap<AccountNumber, DBInfoAccountExecutionCollection>,
+    #[serde(default)]
+    account_activities: HashMap<AccountNumber, DBInfoAccountActivityCollection>,
+    #[serde(default)]
+    account_activity_sync: HashMap<AccountNumber, DBInfoActivitySyncState>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// The layout of the first release, with only accounts, balances, and positions. Ron and Yaml
+/// files from it load as LegacyDBInfo, but Bincode files need the exact fields back.
+pub struct ReleaseDBInfo {
+    accounts: HashMap<AccountName, Account>,
+    account_balances: HashMap<AccountNumber, DBInfoAccountBalanceCollection>,
+    account_positions: HashMap<AccountNumber, ReleaseDBInfoAccountPositionCollection>,
+}
+
+impl From<ReleaseDBInfo> for LegacyDBInfo {
+    fn from(release: ReleaseDBInfo) -> Self {
+        LegacyDBInfo {
+            accounts: release.accounts,
+            account_balances: release.account_balances,
+            account_positions: release
+                .account_positions
+                .into_iter()
+                .map(|(number, symbols)| {
+                    let symbols = symbols
+                        .into_iter()
+                        .map(|(symbol, days)| {
+                            let days = days
+                                .into_iter()
+                                .map(|(date, positions)| {
+                                    (date, positions.into_iter().map(Into::into).collect())
+                                })
+                                .collect();
+                            (symbol, days)
+                        })
+                        .collect();
+                    (number, symbols)
+                })
+                .collect(),
+            account_orders: HashMap::new(),
+            account_executions: HashMap::new(),
+            account_activities: HashMap::new(),
+            account_activity_sync: HashMap::new(),
+        }
+    }
+}
+
+impl From<LegacyDBInfo> for DBInfo {
+    fn from(legacy: LegacyDBInfo) -> Self {
+        // only one per currency balance was kept, so each day goes under the kind matching the
+        // currency it was stored in.
+        let mut account_balances: HashMap<AccountNumber, DBInfoAccountBalanceKinds> =
+            HashMap::new();
+        for (number, days) in legacy.account_balances.into_iter() {
+            let kinds = account_balances.entry(number).or_default();
+            for day in days.into_iter() {
+                kinds
+                    .entry(BalanceKind::from(&day.start_of_day_bal.currency))
+                    .or_default()
+                    .push(day);
+            }
+        }
+        DBInfo {
+            accounts: legacy.accounts,
+            account_balances,
+            account_positions: legacy.account_positions,
+            account_orders: legacy.account_orders,
+            account_executions: legacy.account_executions,
+            account_activities: legacy.account_activities,
+            account_activity_sync: legacy.account_activity_sync,
+            fx_rates: DBInfoFxRateCollection::new(),
+            alerts: DBInfoAlertCollection::new(),
+            quotes: DBInfoQuoteCollection::new(),
+            symbols: DBInfoSymbolCollection::new(),
+        }
+    }
 }
 
 impl DBInfo {
@@ -93,6 +403,14 @@ impl DBInfo {
             accounts: HashMap::new(),
             account_balances: HashMap::new(),
             account_positions: HashMap::new(),
+            account_orders: HashMap::new(),
+            account_executions: HashMap::new(),
+            account_activities: HashMap::new(),
+            account_activity_sync: HashMap::new(),
+            fx_rates: DBInfoFxRateCollection::new(),
+            alerts: DBInfoAlertCollection::new(),
+            quotes: DBInfoQuoteCollection::new(),
+            symbols: DBInfoSymbolCollection::new(),
         }
     }
 
@@ -121,6 +439,7 @@ impl DBInfo {
         &mut self,
         datetime: DateTime<Local>,
         number: &AccountNumber,
+        kind: BalanceKind,
         balance: AccountBalance,
         sod: AccountBalance,
     ) -> Result<()> {
@@ -136,10 +455,12 @@ impl DBInfo {
             Some(abs) => abs,
             None => {
                 self.account_balances
-                    .insert(number.clone(), DBInfoAccountBalanceCollection::new());
+                    .insert(number.clone(), DBInfoAccountBalanceKinds::new());
                 self.account_balances.get_mut(number).unwrap()
             }
         };
+        // each kind of balance keeps its own trace of days.
+        let acct_bal = acct_bal.entry(kind).or_default();
         match acct_bal.iter_mut().find(|ab| ab.date == date) {
             // If we already have a trace of the balances going then we insert the current we were
             // given.
@@ -160,6 +481,7 @@ impl DBInfo {
         datetime: DateTime<Local>,
         number: &AccountNumber,
         position: AccountPosition,
+        source: PositionSource,
     ) -> Result<()> {
         let (date, time) = make_dateime_naive(datetime);
         if None == self.accounts.values().find(|val| val.number == *number) {
@@ -176,7 +498,7 @@ impl DBInfo {
         match acct_map.get_mut(&position.symbol) {
             Some(pm) => match pm.get_mut(&date) {
                 Some(pl) => {
-                    let temp = DBInfoAccountPosition::new(position, time);
+                    let temp = DBInfoAccountPosition::new(position, time, source);
                     if !pl.iter().any(|p| *p == temp) {
                         pl.push(temp);
                         // make sure the list stays sorted from earliest to latest.
@@ -187,24 +509,171 @@ impl DBInfo {
                     }
                 }
                 None => {
-                    pm.insert(date, vec![DBInfoAccountPosition::new(position, time)]);
+                    pm.insert(
+                        date,
+                        vec![DBInfoAccountPosition::new(position, time, source)],
+                    );
                     Ok(())
                 }
             },
             None => {
                 let symbol = position.symbol.clone();
                 let mut day = DBInfoAccountPositionDay::new();
-                day.insert(date, vec![DBInfoAccountPosition::new(position, time)]);
+                day.insert(
+                    date,
+                    vec![DBInfoAccountPosition::new(position, time, source)],
+                );
                 acct_map.insert(symbol, day);
                 Ok(())
             }
         }
     }
 
+    pub fn insert_account_order(
+        &mut self,
+        number: &AccountNumber,
+        order: AccountOrder,
+    ) -> Result<()> {
+        if None == self.accounts.values().find(|val| val.number == *number) {
+            return Err(Box::new(DBInsertError::InsertAccountOrderNoAccountError));
+        }
+        let acct_orders = self
+            .account_orders
+            .entry(number.clone())
+            .or_insert_with(DBInfoAccountOrderCollection::new);
+        let order = DBInfoAccountOrder::new(order);
+        // orders change state over their life so we replace the one we have with the same id.
+        match acct_orders.iter_mut().find(|o| o.id == order.id) {
+            Some(o) => *o = order,
+            None => {
+                acct_orders.push(order);
+                // make sure the list stays sorted from earliest to latest.
+                acct_orders.sort_unstable_by(|a, b| a.creation_time.cmp(&b.creation_time));
+            }
+        }
+        Ok(())
+    }
+    pub fn insert_account_execution(
+        &mut self,
+        number: &AccountNumber,
+        execution: AccountExecution,
+    ) -> Result<()> {
+        if None == self.accounts.values().find(|val| val.number == *number) {
+            return Err(Box::new(
+                DBInsertError::InsertAccountExecutionNoAccountError,
+            ));
+        }
+        let acct_execs = self
+            .account_executions
+            .entry(number.clone())
+            .or_insert_with(DBInfoAccountExecutionCollection::new);
+        // executions never change once they happen, so if we have it already we are done.
+        if !acct_execs.iter().any(|e| e.id == execution.id) {
+            acct_execs.push(DBInfoAccountExecution::new(execution));
+            acct_execs.sort_unstable_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        }
+        Ok(())
+    }
+    // Inserts a batch of activities pulled for one window. Activities don't have an id so
+    // we match them against what we already have and only add the ones that are new.
+    pub fn insert_account_activities(
+        &mut self,
+        number: &AccountNumber,
+        activities: Vec<AccountActivity>,
+    ) -> Result<usize> {
+        if None == self.accounts.values().find(|val| val.number == *number) {
+            return Err(Box::new(DBInsertError::InsertAccountActivityNoAccountError));
+        }
+        let acct_acts = self
+            .account_activities
+            .entry(number.clone())
+            .or_insert_with(DBInfoAccountActivityCollection::new);
+        // each stored activity can only match one new one, so real duplicates in the same
+        // window (two identical buys on the same day) are still kept.
+        let mut matched = vec![false; acct_acts.len()];
+        let mut new_acts = Vec::new();
+        for act in activities.into_iter().map(DBInfoAccountActivity::new) {
+            match acct_acts
+                .iter()
+                .enumerate()
+                .position(|(i, a)| !matched[i] && *a == act)
+            {
+                Some(i) => matched[i] = true,
+                None => new_acts.push(act),
+            }
+        }
+        let inserted = new_acts.len();
+        acct_acts.append(&mut new_acts);
+        // make sure the list stays sorted from earliest to latest.
+        acct_acts.sort_by(|a, b| a.transaction_date.cmp(&b.transaction_date));
+        Ok(inserted)
+    }
+    pub fn set_activity_sync_state(
+        &mut self,
+        number: &AccountNumber,
+        state: DBInfoActivitySyncState,
+    ) {
+        self.account_activity_sync.insert(number.clone(), state);
+    }
+    pub fn insert_fx_rate(&mut self, datetime: DateTime<Local>, usd_cad: f64) -> Result<()> {
+        let (date, time) = make_dateime_naive(datetime);
+        if self
+            .fx_rates
+            .iter()
+            .any(|r| r.date == date && r.time_retrieved == time)
+        {
+            return Err(Box::new(DBInsertError::InsertFxRateDuplicateError));
+        }
+        self.fx_rates.push(DBInfoFxRate::new(date, time, usd_cad));
+        // make sure the list stays sorted from earliest to latest.
+        self.fx_rates.sort_by_key(|r| r.datetime());
+        Ok(())
+    }
+    pub fn insert_alert(
+        &mut self,
+        datetime: DateTime<Local>,
+        number: &AccountNumber,
+        rule: String,
+        value: f64,
+    ) -> Result<()> {
+        let (date, time) = make_dateime_naive(datetime);
+        if !self.iter_accounts().any(|acc| acc.number == *number) {
+            return Err(Box::new(DBInsertError::InsertAlertNoAccountError));
+        }
+        self.alerts
+            .push(DBInfoAlert::new(date, time, number.clone(), rule, value));
+        // make sure the list stays sorted from earliest to latest.
+        self.alerts.sort_by_key(|a| a.datetime());
+        Ok(())
+    }
+    pub fn insert_quote(&mut self, datetime: DateTime<Local>, quote: MarketQuote) -> Result<()> {
+        let (date, time) = make_dateime_naive(datetime);
+        let quote = DBInfoQuote::new(quote, time);
+        let day = self
+            .quotes
+            .entry(quote.symbol.clone())
+            .or_default()
+            .entry(date)
+            .or_default();
+        if day.iter().any(|q| q.time_retrieved == time) {
+            return Err(Box::new(DBInsertError::InsertQuoteDuplicateError));
+        }
+        day.push(quote);
+        // make sure the list stays sorted from earliest to latest.
+        day.sort_by_key(|q| q.time_retrieved);
+        Ok(())
+    }
+    pub fn insert_symbol(&mut self, info: DBInfoSymbol) {
+        self.symbols.insert(info.symbol.clone(), info);
+    }
+
     // *** Reading functions. ***
     pub fn iter_accounts(&self) -> hash_map::Values<'_, String, Account> {
         self.accounts.values()
     }
+    pub fn list_account_numbers(&self) -> Vec<AccountNumber> {
+        self.iter_accounts().map(|acc| acc.number.clone()).collect()
+    }
 
     // ** get account info **
     pub fn get_account_list(&self) -> Result<Vec<String>> {
@@ -238,6 +707,7 @@ impl DBInfo {
     pub fn get_start_of_day_balance(
         &self,
         account_identifier: &str,
+        kind: BalanceKind,
         date: NaiveDate,
     ) -> Result<DBInfoAccountBalance> {
         // first we verify that we have a valid account identifier and reduce it to just a number.
@@ -249,7 +719,7 @@ impl DBInfo {
             }
         };
         // now that we have a valid account number we use it to pull the balance collection.
-        let todays_bal = match self.list_balances_of_date(&account_number, &date) {
+        let todays_bal = match self.list_balances_of_date(&account_number, kind, &date) {
             Ok(tb) => tb,
             Err(e) => {
                 warn!("Could not retrieve balance, with error: {}", e);
@@ -263,6 +733,7 @@ impl DBInfo {
     pub fn get_latest_balance(
         &self,
         account_identifier: &str,
+        kind: BalanceKind,
         date: NaiveDate,
     ) -> Result<DBInfoAccountBalance> {
         // first we verify that we have a valid account identifier and reduce it to just a number.
@@ -274,7 +745,7 @@ impl DBInfo {
             }
         };
         // now that we have a valid account number we use it to pull the balance collection.
-        let todays_bal = match self.list_balances_of_date(&account_number, &date) {
+        let todays_bal = match self.list_balances_of_date(&account_number, kind, &date) {
             Ok(tb) => tb,
             Err(e) => {
                 warn!("Could not retrieve balance, with error: {}", e);
@@ -289,6 +760,7 @@ impl DBInfo {
     pub fn get_closest_balance(
         &self,
         account_identifier: &str,
+        kind: BalanceKind,
         date: NaiveDate,
         time: NaiveTime,
     ) -> Result<DBInfoAccountBalance> {
@@ -301,7 +773,7 @@ impl DBInfo {
             }
         };
         // now that we have a valid account number we use it to pull the balance collection.
-        let todays_bal = match self.list_balances_of_date(&account_number, &date) {
+        let todays_bal = match self.list_balances_of_date(&account_number, kind, &date) {
             Ok(tb) => tb,
             Err(e) => {
                 warn!("Could not retrieve balance, with error: {}", e);
@@ -326,6 +798,43 @@ impl DBInfo {
         // Once we get to the end or break early we return whatever is in the var.
         Ok(return_bal.clone())
     }
+    // function to get every day of balances between two dates, inclusive.
+    pub fn get_balance_days(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountBalanceDay>> {
+        // first we verify that we have a valid account identifier and reduce it to just a number.
+        let account_number = match self.acct_identifier_to_number(account_identifier) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve balances, with error: {}", e);
+                return Err(e);
+            }
+        };
+        match self
+            .account_balances
+            .get(&account_number)
+            .and_then(|kinds| kinds.get(&kind))
+        {
+            Some(balance_days) => {
+                let mut days: Vec<DBInfoAccountBalanceDay> = balance_days
+                    .iter()
+                    .filter(|bd| bd.date >= from && bd.date <= to)
+                    .cloned()
+                    .collect();
+                days.sort_unstable_by(|a, b| a.date.cmp(&b.date));
+                Ok(days)
+            }
+            None => {
+                let e = Box::new(DBRetrieveError::RetrieveAccountBalanceNotSyncedError);
+                warn!("Could not retrieve balances, with error: {}", e);
+                Err(e)
+            }
+        }
+    }
     // function to get a list of position symbols.
     pub fn get_position_symbols(&self, acct_ident: &str) -> Result<Vec<String>> {
         // first we verify that we have a valid account identifier and reduce it to just a number.
@@ -404,6 +913,251 @@ impl DBInfo {
         }
         Ok(result.clone())
     }
+    // function to get every day of snapshots for one position between two dates, inclusive.
+    pub fn get_position_days(
+        &self,
+        acct_ident: &str,
+        position_symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Vec<DBInfoAccountPosition>)>> {
+        // first we verify that we have a valid account identifier and reduce it to just a number.
+        let acc_num = match self.acct_identifier_to_number(acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve positions, with error: {}", e);
+                return Err(e);
+            }
+        };
+        let pos_days = match self.account_positions.get(&acc_num) {
+            Some(pos_collection) => match pos_collection.get(position_symbol) {
+                Some(pos_days) => pos_days,
+                None => {
+                    let e = Box::new(DBRetrieveError::RetrieveAccountPositionNotSyncedError(
+                        position_symbol.to_string(),
+                    ));
+                    warn!("Could not retrieve positions, with error: {}", e);
+                    return Err(e);
+                }
+            },
+            None => {
+                let e = Box::new(DBRetrieveError::RetrieveAccountPositionAllNotSyncedError);
+                warn!("Could not retrieve positions, with error: {}", e);
+                return Err(e);
+            }
+        };
+        // the days are kept in a HashMap so we have to put them in order ourselves.
+        let mut days: Vec<(NaiveDate, Vec<DBInfoAccountPosition>)> = pos_days
+            .iter()
+            .filter(|(date, _)| **date >= from && **date <= to)
+            .map(|(date, positions)| (*date, positions.clone()))
+            .collect();
+        days.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        Ok(days)
+    }
+    // ** get order & execution info **
+    // function to get all the orders created between two dates, inclusive.
+    pub fn get_orders(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountOrder>> {
+        // first we verify that we have a valid account identifier and reduce it to just a number.
+        let acc_num = match self.acct_identifier_to_number(acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve orders, with error: {}", e);
+                return Err(e);
+            }
+        };
+        match self.account_orders.get(&acc_num) {
+            Some(orders) => Ok(orders
+                .iter()
+                .filter(|o| {
+                    let date = o.creation_time.date().naive_local();
+                    date >= from && date <= to
+                })
+                .cloned()
+                .collect()),
+            None => {
+                let e = Box::new(DBRetrieveError::RetrieveAccountOrderAllNotSyncedError);
+                warn!("Could not retrieve orders, with error: {}", e);
+                Err(e)
+            }
+        }
+    }
+    pub fn get_order(&self, acct_ident: &str, order_id: u32) -> Result<DBInfoAccountOrder> {
+        // first we verify that we have a valid account identifier and reduce it to just a number.
+        let acc_num = match self.acct_identifier_to_number(acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve order, with error: {}", e);
+                return Err(e);
+            }
+        };
+        match self
+            .account_orders
+            .get(&acc_num)
+            .and_then(|orders| orders.iter().find(|o| o.id == order_id))
+        {
+            Some(order) => Ok(order.clone()),
+            None => {
+                let e = Box::new(DBRetrieveError::RetrieveAccountOrderNotSyncedError(
+                    order_id,
+                ));
+                warn!("Could not retrieve order, with error: {}", e);
+                Err(e)
+            }
+        }
+    }
+    // function to get all the executions that happened between two dates, inclusive.
+    pub fn get_executions(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountExecution>> {
+        // first we verify that we have a valid account identifier and reduce it to just a number.
+        let acc_num = match self.acct_identifier_to_number(acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve executions, with error: {}", e);
+                return Err(e);
+            }
+        };
+        match self.account_executions.get(&acc_num) {
+            Some(execs) => Ok(execs
+                .iter()
+                .filter(|e| {
+                    let date = e.timestamp.date().naive_local();
+                    date >= from && date <= to
+                })
+                .cloned()
+                .collect()),
+            None => {
+                let e = Box::new(DBRetrieveError::RetrieveAccountExecutionAllNotSyncedError);
+                warn!("Could not retrieve executions, with error: {}", e);
+                Err(e)
+            }
+        }
+    }
+    // ** get activity info **
+    pub fn get_activity_sync_state(&self, acct_num: &str) -> Option<DBInfoActivitySyncState> {
+        self.account_activity_sync.get(acct_num).cloned()
+    }
+    // function to get the activities between two dates, inclusive, optionally of one type.
+    pub fn get_activities(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        activity_type: Option<ActivityType>,
+    ) -> Result<Vec<DBInfoAccountActivity>> {
+        // first we verify that we have a valid account identifier and reduce it to just a number.
+        let acc_num = match self.acct_identifier_to_number(acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve activities, with error: {}", e);
+                return Err(e);
+            }
+        };
+        match self.account_activities.get(&acc_num) {
+            Some(acts) => Ok(acts
+                .iter()
+                .filter(|a| a.transaction_date >= from && a.transaction_date <= to)
+                .filter(|a| activity_type.map_or(true, |at| a.activity_type == at))
+                .cloned()
+                .collect()),
+            None => {
+                let e = Box::new(DBRetrieveError::RetrieveAccountActivityAllNotSyncedError);
+                warn!("Could not retrieve activities, with error: {}", e);
+                Err(e)
+            }
+        }
+    }
+    // ** get exchange rate info **
+    pub fn get_fx_rates(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<DBInfoFxRate>> {
+        if self.fx_rates.is_empty() {
+            let e = Box::new(DBRetrieveError::RetrieveFxRateNotSyncedError);
+            warn!("Could not retrieve exchange rates, with error: {}", e);
+            return Err(e);
+        }
+        let before = self.fx_rates.iter().rev().find(|r| r.date < from);
+        let after = self.fx_rates.iter().find(|r| r.date > to);
+        Ok(before
+            .into_iter()
+            .chain(
+                self.fx_rates
+                    .iter()
+                    .filter(|r| r.date >= from && r.date <= to),
+            )
+            .chain(after)
+            .cloned()
+            .collect())
+    }
+    // ** get alert info **
+    pub fn get_alerts(&self, from: NaiveDate, to: NaiveDate) -> Vec<DBInfoAlert> {
+        self.alerts
+            .iter()
+            .filter(|a| a.date >= from && a.date <= to)
+            .cloned()
+            .collect()
+    }
+    pub fn get_last_alert(&self, rule: &str) -> Option<DBInfoAlert> {
+        self.alerts.iter().rev().find(|a| a.rule == rule).cloned()
+    }
+    // ** get quote info **
+    pub fn get_quote_symbols(&self) -> Result<Vec<String>> {
+        if self.quotes.is_empty() {
+            let e = Box::new(DBRetrieveError::RetrieveQuoteAllNotSyncedError);
+            warn!("Could not retrieve quotes, with error: {}", e);
+            return Err(e);
+        }
+        Ok(self.quotes.keys().map(|k| k.to_string()).collect())
+    }
+    pub fn get_latest_quote(&self, symbol: &str, date: NaiveDate) -> Result<DBInfoQuote> {
+        let quote_days = match self.quote_days_of_symbol(symbol) {
+            Ok(qd) => qd,
+            Err(e) => {
+                warn!("Could not retrieve quote, with error: {}", e);
+                return Err(e);
+            }
+        };
+        match quote_days.get(&date).and_then(|quotes| quotes.last()) {
+            Some(quote) => Ok(quote.clone()),
+            None => {
+                let e = Box::new(DBRetrieveError::RetrieveQuoteNotSyncedDayError(
+                    symbol.to_string(),
+                    date,
+                ));
+                warn!("Could not retrieve quote, with error: {}", e);
+                Err(e)
+            }
+        }
+    }
+    pub fn get_quote_days(
+        &self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Vec<DBInfoQuote>)>> {
+        let quote_days = match self.quote_days_of_symbol(symbol) {
+            Ok(qd) => qd,
+            Err(e) => {
+                warn!("Could not retrieve quotes, with error: {}", e);
+                return Err(e);
+            }
+        };
+        // same as the positions, the days are in a HashMap so we sort them ourselves.
+        let mut days: Vec<(NaiveDate, Vec<DBInfoQuote>)> = quote_days
+            .iter()
+            .filter(|(date, _)| **date >= from && **date <= to)
+            .map(|(date, quotes)| (*date, quotes.clone()))
+            .collect();
+        days.sort_unstable_by_key(|(date, _)| *date);
+        Ok(days)
+    }
     // ** Helper methods. **
     fn acct_identifier_to_number(&self, acct_ident: &str) -> Result<String> {
         match self.accounts.get(acct_ident) {
@@ -445,12 +1199,40 @@ impl DBInfo {
             )),
         }
     }
+    // ** get symbol info **
+    pub fn get_symbol(&self, symbol: &str) -> Result<DBInfoSymbol> {
+        match self.symbols.get(symbol) {
+            Some(info) => Ok(info.clone()),
+            None => Err(Box::new(DBRetrieveError::RetrieveSymbolNotSyncedError(
+                symbol.to_string(),
+            ))),
+        }
+    }
+    pub fn get_symbols(&self) -> Vec<DBInfoSymbol> {
+        self.symbols.values().cloned().collect()
+    }
+    fn quote_days_of_symbol(&self, symbol: &str) -> Result<&DBInfoQuoteDay> {
+        match self.quotes.get(symbol) {
+            Some(quote_days) => Ok(quote_days),
+            None if self.quotes.is_empty() => {
+                Err(Box::new(DBRetrieveError::RetrieveQuoteAllNotSyncedError))
+            }
+            None => Err(Box::new(DBRetrieveError::RetrieveQuoteNotSyncedError(
+                symbol.to_string(),
+            ))),
+        }
+    }
     fn list_balances_of_date(
         &self,
         acct_num: &str,
+        kind: BalanceKind,
         date: &NaiveDate,
     ) -> Result<&DBInfoAccountBalanceDay> {
-        match self.account_balances.get(acct_num) {
+        match self
+            .account_balances
+            .get(acct_num)
+            .and_then(|kinds| kinds.get(&kind))
+        {
             Some(balances_days) => match balances_days.iter().find(|bd| bd.date == *date) {
                 Some(bal_day) => Ok(bal_day),
                 None => Err(Box::new(
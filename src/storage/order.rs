@@ -0,0 +1,96 @@
+//! Sub module to contain the account order and execution specific info.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{
+    AccountExecution, AccountOrder, DateTime, Deserialize, Local, OrderSide, OrderState,
+    OrderTimeInForce, OrderType, PositionSymbol, Serialize,
+};
+
+/// pub type defs for the saved orders and executions of an account.
+pub type DBInfoAccountOrderCollection = Vec<DBInfoAccountOrder>;
+pub type DBInfoAccountExecutionCollection = Vec<DBInfoAccountExecution>;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+/// This is the wrapper for our orders.
+pub struct DBInfoAccountOrder {
+    pub id: u32,
+    pub symbol: PositionSymbol,
+    pub symbol_id: u32,
+    pub total_quantity: f64,
+    pub open_quantity: f64,
+    pub filled_quantity: f64,
+    pub canceled_quantity: f64,
+    pub side: OrderSide,
+    pub order_type: OrderType,
+    pub limit_price: Option<f64>,
+    pub stop_price: Option<f64>,
+    pub avg_execution_price: Option<f64>,
+    pub time_in_force: OrderTimeInForce,
+    pub state: OrderState,
+    pub commission_charged: f64,
+    pub notes: Option<String>,
+    pub creation_time: DateTime<Local>,
+    pub update_time: DateTime<Local>,
+}
+
+impl DBInfoAccountOrder {
+    pub fn new(order: AccountOrder) -> Self {
+        Self {
+            id: order.id,
+            symbol: order.symbol,
+            symbol_id: order.symbol_id,
+            total_quantity: order.total_quantity.as_f64().unwrap(),
+            open_quantity: order.open_quantity.as_f64().unwrap(),
+            filled_quantity: order.filled_quantity.as_f64().unwrap(),
+            canceled_quantity: order.canceled_quantity.as_f64().unwrap(),
+            side: order.side,
+            order_type: order.order_type,
+            limit_price: order.limit_price.and_then(|n| n.as_f64()),
+            stop_price: order.stop_price.and_then(|n| n.as_f64()),
+            avg_execution_price: order.avg_execution_price.and_then(|n| n.as_f64()),
+            time_in_force: order.time_in_force,
+            state: order.state,
+            commission_charged: order.commission_charged.as_f64().unwrap(),
+            notes: order.notes,
+            creation_time: order.creation_time.with_timezone(&Local),
+            update_time: order.update_time.with_timezone(&Local),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+/// This is the wrapper for our executions, the actual fills of an order.
+pub struct DBInfoAccountExecution {
+    pub id: u32,
+    pub order_id: u32,
+    pub symbol: PositionSymbol,
+    pub symbol_id: u32,
+    pub quantity: f64,
+    pub side: OrderSide,
+    pub price: f64,
+    pub commission: f64,
+    pub execution_fee: f64,
+    pub sec_fee: f64,
+    pub canadian_execution_fee: f64,
+    pub timestamp: DateTime<Local>,
+}
+
+impl DBInfoAccountExecution {
+    pub fn new(execution: AccountExecution) -> Self {
+        Self {
+            id: execution.id,
+            order_id: execution.order_id,
+            symbol: execution.symbol,
+            symbol_id: execution.symbol_id,
+            quantity: execution.quantity.as_f64().unwrap(),
+            side: execution.side,
+            price: execution.price.as_f64().unwrap(),
+            commission: execution.commission.as_f64().unwrap(),
+            execution_fee: execution.execution_fee.as_f64().unwrap(),
+            sec_fee: execution.sec_fee.as_f64().unwrap(),
+            canadian_execution_fee: execution.canadian_execution_fee.as_f64().unwrap(),
+            timestamp: execution.timestamp.with_timezone(&Local),
+        }
+    }
+}
@@ -9,6 +9,40 @@ use super::super::include::{
 pub type DBInfoAccountPositionDay = HashMap<NaiveDate, Vec<DBInfoAccountPosition>>;
 pub type DBInfoAccountPositionCollection = HashMap<PositionSymbol, DBInfoAccountPositionDay>;
 
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+/// Where a position snapshot came from.
+pub enum PositionSource {
+    /// Pulled from the api during a sync.
+    Live,
+    /// Read out of a statement with the import subcommand.
+    Imported,
+    /// Rebuilt from daily candles for a day the monitor wasn't running.
+    Backfilled,
+}
+
+impl Default for PositionSource {
+    fn default() -> Self {
+        Self::Live
+    }
+}
+
+impl PositionSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Live => "Live",
+            Self::Imported => "Imported",
+            Self::Backfilled => "Backfilled",
+        }
+    }
+    pub fn parse(source: &str) -> Self {
+        match source {
+            "Imported" => Self::Imported,
+            "Backfilled" => Self::Backfilled,
+            _ => Self::Live,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 /// This is the wrapper for our positions.
 pub struct DBInfoAccountPosition {
@@ -23,10 +57,16 @@ pub struct DBInfoAccountPosition {
     pub open_pnl: f64,
     pub total_cost: f64,
     pub time_retrieved: NaiveTime,
+    #[serde(default)]
+    pub source: PositionSource,
 }
 
 impl DBInfoAccountPosition {
-    pub fn new(position: AccountPosition, time_retrieved: NaiveTime) -> Self {
+    pub fn new(
+        position: AccountPosition,
+        time_retrieved: NaiveTime,
+        source: PositionSource,
+    ) -> Self {
         Self {
             symbol: position.symbol,
             open_quantity: position.open_quantity.as_f64().unwrap(),
@@ -39,6 +79,46 @@ impl DBInfoAccountPosition {
             open_pnl: position.open_profit_and_loss.as_f64().unwrap(),
             total_cost: position.total_cost.as_f64().unwrap(),
             time_retrieved,
+            source,
+        }
+    }
+}
+
+pub type ReleaseDBInfoAccountPositionCollection =
+    HashMap<PositionSymbol, HashMap<NaiveDate, Vec<ReleaseDBInfoAccountPosition>>>;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// A position as the first release stored it, before we kept track of where it came from.
+pub struct ReleaseDBInfoAccountPosition {
+    pub symbol: PositionSymbol,
+    pub open_quantity: f64,
+    pub closed_quantity: f64,
+    pub current_market_value: f64,
+    pub current_price: f64,
+    pub average_entry_price: f64,
+    pub closed_pnl: f64,
+    pub day_pnl: f64,
+    pub open_pnl: f64,
+    pub total_cost: f64,
+    pub time_retrieved: NaiveTime,
+}
+
+impl From<ReleaseDBInfoAccountPosition> for DBInfoAccountPosition {
+    fn from(position: ReleaseDBInfoAccountPosition) -> Self {
+        // everything back then came from a sync.
+        Self {
+            symbol: position.symbol,
+            open_quantity: position.open_quantity,
+            closed_quantity: position.closed_quantity,
+            current_market_value: position.current_market_value,
+            current_price: position.current_price,
+            average_entry_price: position.average_entry_price,
+            closed_pnl: position.closed_pnl,
+            day_pnl: position.day_pnl,
+            open_pnl: position.open_pnl,
+            total_cost: position.total_cost,
+            time_retrieved: position.time_retrieved,
+            source: PositionSource::Live,
         }
     }
 }
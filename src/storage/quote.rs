@@ -0,0 +1,44 @@
+//! Sub module to contain the Level 1 quotes of the symbols on the watchlist.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{
+    Deserialize, HashMap, MarketQuote, NaiveDate, NaiveTime, PositionSymbol, Serialize,
+};
+
+pub type DBInfoQuoteDay = HashMap<NaiveDate, Vec<DBInfoQuote>>;
+pub type DBInfoQuoteCollection = HashMap<PositionSymbol, DBInfoQuoteDay>;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+/// One quote of a symbol, bid and ask are missing when the market for it is shut.
+pub struct DBInfoQuote {
+    pub symbol: PositionSymbol,
+    pub bid_price: Option<f64>,
+    pub ask_price: Option<f64>,
+    pub last_trade_price: f64,
+    pub volume: u32,
+    pub open_price: f64,
+    pub high_price: f64,
+    pub low_price: f64,
+    pub delay: bool,
+    pub is_halted: bool,
+    pub time_retrieved: NaiveTime,
+}
+
+impl DBInfoQuote {
+    pub fn new(quote: MarketQuote, time_retrieved: NaiveTime) -> Self {
+        Self {
+            symbol: quote.symbol,
+            bid_price: quote.bid_price.and_then(|p| p.as_f64()),
+            ask_price: quote.ask_price.and_then(|p| p.as_f64()),
+            last_trade_price: quote.last_trade_price.as_f64().unwrap(),
+            volume: quote.volume,
+            open_price: quote.open_price.as_f64().unwrap(),
+            high_price: quote.high_price.as_f64().unwrap(),
+            low_price: quote.low_price.as_f64().unwrap(),
+            delay: quote.delay,
+            is_halted: quote.is_halted,
+            time_retrieved,
+        }
+    }
+}
@@ -0,0 +1,326 @@
+//! Sub module with the Rustbreak backed implementation of Storage.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::include::{
+        info, Account, AccountActivity, AccountBalance, AccountExecution, AccountName,
+        AccountNumber, AccountOrder, AccountPosition, DateTime, DeSerializer, Local, MarketQuote,
+        NaiveDate, NaiveTime, PathBackend, PathBuf, PathDatabase, Result,
+    },
+    ActivityType, BalanceKind, DBInfo, DBInfoAccountActivity, DBInfoAccountBalance,
+    DBInfoAccountBalanceDay, DBInfoAccountExecution, DBInfoAccountOrder, DBInfoAccountPosition,
+    DBInfoActivitySyncState, DBInfoAlert, DBInfoFxRate, DBInfoQuote, DBInfoSymbol, LegacyDBInfo,
+    PositionSource, ReleaseDBInfo, Storage,
+};
+
+#[derive(Debug)]
+/// A path database holding the whole DBInfo in memory. Depending on the DeSerializer it is
+/// saved to disk as Ron, Yaml, or Bincode.
+pub struct RustbreakStorage<D: DeSerializer<DBInfo> + Send + Sync + Clone> {
+    db: PathDatabase<DBInfo, D>,
+}
+
+impl<D> RustbreakStorage<D>
+where
+    D: DeSerializer<DBInfo>
+        + DeSerializer<LegacyDBInfo>
+        + DeSerializer<ReleaseDBInfo>
+        + Send
+        + Sync
+        + Clone,
+{
+    /// Loads a path database at the path provided, or starts a new one if it isn't there.
+    /// Files written before balances were kept for every currency get upgraded in place.
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let db = match PathDatabase::load_from_path_or_default(path.clone()) {
+            Ok(db) => db,
+            Err(e) => match Self::read_legacy_file(&path) {
+                Some(db_info) => {
+                    info!(
+                        "Upgrading DB file @ [{}] to store every currency...",
+                        path.to_str().unwrap_or_default()
+                    );
+                    let db = PathDatabase::create_at_path(path, db_info)?;
+                    db.save()?;
+                    db
+                }
+                None => return Err(Box::new(e)),
+            },
+        };
+        Ok(Self { db })
+    }
+    /// Loads an existing file without ever writing to it, old layouts are upgraded in memory
+    /// only. Errors out if the file isn't there.
+    pub fn open_read_only(path: PathBuf) -> Result<Self>
+    where
+        D: Default,
+    {
+        let db_info = Self::read_file(&path)?;
+        let backend = PathBackend::from_path_or_fail(path)?;
+        Ok(Self {
+            db: PathDatabase::from_parts(db_info, backend, D::default()),
+        })
+    }
+    /// Reads the whole DBInfo out of an existing file, errors out if it isn't there.
+    pub fn read_file(path: &PathBuf) -> Result<DBInfo> {
+        match PathDatabase::<DBInfo, D>::load_from_path(path.clone()) {
+            Ok(db) => Ok(db.get_data(false)?),
+            Err(e) => match Self::read_legacy_file(path) {
+                Some(db_info) => Ok(db_info),
+                None => Err(Box::new(e)),
+            },
+        }
+    }
+    // tries the file again with the old layouts, the first error is the one worth passing up.
+    fn read_legacy_file(path: &PathBuf) -> Option<DBInfo> {
+        PathDatabase::<LegacyDBInfo, D>::load_from_path(path.clone())
+            .and_then(|db| db.get_data(false))
+            .or_else(|_| {
+                // bincode files of the first release are missing every collection after positions.
+                PathDatabase::<ReleaseDBInfo, D>::load_from_path(path.clone())
+                    .and_then(|db| db.get_data(false))
+                    .map(LegacyDBInfo::from)
+            })
+            .ok()
+            .map(DBInfo::from)
+    }
+}
+
+impl<D: DeSerializer<DBInfo> + Send + Sync + Clone> Storage for RustbreakStorage<D> {
+    // *** Insert Functions ***
+    fn insert_account(&self, name: AccountName, account: Account) -> Result<()> {
+        self.db
+            .write(|db_info| db_info.insert_account(name, account))?
+    }
+    fn insert_account_balance(
+        &self,
+        datetime: DateTime<Local>,
+        number: &AccountNumber,
+        kind: BalanceKind,
+        balance: AccountBalance,
+        sod: AccountBalance,
+    ) -> Result<()> {
+        self.db
+            .write(|db_info| db_info.insert_account_balance(datetime, number, kind, balance, sod))?
+    }
+    fn insert_account_position(
+        &self,
+        datetime: DateTime<Local>,
+        number: &AccountNumber,
+        position: AccountPosition,
+        source: PositionSource,
+    ) -> Result<()> {
+        self.db
+            .write(|db_info| db_info.insert_account_position(datetime, number, position, source))?
+    }
+    fn insert_account_order(&self, number: &AccountNumber, order: AccountOrder) -> Result<()> {
+        self.db
+            .write(|db_info| db_info.insert_account_order(number, order))?
+    }
+    fn insert_account_execution(
+        &self,
+        number: &AccountNumber,
+        execution: AccountExecution,
+    ) -> Result<()> {
+        self.db
+            .write(|db_info| db_info.insert_account_execution(number, execution))?
+    }
+    fn insert_account_activities(
+        &self,
+        number: &AccountNumber,
+        activities: Vec<AccountActivity>,
+    ) -> Result<usize> {
+        self.db
+            .write(|db_info| db_info.insert_account_activities(number, activities))?
+    }
+    fn set_activity_sync_state(
+        &self,
+        number: &AccountNumber,
+        state: DBInfoActivitySyncState,
+    ) -> Result<()> {
+        Ok(self
+            .db
+            .write(|db_info| db_info.set_activity_sync_state(number, state))?)
+    }
+    fn insert_fx_rate(&self, datetime: DateTime<Local>, usd_cad: f64) -> Result<()> {
+        self.db
+            .write(|db_info| db_info.insert_fx_rate(datetime, usd_cad))?
+    }
+    fn insert_alert(
+        &self,
+        datetime: DateTime<Local>,
+        number: &AccountNumber,
+        rule: String,
+        value: f64,
+    ) -> Result<()> {
+        self.db
+            .write(|db_info| db_info.insert_alert(datetime, number, rule, value))?
+    }
+    fn insert_quote(&self, datetime: DateTime<Local>, quote: MarketQuote) -> Result<()> {
+        self.db
+            .write(|db_info| db_info.insert_quote(datetime, quote))?
+    }
+    fn insert_symbol(&self, info: DBInfoSymbol) -> Result<()> {
+        Ok(self.db.write(|db_info| db_info.insert_symbol(info))?)
+    }
+
+    // *** Reading functions. ***
+    fn list_account_numbers(&self) -> Result<Vec<AccountNumber>> {
+        Ok(self.db.read(|db_info| db_info.list_account_numbers())?)
+    }
+    fn get_account_list(&self) -> Result<Vec<String>> {
+        self.db.read(|db_info| db_info.get_account_list())?
+    }
+    fn get_account_info(&self, account_identifier: &str) -> Result<Account> {
+        self.db
+            .read(|db_info| db_info.get_account_info(account_identifier))?
+    }
+    fn get_start_of_day_balance(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        date: NaiveDate,
+    ) -> Result<DBInfoAccountBalance> {
+        self.db
+            .read(|db_info| db_info.get_start_of_day_balance(account_identifier, kind, date))?
+    }
+    fn get_latest_balance(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        date: NaiveDate,
+    ) -> Result<DBInfoAccountBalance> {
+        self.db
+            .read(|db_info| db_info.get_latest_balance(account_identifier, kind, date))?
+    }
+    fn get_closest_balance(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        date: NaiveDate,
+        time: NaiveTime,
+    ) -> Result<DBInfoAccountBalance> {
+        self.db
+            .read(|db_info| db_info.get_closest_balance(account_identifier, kind, date, time))?
+    }
+    fn get_balance_days(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountBalanceDay>> {
+        self.db
+            .read(|db_info| db_info.get_balance_days(account_identifier, kind, from, to))?
+    }
+    fn get_position_symbols(&self, acct_ident: &str) -> Result<Vec<String>> {
+        self.db
+            .read(|db_info| db_info.get_position_symbols(acct_ident))?
+    }
+    fn get_latest_position(
+        &self,
+        acct_ident: &str,
+        position_symbol: &str,
+        date: NaiveDate,
+    ) -> Result<DBInfoAccountPosition> {
+        self.db
+            .read(|db_info| db_info.get_latest_position(acct_ident, position_symbol, date))?
+    }
+    fn get_closest_position(
+        &self,
+        acct_ident: &str,
+        position_symbol: &str,
+        date: NaiveDate,
+        time: NaiveTime,
+    ) -> Result<DBInfoAccountPosition> {
+        self.db
+            .read(|db_info| db_info.get_closest_position(acct_ident, position_symbol, date, time))?
+    }
+    fn get_position_days(
+        &self,
+        acct_ident: &str,
+        position_symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Vec<DBInfoAccountPosition>)>> {
+        self.db
+            .read(|db_info| db_info.get_position_days(acct_ident, position_symbol, from, to))?
+    }
+    fn get_orders(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountOrder>> {
+        self.db
+            .read(|db_info| db_info.get_orders(acct_ident, from, to))?
+    }
+    fn get_order(&self, acct_ident: &str, order_id: u32) -> Result<DBInfoAccountOrder> {
+        self.db
+            .read(|db_info| db_info.get_order(acct_ident, order_id))?
+    }
+    fn get_executions(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountExecution>> {
+        self.db
+            .read(|db_info| db_info.get_executions(acct_ident, from, to))?
+    }
+    fn get_activity_sync_state(&self, acct_num: &str) -> Result<Option<DBInfoActivitySyncState>> {
+        Ok(self
+            .db
+            .read(|db_info| db_info.get_activity_sync_state(acct_num))?)
+    }
+    fn get_activities(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        activity_type: Option<ActivityType>,
+    ) -> Result<Vec<DBInfoAccountActivity>> {
+        self.db
+            .read(|db_info| db_info.get_activities(acct_ident, from, to, activity_type))?
+    }
+    fn get_fx_rates(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<DBInfoFxRate>> {
+        self.db.read(|db_info| db_info.get_fx_rates(from, to))?
+    }
+    fn get_alerts(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<DBInfoAlert>> {
+        Ok(self.db.read(|db_info| db_info.get_alerts(from, to))?)
+    }
+    fn get_last_alert(&self, rule: &str) -> Result<Option<DBInfoAlert>> {
+        Ok(self.db.read(|db_info| db_info.get_last_alert(rule))?)
+    }
+    fn get_quote_symbols(&self) -> Result<Vec<String>> {
+        self.db.read(|db_info| db_info.get_quote_symbols())?
+    }
+    fn get_latest_quote(&self, symbol: &str, date: NaiveDate) -> Result<DBInfoQuote> {
+        self.db
+            .read(|db_info| db_info.get_latest_quote(symbol, date))?
+    }
+    fn get_quote_days(
+        &self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Vec<DBInfoQuote>)>> {
+        self.db
+            .read(|db_info| db_info.get_quote_days(symbol, from, to))?
+    }
+    fn get_symbol(&self, symbol: &str) -> Result<DBInfoSymbol> {
+        self.db.read(|db_info| db_info.get_symbol(symbol))?
+    }
+    fn get_symbols(&self) -> Result<Vec<DBInfoSymbol>> {
+        Ok(self.db.read(|db_info| db_info.get_symbols())?)
+    }
+
+    // *** Persistence ***
+    fn import(&self, db_info: DBInfo) -> Result<()> {
+        Ok(self.db.write(|data| *data = db_info)?)
+    }
+    fn save(&self) -> Result<()> {
+        Ok(self.db.save()?)
+    }
+}
@@ -0,0 +1,291 @@
+//! Sub module to turn stored snapshots into downsampled time series.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::include::{Currency, Duration, NaiveDate, NaiveDateTime, Serialize},
+    DBInfoAccountBalance, DBInfoAccountBalanceDay, DBInfoAccountPosition, DBInfoQuote,
+};
+
+/// Helper functions
+// Groups points that are already sorted by time into buckets of the interval. Buckets line up
+// with local midnight so a 1d interval gives one bucket per day.
+fn bucket<T>(
+    points: impl Iterator<Item = (NaiveDateTime, T)>,
+    interval: SeriesInterval,
+) -> Vec<(NaiveDateTime, Vec<T>)> {
+    let mut result: Vec<(NaiveDateTime, Vec<T>)> = Vec::new();
+    for (datetime, point) in points {
+        let start = interval.bucket_start(datetime);
+        match result.last_mut() {
+            Some((s, b)) if *s == start => b.push(point),
+            _ => result.push((start, vec![point])),
+        }
+    }
+    result
+}
+
+/// The longest bucket we allow, a bucket bigger than a year is no use for a series.
+const MAX_INTERVAL_SECS: i64 = 366 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The size of the buckets to downsample a series into.
+pub struct SeriesInterval(Duration);
+
+impl SeriesInterval {
+    /// Parses intervals of the form 5m, 1h, or 1d, up to a year long.
+    pub fn parse(interval: &str) -> Option<Self> {
+        let (count, unit_secs) = if let Some(count) = interval.strip_suffix('m') {
+            (count, 60)
+        } else if let Some(count) = interval.strip_suffix('h') {
+            (count, 60 * 60)
+        } else {
+            (interval.strip_suffix('d')?, 24 * 60 * 60)
+        };
+        let secs = count
+            .parse::<i64>()
+            .ok()
+            .filter(|c| *c > 0)?
+            .checked_mul(unit_secs)?;
+        // the interval comes straight from a query, so anything silly is turned away here.
+        if secs > MAX_INTERVAL_SECS {
+            return None;
+        }
+        Some(Self(Duration::seconds(secs)))
+    }
+    fn bucket_start(&self, datetime: NaiveDateTime) -> NaiveDateTime {
+        let secs = self.0.num_seconds();
+        let stamp = datetime.timestamp();
+        NaiveDateTime::from_timestamp(stamp - stamp.rem_euclid(secs), 0)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+/// The first, last, lowest, and highest value seen in a bucket. For prices that's the OHLC.
+pub struct SeriesStats {
+    pub first: f64,
+    pub last: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl SeriesStats {
+    pub fn new(values: impl Iterator<Item = f64>) -> Option<Self> {
+        let mut result: Option<Self> = None;
+        for value in values {
+            match result.as_mut() {
+                Some(stats) => {
+                    stats.last = value;
+                    stats.min = stats.min.min(value);
+                    stats.max = stats.max.max(value);
+                }
+                None => {
+                    result = Some(Self {
+                        first: value,
+                        last: value,
+                        min: value,
+                        max: value,
+                    })
+                }
+            }
+        }
+        result
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone, Copy)]
+/// Open, high, low, and close of a price over a bucket.
+pub struct Ohlc {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+}
+
+impl From<SeriesStats> for Ohlc {
+    fn from(stats: SeriesStats) -> Self {
+        Self {
+            open: stats.first,
+            high: stats.max,
+            low: stats.min,
+            close: stats.last,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+/// One bucket of a downsampled balance series.
+pub struct BalanceBucket {
+    pub start: NaiveDateTime,
+    pub samples: usize,
+    pub currency: Currency,
+    pub cash: SeriesStats,
+    pub market_value: SeriesStats,
+    pub total_equity: SeriesStats,
+    pub buying_power: SeriesStats,
+    pub maitenance_excess: SeriesStats,
+}
+
+impl BalanceBucket {
+    fn new(start: NaiveDateTime, balances: Vec<&DBInfoAccountBalance>) -> Self {
+        let stats = |f: fn(&DBInfoAccountBalance) -> f64| {
+            // buckets are never empty so there is always something to unwrap.
+            SeriesStats::new(balances.iter().map(|b| f(b))).unwrap()
+        };
+        Self {
+            start,
+            samples: balances.len(),
+            currency: balances[0].currency.clone(),
+            cash: stats(|b| b.cash),
+            market_value: stats(|b| b.market_value),
+            total_equity: stats(|b| b.total_equity),
+            buying_power: stats(|b| b.buying_power),
+            maitenance_excess: stats(|b| b.maitenance_excess),
+        }
+    }
+}
+
+/// Downsamples the balances taken over the day, the sod balances are left out since they
+/// describe the close before and not the time they were pulled.
+pub fn downsample_balances(
+    days: &[DBInfoAccountBalanceDay],
+    interval: SeriesInterval,
+) -> Vec<BalanceBucket> {
+    let points = days.iter().flat_map(|day| {
+        day.over_day_balances
+            .iter()
+            .map(move |bal| (day.date.and_time(bal.time_retrieved), bal))
+    });
+    bucket(points, interval)
+        .into_iter()
+        .map(|(start, balances)| BalanceBucket::new(start, balances))
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+/// One bucket of a downsampled position series.
+pub struct PositionBucket {
+    pub start: NaiveDateTime,
+    pub samples: usize,
+    pub price: Ohlc,
+    pub market_value: SeriesStats,
+    pub open_pnl: SeriesStats,
+    pub day_pnl: SeriesStats,
+    pub open_quantity: SeriesStats,
+}
+
+impl PositionBucket {
+    fn new(start: NaiveDateTime, positions: Vec<&DBInfoAccountPosition>) -> Self {
+        let stats = |f: fn(&DBInfoAccountPosition) -> f64| {
+            SeriesStats::new(positions.iter().map(|p| f(p))).unwrap()
+        };
+        Self {
+            start,
+            samples: positions.len(),
+            price: stats(|p| p.current_price).into(),
+            market_value: stats(|p| p.current_market_value),
+            open_pnl: stats(|p| p.open_pnl),
+            day_pnl: stats(|p| p.day_pnl),
+            open_quantity: stats(|p| p.open_quantity),
+        }
+    }
+}
+
+/// Downsamples the snapshots of one position, the days have to be sorted earliest first.
+pub fn downsample_positions(
+    days: &[(NaiveDate, Vec<DBInfoAccountPosition>)],
+    interval: SeriesInterval,
+) -> Vec<PositionBucket> {
+    let points = days.iter().flat_map(|(date, positions)| {
+        positions
+            .iter()
+            .map(move |pos| (date.and_time(pos.time_retrieved), pos))
+    });
+    bucket(points, interval)
+        .into_iter()
+        .map(|(start, positions)| PositionBucket::new(start, positions))
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Serialize, Clone)]
+/// One bucket of a downsampled quote series. Volume is the running total of the day, so the
+/// stats show how much traded by the end of the bucket.
+pub struct QuoteBucket {
+    pub start: NaiveDateTime,
+    pub samples: usize,
+    pub price: Ohlc,
+    pub volume: SeriesStats,
+}
+
+impl QuoteBucket {
+    fn new(start: NaiveDateTime, quotes: Vec<&DBInfoQuote>) -> Self {
+        Self {
+            start,
+            samples: quotes.len(),
+            price: SeriesStats::new(quotes.iter().map(|q| q.last_trade_price))
+                .unwrap()
+                .into(),
+            volume: SeriesStats::new(quotes.iter().map(|q| f64::from(q.volume))).unwrap(),
+        }
+    }
+}
+
+/// Downsamples the quotes of one symbol, the days have to be sorted earliest first.
+pub fn downsample_quotes(
+    days: &[(NaiveDate, Vec<DBInfoQuote>)],
+    interval: SeriesInterval,
+) -> Vec<QuoteBucket> {
+    let points = days.iter().flat_map(|(date, quotes)| {
+        quotes
+            .iter()
+            .map(move |quote| (date.and_time(quote.time_retrieved), quote))
+    });
+    bucket(points, interval)
+        .into_iter()
+        .map(|(start, quotes)| QuoteBucket::new(start, quotes))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_intervals() {
+        assert_eq!(
+            SeriesInterval::parse("5m"),
+            Some(SeriesInterval(Duration::minutes(5)))
+        );
+        assert_eq!(
+            SeriesInterval::parse("1h"),
+            Some(SeriesInterval(Duration::hours(1)))
+        );
+        assert_eq!(
+            SeriesInterval::parse("366d"),
+            Some(SeriesInterval(Duration::days(366)))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_intervals() {
+        for interval in [
+            "",
+            "m",
+            "0m",
+            "-5m",
+            "5",
+            "5s",
+            "5é",
+            "é",
+            "1m\u{1F600}",
+            "367d",
+            "9223372036854775807m",
+            "153722867280912931m",
+            "99999999999999999999d",
+        ]
+        .iter()
+        {
+            assert_eq!(SeriesInterval::parse(interval), None, "{}", interval);
+        }
+    }
+}
@@ -0,0 +1,1473 @@
+//! Sub module with the SQLite backed implementation of Storage.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::include::{
+        error, from_str, info, params, to_string, warn, Account, AccountActivity, AccountBalance,
+        AccountExecution, AccountName, AccountNumber, AccountOrder, AccountPosition, Connection,
+        Currency, DateTime, DeserializeOwned, Local, MarketQuote, Mutex, NaiveDate, NaiveTime,
+        OpenFlags, OptionalExtension, PathBuf, Result, Row,
+    },
+    duration_abs, make_dateime_naive, ActivityType, BalanceKind, DBInfo, DBInfoAccountActivity,
+    DBInfoAccountBalance, DBInfoAccountBalanceDay, DBInfoAccountExecution, DBInfoAccountOrder,
+    DBInfoAccountPosition, DBInfoActivitySyncState, DBInfoAlert, DBInfoFxRate, DBInfoQuote,
+    DBInfoSymbol, DBInsertError, DBRetrieveError, PositionSource, Storage,
+};
+
+/// Tables are only created if they aren't there yet, so this is safe to run on every start.
+/// Balances and positions get real columns and an index on (account, date, time) since those
+/// are the big time series. Everything else is small so we keep the Ron of the struct.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS accounts (
+    name TEXT PRIMARY KEY,
+    number TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS account_balances (
+    account TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    date TEXT NOT NULL,
+    time TEXT NOT NULL,
+    start_of_day INTEGER NOT NULL,
+    currency TEXT NOT NULL,
+    cash REAL NOT NULL,
+    market_value REAL NOT NULL,
+    total_equity REAL NOT NULL,
+    buying_power REAL NOT NULL,
+    maitenance_excess REAL NOT NULL,
+    UNIQUE (account, kind, date, start_of_day, time)
+);
+CREATE INDEX IF NOT EXISTS account_balances_by_time
+    ON account_balances (account, kind, date, time);
+CREATE TABLE IF NOT EXISTS account_positions (
+    account TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    date TEXT NOT NULL,
+    time TEXT NOT NULL,
+    open_quantity REAL NOT NULL,
+    closed_quantity REAL NOT NULL,
+    current_market_value REAL NOT NULL,
+    current_price REAL NOT NULL,
+    average_entry_price REAL NOT NULL,
+    closed_pnl REAL NOT NULL,
+    day_pnl REAL NOT NULL,
+    open_pnl REAL NOT NULL,
+    total_cost REAL NOT NULL,
+    source TEXT NOT NULL DEFAULT 'Live',
+    UNIQUE (account, symbol, date, time)
+);
+CREATE INDEX IF NOT EXISTS account_positions_by_time
+    ON account_positions (account, date, time);
+CREATE TABLE IF NOT EXISTS account_orders (
+    account TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    creation_date TEXT NOT NULL,
+    creation_time INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (account, id)
+);
+CREATE TABLE IF NOT EXISTS account_executions (
+    account TEXT NOT NULL,
+    id INTEGER NOT NULL,
+    date TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (account, id)
+);
+CREATE TABLE IF NOT EXISTS account_activities (
+    account TEXT NOT NULL,
+    transaction_date TEXT NOT NULL,
+    activity_type TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS account_activities_by_date
+    ON account_activities (account, transaction_date);
+CREATE TABLE IF NOT EXISTS account_activity_sync (
+    account TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS fx_rates (
+    date TEXT NOT NULL,
+    time TEXT NOT NULL,
+    usd_cad REAL NOT NULL,
+    UNIQUE (date, time)
+);
+CREATE TABLE IF NOT EXISTS alerts (
+    date TEXT NOT NULL,
+    time TEXT NOT NULL,
+    account TEXT NOT NULL,
+    rule TEXT NOT NULL,
+    value REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS alerts_by_rule ON alerts (rule, date, time);
+CREATE TABLE IF NOT EXISTS quotes (
+    symbol TEXT NOT NULL,
+    date TEXT NOT NULL,
+    time TEXT NOT NULL,
+    bid_price REAL,
+    ask_price REAL,
+    last_trade_price REAL NOT NULL,
+    volume INTEGER NOT NULL,
+    open_price REAL NOT NULL,
+    high_price REAL NOT NULL,
+    low_price REAL NOT NULL,
+    delay INTEGER NOT NULL,
+    is_halted INTEGER NOT NULL,
+    UNIQUE (symbol, date, time)
+);
+CREATE TABLE IF NOT EXISTS symbols (
+    symbol TEXT PRIMARY KEY,
+    data TEXT NOT NULL
+);
+";
+
+/// Files from before balances were kept for every currency have no kind column. The old
+/// table is moved out of the way, the schema makes the new one, and the rows are copied over
+/// with the kind matching the currency they were stored in.
+const UPGRADE_BALANCES: &str = "
+DROP INDEX IF EXISTS account_balances_by_time;
+ALTER TABLE account_balances RENAME TO account_balances_legacy;
+";
+/// Run once the schema has made the new table.
+const UPGRADE_BALANCES_COPY: &str = "
+INSERT INTO account_balances (account, kind, date, time, start_of_day, currency, cash,
+    market_value, total_equity, buying_power, maitenance_excess)
+SELECT account, currency, date, time, start_of_day, currency, cash, market_value, total_equity,
+    buying_power, maitenance_excess FROM account_balances_legacy;
+DROP TABLE account_balances_legacy;
+";
+
+/// Files from before positions knew where they came from only have synced ones.
+const UPGRADE_POSITIONS: &str = "
+ALTER TABLE account_positions ADD COLUMN source TEXT NOT NULL DEFAULT 'Live';
+";
+
+/// Column lists so the row readers and the queries can't get out of step.
+const BALANCE_COLUMNS: &str =
+    "currency, cash, market_value, total_equity, buying_power, maitenance_excess, time";
+const POSITION_COLUMNS: &str = "symbol, open_quantity, closed_quantity, current_market_value, \
+    current_price, average_entry_price, closed_pnl, day_pnl, open_pnl, total_cost, time, source";
+const QUOTE_COLUMNS: &str = "symbol, bid_price, ask_price, last_trade_price, volume, open_price, \
+    high_price, low_price, delay, is_halted, time";
+
+/// Helper functions
+fn currency_to_sql(currency: &Currency) -> &'static str {
+    match currency {
+        Currency::CAD => "CAD",
+        Currency::USD => "USD",
+    }
+}
+fn balance_from_row(row: &Row) -> rusqlite::Result<DBInfoAccountBalance> {
+    Ok(DBInfoAccountBalance {
+        currency: match row.get::<_, String>(0)?.as_str() {
+            "USD" => Currency::USD,
+            _ => Currency::CAD,
+        },
+        cash: row.get(1)?,
+        market_value: row.get(2)?,
+        total_equity: row.get(3)?,
+        buying_power: row.get(4)?,
+        maitenance_excess: row.get(5)?,
+        time_retrieved: row.get(6)?,
+    })
+}
+fn position_from_row(row: &Row) -> rusqlite::Result<DBInfoAccountPosition> {
+    Ok(DBInfoAccountPosition {
+        symbol: row.get(0)?,
+        open_quantity: row.get(1)?,
+        closed_quantity: row.get(2)?,
+        current_market_value: row.get(3)?,
+        current_price: row.get(4)?,
+        average_entry_price: row.get(5)?,
+        closed_pnl: row.get(6)?,
+        day_pnl: row.get(7)?,
+        open_pnl: row.get(8)?,
+        total_cost: row.get(9)?,
+        time_retrieved: row.get(10)?,
+        source: PositionSource::parse(&row.get::<_, String>(11)?),
+    })
+}
+fn quote_from_row(row: &Row) -> rusqlite::Result<DBInfoQuote> {
+    Ok(DBInfoQuote {
+        symbol: row.get(0)?,
+        bid_price: row.get(1)?,
+        ask_price: row.get(2)?,
+        last_trade_price: row.get(3)?,
+        volume: row.get(4)?,
+        open_price: row.get(5)?,
+        high_price: row.get(6)?,
+        low_price: row.get(7)?,
+        delay: row.get(8)?,
+        is_halted: row.get(9)?,
+        time_retrieved: row.get(10)?,
+    })
+}
+// the structs we keep as Ron can't be parsed inside a rusqlite closure, so we pull the
+// strings out first and parse them here.
+fn parse_data<T: DeserializeOwned>(
+    rows: impl Iterator<Item = rusqlite::Result<String>>,
+) -> Result<Vec<T>> {
+    let mut result = Vec::new();
+    for data in rows {
+        result.push(from_str(&data?)?);
+    }
+    Ok(result)
+}
+// pick whichever of the snapshots on either side of the time is closer, the later one on a tie.
+fn closest_of<T, F: Fn(&T) -> NaiveTime>(
+    time: NaiveTime,
+    before: Option<T>,
+    after: Option<T>,
+    time_of: F,
+) -> Option<T> {
+    match (before, after) {
+        (Some(b), Some(a)) => {
+            if duration_abs(time - time_of(&a)) <= duration_abs(time - time_of(&b)) {
+                Some(a)
+            } else {
+                Some(b)
+            }
+        }
+        (b, None) => b,
+        (None, a) => a,
+    }
+}
+fn insert_balance_row(
+    conn: &Connection,
+    number: &str,
+    kind: BalanceKind,
+    date: NaiveDate,
+    start_of_day: bool,
+    balance: &DBInfoAccountBalance,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO account_balances (account, kind, date, time, start_of_day, \
+         currency, cash, market_value, total_equity, buying_power, maitenance_excess) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            number,
+            kind.as_str(),
+            date,
+            balance.time_retrieved,
+            start_of_day,
+            currency_to_sql(&balance.currency),
+            balance.cash,
+            balance.market_value,
+            balance.total_equity,
+            balance.buying_power,
+            balance.maitenance_excess,
+        ],
+    )?;
+    Ok(())
+}
+fn insert_position_row(
+    conn: &Connection,
+    number: &str,
+    date: NaiveDate,
+    position: &DBInfoAccountPosition,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO account_positions (account, symbol, date, time, open_quantity, \
+         closed_quantity, current_market_value, current_price, average_entry_price, closed_pnl, \
+         day_pnl, open_pnl, total_cost, source) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+        params![
+            number,
+            position.symbol,
+            date,
+            position.time_retrieved,
+            position.open_quantity,
+            position.closed_quantity,
+            position.current_market_value,
+            position.current_price,
+            position.average_entry_price,
+            position.closed_pnl,
+            position.day_pnl,
+            position.open_pnl,
+            position.total_cost,
+            position.source.as_str(),
+        ],
+    )?;
+    Ok(())
+}
+fn insert_order_row(conn: &Connection, number: &str, order: &DBInfoAccountOrder) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO account_orders (account, id, creation_date, creation_time, data) \
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            number,
+            order.id,
+            order.creation_time.date().naive_local(),
+            order.creation_time.timestamp_millis(),
+            to_string(order)?,
+        ],
+    )?;
+    Ok(())
+}
+fn insert_execution_row(
+    conn: &Connection,
+    number: &str,
+    execution: &DBInfoAccountExecution,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO account_executions (account, id, date, timestamp, data) \
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            number,
+            execution.id,
+            execution.timestamp.date().naive_local(),
+            execution.timestamp.timestamp_millis(),
+            to_string(execution)?,
+        ],
+    )?;
+    Ok(())
+}
+fn insert_activity_row(
+    conn: &Connection,
+    number: &str,
+    activity: &DBInfoAccountActivity,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO account_activities (account, transaction_date, activity_type, data) \
+         VALUES (?1, ?2, ?3, ?4)",
+        params![
+            number,
+            activity.transaction_date,
+            to_string(&activity.activity_type)?,
+            to_string(activity)?,
+        ],
+    )?;
+    Ok(())
+}
+fn insert_fx_rate_row(conn: &Connection, rate: &DBInfoFxRate) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO fx_rates (date, time, usd_cad) VALUES (?1, ?2, ?3)",
+        params![rate.date, rate.time_retrieved, rate.usd_cad],
+    )?;
+    Ok(())
+}
+fn fx_rate_from_row(row: &Row) -> rusqlite::Result<DBInfoFxRate> {
+    Ok(DBInfoFxRate::new(row.get(0)?, row.get(1)?, row.get(2)?))
+}
+fn insert_alert_row(conn: &Connection, alert: &DBInfoAlert) -> Result<()> {
+    conn.execute(
+        "INSERT INTO alerts (date, time, account, rule, value) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            alert.date,
+            alert.time_triggered,
+            alert.account,
+            alert.rule,
+            alert.value
+        ],
+    )?;
+    Ok(())
+}
+fn alert_from_row(row: &Row) -> rusqlite::Result<DBInfoAlert> {
+    Ok(DBInfoAlert::new(
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+    ))
+}
+fn insert_quote_row(conn: &Connection, date: NaiveDate, quote: &DBInfoQuote) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO quotes (symbol, date, time, bid_price, ask_price, \
+         last_trade_price, volume, open_price, high_price, low_price, delay, is_halted) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            quote.symbol,
+            date,
+            quote.time_retrieved,
+            quote.bid_price,
+            quote.ask_price,
+            quote.last_trade_price,
+            quote.volume,
+            quote.open_price,
+            quote.high_price,
+            quote.low_price,
+            quote.delay,
+            quote.is_halted,
+        ],
+    )?;
+    Ok(())
+}
+fn insert_symbol_row(conn: &Connection, info: &DBInfoSymbol) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO symbols (symbol, data) VALUES (?1, ?2)",
+        params![info.symbol, to_string(info)?],
+    )?;
+    Ok(())
+}
+fn account_exists(conn: &Connection, number: &str) -> Result<bool> {
+    Ok(conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM accounts WHERE number = ?1)",
+        params![number],
+        |row| row.get(0),
+    )?)
+}
+// quick check used to tell "nothing synced at all" apart from "nothing in this range".
+fn any_rows(conn: &Connection, table: &str, number: &str) -> Result<bool> {
+    Ok(conn.query_row(
+        &format!("SELECT EXISTS(SELECT 1 FROM {} WHERE account = ?1)", table),
+        params![number],
+        |row| row.get(0),
+    )?)
+}
+// tells "never quoted" apart from "not on the watchlist" like the Rustbreak lookup does.
+fn check_quote_symbol(conn: &Connection, symbol: &str) -> Result<()> {
+    let exists = |sql: &str, p: &[&dyn rusqlite::ToSql]| -> Result<bool> {
+        Ok(conn.query_row(sql, p, |row| row.get(0))?)
+    };
+    if exists(
+        "SELECT EXISTS(SELECT 1 FROM quotes WHERE symbol = ?1)",
+        params![symbol],
+    )? {
+        Ok(())
+    } else if exists("SELECT EXISTS(SELECT 1 FROM quotes)", params![])? {
+        Err(Box::new(DBRetrieveError::RetrieveQuoteNotSyncedError(
+            symbol.to_string(),
+        )))
+    } else {
+        Err(Box::new(DBRetrieveError::RetrieveQuoteAllNotSyncedError))
+    }
+}
+// names win over numbers, same as the Rustbreak lookup.
+fn acct_identifier_to_number(conn: &Connection, acct_ident: &str) -> Result<String> {
+    match conn
+        .query_row(
+            "SELECT number FROM accounts WHERE name = ?1 OR number = ?1 \
+             ORDER BY name = ?1 DESC LIMIT 1",
+            params![acct_ident],
+            |row| row.get(0),
+        )
+        .optional()?
+    {
+        Some(number) => Ok(number),
+        None => Err(Box::new(DBRetrieveError::RetrieveAccountNoAccountError(
+            acct_ident.to_string(),
+        ))),
+    }
+}
+fn any_balance_rows(conn: &Connection, number: &str, kind: BalanceKind) -> Result<bool> {
+    Ok(conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM account_balances WHERE account = ?1 AND kind = ?2)",
+        params![number, kind.as_str()],
+        |row| row.get(0),
+    )?)
+}
+// makes sure there is a trace of balances for the day, so the lookups after always find a row.
+fn check_balances_of_date(
+    conn: &Connection,
+    acct_num: &str,
+    kind: BalanceKind,
+    date: NaiveDate,
+) -> Result<()> {
+    let synced: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM account_balances \
+         WHERE account = ?1 AND kind = ?2 AND date = ?3 AND start_of_day = 1)",
+        params![acct_num, kind.as_str(), date],
+        |row| row.get(0),
+    )?;
+    if synced {
+        Ok(())
+    } else if any_balance_rows(conn, acct_num, kind)? {
+        Err(Box::new(
+            DBRetrieveError::RetrieveAccountBalanceNotSyncedDayError(date),
+        ))
+    } else {
+        Err(Box::new(
+            DBRetrieveError::RetrieveAccountBalanceNotSyncedError,
+        ))
+    }
+}
+fn check_positions_of_date(
+    conn: &Connection,
+    acct_num: &str,
+    symbol: &str,
+    date: NaiveDate,
+) -> Result<()> {
+    let exists = |sql: &str, p: &[&dyn rusqlite::ToSql]| -> Result<bool> {
+        Ok(conn.query_row(sql, p, |row| row.get(0))?)
+    };
+    if exists(
+        "SELECT EXISTS(SELECT 1 FROM account_positions \
+         WHERE account = ?1 AND symbol = ?2 AND date = ?3)",
+        params![acct_num, symbol, date],
+    )? {
+        Ok(())
+    } else if exists(
+        "SELECT EXISTS(SELECT 1 FROM account_positions WHERE account = ?1 AND symbol = ?2)",
+        params![acct_num, symbol],
+    )? {
+        Err(Box::new(
+            DBRetrieveError::RetrieveAccountPositionNotSyncedDayError(symbol.to_string(), date),
+        ))
+    } else if any_rows(conn, "account_positions", acct_num)? {
+        Err(Box::new(
+            DBRetrieveError::RetrieveAccountPositionNotSyncedError(symbol.to_string()),
+        ))
+    } else {
+        Err(Box::new(
+            DBRetrieveError::RetrieveAccountPositionAllNotSyncedError,
+        ))
+    }
+}
+
+#[derive(Debug)]
+/// A SQLite file on disk. Every insert goes straight to the file so there is nothing
+/// to re-write when we save.
+pub struct SqliteStorage {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Opens the SQLite file at the path provided, creating it and the tables if needed.
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let mut conn = Connection::open(path)?;
+        let tx = conn.transaction()?;
+        // a balances table without the kind column is from before we stored every currency.
+        let legacy_balances: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' \
+             AND name = 'account_balances') \
+             AND NOT EXISTS(SELECT 1 FROM pragma_table_info('account_balances') \
+             WHERE name = 'kind')",
+            params![],
+            |row| row.get(0),
+        )?;
+        if legacy_balances {
+            info!("Upgrading SQLite DB to store every currency...");
+            tx.execute_batch(UPGRADE_BALANCES)?;
+        }
+        // same goes for a positions table without the source column.
+        let legacy_positions: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' \
+             AND name = 'account_positions') \
+             AND NOT EXISTS(SELECT 1 FROM pragma_table_info('account_positions') \
+             WHERE name = 'source')",
+            params![],
+            |row| row.get(0),
+        )?;
+        if legacy_positions {
+            info!("Upgrading SQLite DB to track where positions came from...");
+            tx.execute_batch(UPGRADE_POSITIONS)?;
+        }
+        tx.execute_batch(SCHEMA)?;
+        if legacy_balances {
+            tx.execute_batch(UPGRADE_BALANCES_COPY)?;
+        }
+        tx.commit()?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+    /// Opens an existing SQLite file without the tables being created or upgraded, any
+    /// attempt to write through it errors out.
+    pub fn open_read_only(path: PathBuf) -> Result<Self> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+impl Storage for SqliteStorage {
+    // *** Insert Functions ***
+    fn insert_account(&self, name: AccountName, account: Account) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT name, data FROM accounts")?;
+        let mut accounts: Vec<(String, Account)> = Vec::new();
+        for row in stmt.query_map(params![], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)))? {
+            let (n, data) = row?;
+            accounts.push((n, from_str(&data)?));
+        }
+        // same rules as the Rustbreak version, the list of accounts is always tiny.
+        if accounts.iter().any(|(k, v)| *k == name && *v == account) {
+            Ok(())
+        } else if accounts
+            .iter()
+            .any(|(k, v)| *k == name && v.number != account.number)
+        {
+            Err(Box::new(DBInsertError::InsertAccountDuplicateNameError))
+        } else if accounts.iter().any(|(_, v)| *v == account) {
+            Err(Box::new(DBInsertError::InsertAccountDuplicateInfoError))
+        } else {
+            conn.execute(
+                "INSERT OR REPLACE INTO accounts (name, number, data) VALUES (?1, ?2, ?3)",
+                params![name, account.number, to_string(&account)?],
+            )?;
+            Ok(())
+        }
+    }
+    fn insert_account_balance(
+        &self,
+        datetime: DateTime<Local>,
+        number: &AccountNumber,
+        kind: BalanceKind,
+        balance: AccountBalance,
+        sod: AccountBalance,
+    ) -> Result<()> {
+        let (date, time) = make_dateime_naive(datetime);
+        let mut conn = self.conn.lock().unwrap();
+        if !account_exists(&conn, number)? {
+            return Err(Box::new(DBInsertError::InsertAccountBalanceNoAccountError));
+        }
+        let tx = conn.transaction()?;
+        let balance = DBInfoAccountBalance::new(balance, time);
+        let duplicate: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM account_balances \
+             WHERE account = ?1 AND kind = ?2 AND date = ?3 AND start_of_day = 0 AND time = ?4)",
+            params![number, kind.as_str(), date, time],
+            |row| row.get(0),
+        )?;
+        if duplicate {
+            return Err(Box::new(DBInsertError::InsertAccountBalanceDuplicateError));
+        }
+        // the first balance of the day also brings the sod balance along with it.
+        let has_sod: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM account_balances \
+             WHERE account = ?1 AND kind = ?2 AND date = ?3 AND start_of_day = 1)",
+            params![number, kind.as_str(), date],
+            |row| row.get(0),
+        )?;
+        if !has_sod {
+            insert_balance_row(
+                &tx,
+                number,
+                kind,
+                date,
+                true,
+                &DBInfoAccountBalance::new(sod, time),
+            )?;
+        }
+        insert_balance_row(&tx, number, kind, date, false, &balance)?;
+        tx.commit()?;
+        Ok(())
+    }
+    fn insert_account_position(
+        &self,
+        datetime: DateTime<Local>,
+        number: &AccountNumber,
+        position: AccountPosition,
+        source: PositionSource,
+    ) -> Result<()> {
+        let (date, time) = make_dateime_naive(datetime);
+        let conn = self.conn.lock().unwrap();
+        if !account_exists(&conn, number)? {
+            return Err(Box::new(DBInsertError::InsertAccountPositionNoAccountError));
+        }
+        let position = DBInfoAccountPosition::new(position, time, source);
+        let duplicate: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM account_positions \
+             WHERE account = ?1 AND symbol = ?2 AND date = ?3 AND time = ?4)",
+            params![number, position.symbol, date, time],
+            |row| row.get(0),
+        )?;
+        if duplicate {
+            return Err(Box::new(DBInsertError::InsertAccountPositionDuplicateError));
+        }
+        insert_position_row(&conn, number, date, &position)
+    }
+    fn insert_account_order(&self, number: &AccountNumber, order: AccountOrder) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        if !account_exists(&conn, number)? {
+            return Err(Box::new(DBInsertError::InsertAccountOrderNoAccountError));
+        }
+        // orders change state over their life so the row with the same id gets replaced.
+        insert_order_row(&conn, number, &DBInfoAccountOrder::new(order))
+    }
+    fn insert_account_execution(
+        &self,
+        number: &AccountNumber,
+        execution: AccountExecution,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        if !account_exists(&conn, number)? {
+            return Err(Box::new(
+                DBInsertError::InsertAccountExecutionNoAccountError,
+            ));
+        }
+        insert_execution_row(&conn, number, &DBInfoAccountExecution::new(execution))
+    }
+    fn insert_account_activities(
+        &self,
+        number: &AccountNumber,
+        activities: Vec<AccountActivity>,
+    ) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        if !account_exists(&conn, number)? {
+            return Err(Box::new(DBInsertError::InsertAccountActivityNoAccountError));
+        }
+        let activities: Vec<DBInfoAccountActivity> = activities
+            .into_iter()
+            .map(DBInfoAccountActivity::new)
+            .collect();
+        let (from, to) = match (
+            activities.iter().map(|a| a.transaction_date).min(),
+            activities.iter().map(|a| a.transaction_date).max(),
+        ) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return Ok(0),
+        };
+        let tx = conn.transaction()?;
+        // only what we already have in the same date range can be a duplicate. Same
+        // matching as the Rustbreak version so real duplicates are still kept.
+        let existing: Vec<DBInfoAccountActivity> = {
+            let mut stmt = tx.prepare(
+                "SELECT data FROM account_activities \
+                 WHERE account = ?1 AND transaction_date BETWEEN ?2 AND ?3",
+            )?;
+            let rows = stmt.query_map(params![number, from, to], |row| row.get(0))?;
+            parse_data(rows)?
+        };
+        let mut matched = vec![false; existing.len()];
+        let mut inserted = 0;
+        for act in activities {
+            match existing
+                .iter()
+                .enumerate()
+                .position(|(i, a)| !matched[i] && *a == act)
+            {
+                Some(i) => matched[i] = true,
+                None => {
+                    insert_activity_row(&tx, number, &act)?;
+                    inserted += 1;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+    fn set_activity_sync_state(
+        &self,
+        number: &AccountNumber,
+        state: DBInfoActivitySyncState,
+    ) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT OR REPLACE INTO account_activity_sync (account, data) VALUES (?1, ?2)",
+            params![number, to_string(&state)?],
+        )?;
+        Ok(())
+    }
+    fn insert_fx_rate(&self, datetime: DateTime<Local>, usd_cad: f64) -> Result<()> {
+        let (date, time) = make_dateime_naive(datetime);
+        let conn = self.conn.lock().unwrap();
+        let duplicate: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM fx_rates WHERE date = ?1 AND time = ?2)",
+            params![date, time],
+            |row| row.get(0),
+        )?;
+        if duplicate {
+            return Err(Box::new(DBInsertError::InsertFxRateDuplicateError));
+        }
+        insert_fx_rate_row(&conn, &DBInfoFxRate::new(date, time, usd_cad))
+    }
+    fn insert_alert(
+        &self,
+        datetime: DateTime<Local>,
+        number: &AccountNumber,
+        rule: String,
+        value: f64,
+    ) -> Result<()> {
+        let (date, time) = make_dateime_naive(datetime);
+        let conn = self.conn.lock().unwrap();
+        if !account_exists(&conn, number)? {
+            return Err(Box::new(DBInsertError::InsertAlertNoAccountError));
+        }
+        insert_alert_row(
+            &conn,
+            &DBInfoAlert::new(date, time, number.clone(), rule, value),
+        )
+    }
+    fn insert_quote(&self, datetime: DateTime<Local>, quote: MarketQuote) -> Result<()> {
+        let (date, time) = make_dateime_naive(datetime);
+        let conn = self.conn.lock().unwrap();
+        let quote = DBInfoQuote::new(quote, time);
+        let duplicate: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM quotes WHERE symbol = ?1 AND date = ?2 AND time = ?3)",
+            params![quote.symbol, date, time],
+            |row| row.get(0),
+        )?;
+        if duplicate {
+            return Err(Box::new(DBInsertError::InsertQuoteDuplicateError));
+        }
+        insert_quote_row(&conn, date, &quote)
+    }
+    fn insert_symbol(&self, info: DBInfoSymbol) -> Result<()> {
+        insert_symbol_row(&self.conn.lock().unwrap(), &info)
+    }
+
+    // *** Reading functions. ***
+    fn list_account_numbers(&self) -> Result<Vec<AccountNumber>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT number FROM accounts")?;
+        let rows = stmt.query_map(params![], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+    fn get_account_list(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT name FROM accounts")?;
+        let names: Vec<String> = stmt
+            .query_map(params![], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        if names.is_empty() {
+            Err(Box::new(DBRetrieveError::RetrieveAccountsNotSyncedError))
+        } else {
+            Ok(names)
+        }
+    }
+    fn get_account_info(&self, account_identifier: &str) -> Result<Account> {
+        let conn = self.conn.lock().unwrap();
+        let account_number = match acct_identifier_to_number(&conn, account_identifier) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve balance, with error: {}", e);
+                return Err(e);
+            }
+        };
+        let data: String = conn.query_row(
+            "SELECT data FROM accounts WHERE number = ?1",
+            params![account_number],
+            |row| row.get(0),
+        )?;
+        Ok(from_str(&data)?)
+    }
+    fn get_start_of_day_balance(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        date: NaiveDate,
+    ) -> Result<DBInfoAccountBalance> {
+        let conn = self.conn.lock().unwrap();
+        let account_number = match acct_identifier_to_number(&conn, account_identifier) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve balance, with error: {}", e);
+                return Err(e);
+            }
+        };
+        if let Err(e) = check_balances_of_date(&conn, &account_number, kind, date) {
+            warn!("Could not retrieve balance, with error: {}", e);
+            return Err(e);
+        }
+        Ok(conn.query_row(
+            &format!(
+                "SELECT {} FROM account_balances \
+                 WHERE account = ?1 AND kind = ?2 AND date = ?3 AND start_of_day = 1",
+                BALANCE_COLUMNS
+            ),
+            params![account_number, kind.as_str(), date],
+            balance_from_row,
+        )?)
+    }
+    fn get_latest_balance(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        date: NaiveDate,
+    ) -> Result<DBInfoAccountBalance> {
+        let conn = self.conn.lock().unwrap();
+        let account_number = match acct_identifier_to_number(&conn, account_identifier) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve balance, with error: {}", e);
+                return Err(e);
+            }
+        };
+        if let Err(e) = check_balances_of_date(&conn, &account_number, kind, date) {
+            warn!("Could not retrieve balance, with error: {}", e);
+            return Err(e);
+        }
+        // the sod balance only comes back if there is nothing else in the day.
+        Ok(conn.query_row(
+            &format!(
+                "SELECT {} FROM account_balances WHERE account = ?1 AND kind = ?2 \
+                 AND date = ?3 ORDER BY start_of_day ASC, time DESC LIMIT 1",
+                BALANCE_COLUMNS
+            ),
+            params![account_number, kind.as_str(), date],
+            balance_from_row,
+        )?)
+    }
+    fn get_closest_balance(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        date: NaiveDate,
+        time: NaiveTime,
+    ) -> Result<DBInfoAccountBalance> {
+        let conn = self.conn.lock().unwrap();
+        let account_number = match acct_identifier_to_number(&conn, account_identifier) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve balance, with error: {}", e);
+                return Err(e);
+            }
+        };
+        if let Err(e) = check_balances_of_date(&conn, &account_number, kind, date) {
+            warn!("Could not retrieve balance, with error: {}", e);
+            return Err(e);
+        }
+        // two index lookups, the last balance at or before the time and the first one after.
+        let nearest = |cmp: &str, order: &str| -> Result<Option<DBInfoAccountBalance>> {
+            Ok(conn
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM account_balances WHERE account = ?1 AND kind = ?2 \
+                         AND date = ?3 AND start_of_day = 0 AND time {} ?4 \
+                         ORDER BY time {} LIMIT 1",
+                        BALANCE_COLUMNS, cmp, order
+                    ),
+                    params![account_number, kind.as_str(), date, time],
+                    balance_from_row,
+                )
+                .optional()?)
+        };
+        match closest_of(time, nearest("<=", "DESC")?, nearest(">", "ASC")?, |b| {
+            b.time_retrieved
+        }) {
+            Some(bal) => Ok(bal),
+            None => Ok(conn.query_row(
+                &format!(
+                    "SELECT {} FROM account_balances \
+                     WHERE account = ?1 AND kind = ?2 AND date = ?3 AND start_of_day = 1",
+                    BALANCE_COLUMNS
+                ),
+                params![account_number, kind.as_str(), date],
+                balance_from_row,
+            )?),
+        }
+    }
+    fn get_balance_days(
+        &self,
+        account_identifier: &str,
+        kind: BalanceKind,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountBalanceDay>> {
+        let conn = self.conn.lock().unwrap();
+        let account_number = match acct_identifier_to_number(&conn, account_identifier) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve balances, with error: {}", e);
+                return Err(e);
+            }
+        };
+        if !any_balance_rows(&conn, &account_number, kind)? {
+            let e = Box::new(DBRetrieveError::RetrieveAccountBalanceNotSyncedError);
+            warn!("Could not retrieve balances, with error: {}", e);
+            return Err(e);
+        }
+        // the sod balance sorts first in each day so it always starts the new day off.
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, date, start_of_day FROM account_balances \
+             WHERE account = ?1 AND kind = ?2 AND date BETWEEN ?3 AND ?4 \
+             ORDER BY date, start_of_day DESC, time",
+            BALANCE_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![account_number, kind.as_str(), from, to], |row| {
+            Ok((balance_from_row(row)?, row.get(7)?, row.get(8)?))
+        })?;
+        let mut days: Vec<DBInfoAccountBalanceDay> = Vec::new();
+        for row in rows {
+            let (balance, date, start_of_day): (DBInfoAccountBalance, NaiveDate, bool) = row?;
+            if start_of_day {
+                days.push(DBInfoAccountBalanceDay::new(date, balance));
+            } else if let Some(day) = days.last_mut() {
+                day.over_day_balances.push(balance);
+            }
+        }
+        Ok(days)
+    }
+    fn get_position_symbols(&self, acct_ident: &str) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let account_number = match acct_identifier_to_number(&conn, acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve position, with error: {}", e);
+                return Err(e);
+            }
+        };
+        let mut stmt =
+            conn.prepare("SELECT DISTINCT symbol FROM account_positions WHERE account = ?1")?;
+        let symbols: Vec<String> = stmt
+            .query_map(params![account_number], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        if symbols.is_empty() {
+            let e = Box::new(DBRetrieveError::RetrieveAccountPositionAllNotSyncedError);
+            warn!("Could not retrieve position, with error: {}", e);
+            Err(e)
+        } else {
+            Ok(symbols)
+        }
+    }
+    fn get_latest_position(
+        &self,
+        acct_ident: &str,
+        position_symbol: &str,
+        date: NaiveDate,
+    ) -> Result<DBInfoAccountPosition> {
+        let conn = self.conn.lock().unwrap();
+        let acc_num = match acct_identifier_to_number(&conn, acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve position, with error: {}", e);
+                return Err(e);
+            }
+        };
+        if let Err(e) = check_positions_of_date(&conn, &acc_num, position_symbol, date) {
+            warn!("Could not retrieve position, with error: {}", e);
+            return Err(e);
+        }
+        Ok(conn.query_row(
+            &format!(
+                "SELECT {} FROM account_positions WHERE account = ?1 AND symbol = ?2 \
+                 AND date = ?3 ORDER BY time DESC LIMIT 1",
+                POSITION_COLUMNS
+            ),
+            params![acc_num, position_symbol, date],
+            position_from_row,
+        )?)
+    }
+    fn get_closest_position(
+        &self,
+        acct_ident: &str,
+        position_symbol: &str,
+        date: NaiveDate,
+        time: NaiveTime,
+    ) -> Result<DBInfoAccountPosition> {
+        let conn = self.conn.lock().unwrap();
+        let acc_num = match acct_identifier_to_number(&conn, acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve position, with error: {}", e);
+                return Err(e);
+            }
+        };
+        if let Err(e) = check_positions_of_date(&conn, &acc_num, position_symbol, date) {
+            warn!("Could not retrieve position, with error: {}", e);
+            return Err(e);
+        }
+        let nearest = |cmp: &str, order: &str| -> Result<Option<DBInfoAccountPosition>> {
+            Ok(conn
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM account_positions WHERE account = ?1 AND symbol = ?2 \
+                         AND date = ?3 AND time {} ?4 ORDER BY time {} LIMIT 1",
+                        POSITION_COLUMNS, cmp, order
+                    ),
+                    params![acc_num, position_symbol, date, time],
+                    position_from_row,
+                )
+                .optional()?)
+        };
+        // the day is known to have at least one position so one of the two is always there.
+        Ok(
+            closest_of(time, nearest("<=", "DESC")?, nearest(">", "ASC")?, |p| {
+                p.time_retrieved
+            })
+            .unwrap(),
+        )
+    }
+    fn get_position_days(
+        &self,
+        acct_ident: &str,
+        position_symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Vec<DBInfoAccountPosition>)>> {
+        let conn = self.conn.lock().unwrap();
+        let acc_num = match acct_identifier_to_number(&conn, acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve positions, with error: {}", e);
+                return Err(e);
+            }
+        };
+        let symbol_synced: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM account_positions WHERE account = ?1 AND symbol = ?2)",
+            params![acc_num, position_symbol],
+            |row| row.get(0),
+        )?;
+        if !symbol_synced {
+            let e: Box<DBRetrieveError> = if any_rows(&conn, "account_positions", &acc_num)? {
+                Box::new(DBRetrieveError::RetrieveAccountPositionNotSyncedError(
+                    position_symbol.to_string(),
+                ))
+            } else {
+                Box::new(DBRetrieveError::RetrieveAccountPositionAllNotSyncedError)
+            };
+            warn!("Could not retrieve positions, with error: {}", e);
+            return Err(e);
+        }
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, date FROM account_positions \
+             WHERE account = ?1 AND symbol = ?2 AND date BETWEEN ?3 AND ?4 ORDER BY date, time",
+            POSITION_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![acc_num, position_symbol, from, to], |row| {
+            Ok((row.get(12)?, position_from_row(row)?))
+        })?;
+        let mut days: Vec<(NaiveDate, Vec<DBInfoAccountPosition>)> = Vec::new();
+        for row in rows {
+            let (date, position): (NaiveDate, DBInfoAccountPosition) = row?;
+            match days.last_mut() {
+                Some((d, positions)) if *d == date => positions.push(position),
+                _ => days.push((date, vec![position])),
+            }
+        }
+        Ok(days)
+    }
+    fn get_orders(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountOrder>> {
+        let conn = self.conn.lock().unwrap();
+        let acc_num = match acct_identifier_to_number(&conn, acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve orders, with error: {}", e);
+                return Err(e);
+            }
+        };
+        if !any_rows(&conn, "account_orders", &acc_num)? {
+            let e = Box::new(DBRetrieveError::RetrieveAccountOrderAllNotSyncedError);
+            warn!("Could not retrieve orders, with error: {}", e);
+            return Err(e);
+        }
+        let mut stmt = conn.prepare(
+            "SELECT data FROM account_orders \
+             WHERE account = ?1 AND creation_date BETWEEN ?2 AND ?3 ORDER BY creation_time",
+        )?;
+        let rows = stmt.query_map(params![acc_num, from, to], |row| row.get(0))?;
+        parse_data(rows)
+    }
+    fn get_order(&self, acct_ident: &str, order_id: u32) -> Result<DBInfoAccountOrder> {
+        let conn = self.conn.lock().unwrap();
+        let acc_num = match acct_identifier_to_number(&conn, acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve order, with error: {}", e);
+                return Err(e);
+            }
+        };
+        match conn
+            .query_row(
+                "SELECT data FROM account_orders WHERE account = ?1 AND id = ?2",
+                params![acc_num, order_id],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+        {
+            Some(data) => Ok(from_str(&data)?),
+            None => {
+                let e = Box::new(DBRetrieveError::RetrieveAccountOrderNotSyncedError(
+                    order_id,
+                ));
+                warn!("Could not retrieve order, with error: {}", e);
+                Err(e)
+            }
+        }
+    }
+    fn get_executions(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DBInfoAccountExecution>> {
+        let conn = self.conn.lock().unwrap();
+        let acc_num = match acct_identifier_to_number(&conn, acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve executions, with error: {}", e);
+                return Err(e);
+            }
+        };
+        if !any_rows(&conn, "account_executions", &acc_num)? {
+            let e = Box::new(DBRetrieveError::RetrieveAccountExecutionAllNotSyncedError);
+            warn!("Could not retrieve executions, with error: {}", e);
+            return Err(e);
+        }
+        let mut stmt = conn.prepare(
+            "SELECT data FROM account_executions \
+             WHERE account = ?1 AND date BETWEEN ?2 AND ?3 ORDER BY timestamp",
+        )?;
+        let rows = stmt.query_map(params![acc_num, from, to], |row| row.get(0))?;
+        parse_data(rows)
+    }
+    fn get_activity_sync_state(&self, acct_num: &str) -> Result<Option<DBInfoActivitySyncState>> {
+        let data: Option<String> = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT data FROM account_activity_sync WHERE account = ?1",
+                params![acct_num],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match data {
+            Some(data) => Ok(Some(from_str(&data)?)),
+            None => Ok(None),
+        }
+    }
+    fn get_activities(
+        &self,
+        acct_ident: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+        activity_type: Option<ActivityType>,
+    ) -> Result<Vec<DBInfoAccountActivity>> {
+        let conn = self.conn.lock().unwrap();
+        let acc_num = match acct_identifier_to_number(&conn, acct_ident) {
+            Ok(an) => an,
+            Err(e) => {
+                error!("Could not retrieve activities, with error: {}", e);
+                return Err(e);
+            }
+        };
+        if !any_rows(&conn, "account_activities", &acc_num)? {
+            let e = Box::new(DBRetrieveError::RetrieveAccountActivityAllNotSyncedError);
+            warn!("Could not retrieve activities, with error: {}", e);
+            return Err(e);
+        }
+        let activity_type = match activity_type {
+            Some(at) => Some(to_string(&at)?),
+            None => None,
+        };
+        let mut stmt = conn.prepare(
+            "SELECT data FROM account_activities \
+             WHERE account = ?1 AND transaction_date BETWEEN ?2 AND ?3 \
+             AND (?4 IS NULL OR activity_type = ?4) ORDER BY transaction_date, rowid",
+        )?;
+        let rows = stmt.query_map(params![acc_num, from, to, activity_type], |row| row.get(0))?;
+        parse_data(rows)
+    }
+    fn get_fx_rates(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<DBInfoFxRate>> {
+        let conn = self.conn.lock().unwrap();
+        let synced: bool =
+            conn.query_row("SELECT EXISTS(SELECT 1 FROM fx_rates)", params![], |row| {
+                row.get(0)
+            })?;
+        if !synced {
+            let e = Box::new(DBRetrieveError::RetrieveFxRateNotSyncedError);
+            warn!("Could not retrieve exchange rates, with error: {}", e);
+            return Err(e);
+        }
+        // the union keeps the last rate before the range and the first one after it.
+        let mut stmt = conn.prepare(
+            "SELECT date, time, usd_cad FROM ( \
+                 SELECT * FROM (SELECT date, time, usd_cad FROM fx_rates WHERE date < ?1 \
+                 ORDER BY date DESC, time DESC LIMIT 1) \
+                 UNION ALL SELECT date, time, usd_cad FROM fx_rates \
+                 WHERE date BETWEEN ?1 AND ?2 \
+                 UNION ALL SELECT * FROM (SELECT date, time, usd_cad FROM fx_rates \
+                 WHERE date > ?2 ORDER BY date, time LIMIT 1) \
+             ) ORDER BY date, time",
+        )?;
+        let rows = stmt.query_map(params![from, to], fx_rate_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+    fn get_alerts(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<DBInfoAlert>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT date, time, account, rule, value FROM alerts \
+             WHERE date BETWEEN ?1 AND ?2 ORDER BY date, time",
+        )?;
+        let rows = stmt.query_map(params![from, to], alert_from_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+    fn get_last_alert(&self, rule: &str) -> Result<Option<DBInfoAlert>> {
+        Ok(self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT date, time, account, rule, value FROM alerts \
+                 WHERE rule = ?1 ORDER BY date DESC, time DESC LIMIT 1",
+                params![rule],
+                alert_from_row,
+            )
+            .optional()?)
+    }
+    fn get_quote_symbols(&self) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT DISTINCT symbol FROM quotes")?;
+        let symbols: Vec<String> = stmt
+            .query_map(params![], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        if symbols.is_empty() {
+            let e = Box::new(DBRetrieveError::RetrieveQuoteAllNotSyncedError);
+            warn!("Could not retrieve quotes, with error: {}", e);
+            Err(e)
+        } else {
+            Ok(symbols)
+        }
+    }
+    fn get_latest_quote(&self, symbol: &str, date: NaiveDate) -> Result<DBInfoQuote> {
+        let conn = self.conn.lock().unwrap();
+        if let Err(e) = check_quote_symbol(&conn, symbol) {
+            warn!("Could not retrieve quote, with error: {}", e);
+            return Err(e);
+        }
+        match conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM quotes WHERE symbol = ?1 AND date = ?2 \
+                     ORDER BY time DESC LIMIT 1",
+                    QUOTE_COLUMNS
+                ),
+                params![symbol, date],
+                quote_from_row,
+            )
+            .optional()?
+        {
+            Some(quote) => Ok(quote),
+            None => {
+                let e = Box::new(DBRetrieveError::RetrieveQuoteNotSyncedDayError(
+                    symbol.to_string(),
+                    date,
+                ));
+                warn!("Could not retrieve quote, with error: {}", e);
+                Err(e)
+            }
+        }
+    }
+    fn get_quote_days(
+        &self,
+        symbol: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, Vec<DBInfoQuote>)>> {
+        let conn = self.conn.lock().unwrap();
+        if let Err(e) = check_quote_symbol(&conn, symbol) {
+            warn!("Could not retrieve quotes, with error: {}", e);
+            return Err(e);
+        }
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, date FROM quotes \
+             WHERE symbol = ?1 AND date BETWEEN ?2 AND ?3 ORDER BY date, time",
+            QUOTE_COLUMNS
+        ))?;
+        let rows = stmt.query_map(params![symbol, from, to], |row| {
+            Ok((row.get(11)?, quote_from_row(row)?))
+        })?;
+        let mut days: Vec<(NaiveDate, Vec<DBInfoQuote>)> = Vec::new();
+        for row in rows {
+            let (date, quote): (NaiveDate, DBInfoQuote) = row?;
+            match days.last_mut() {
+                Some((d, quotes)) if *d == date => quotes.push(quote),
+                _ => days.push((date, vec![quote])),
+            }
+        }
+        Ok(days)
+    }
+    fn get_symbol(&self, symbol: &str) -> Result<DBInfoSymbol> {
+        let data: Option<String> = self
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT data FROM symbols WHERE symbol = ?1",
+                params![symbol],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match data {
+            Some(d) => Ok(from_str(&d)?),
+            None => Err(Box::new(DBRetrieveError::RetrieveSymbolNotSyncedError(
+                symbol.to_string(),
+            ))),
+        }
+    }
+    fn get_symbols(&self) -> Result<Vec<DBInfoSymbol>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT data FROM symbols")?;
+        let rows = stmt.query_map(params![], |row| row.get(0))?;
+        parse_data(rows)
+    }
+
+    // *** Persistence ***
+    fn import(&self, db_info: DBInfo) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for (name, account) in db_info.accounts.iter() {
+            tx.execute(
+                "INSERT OR REPLACE INTO accounts (name, number, data) VALUES (?1, ?2, ?3)",
+                params![name, account.number, to_string(account)?],
+            )?;
+        }
+        for (number, kinds) in db_info.account_balances.iter() {
+            for (kind, days) in kinds.iter() {
+                for day in days.iter() {
+                    insert_balance_row(&tx, number, *kind, day.date, true, &day.start_of_day_bal)?;
+                    for bal in day.over_day_balances.iter() {
+                        insert_balance_row(&tx, number, *kind, day.date, false, bal)?;
+                    }
+                }
+            }
+        }
+        for (number, symbols) in db_info.account_positions.iter() {
+            for (date, positions) in symbols.values().flat_map(|days| days.iter()) {
+                for pos in positions.iter() {
+                    insert_position_row(&tx, number, *date, pos)?;
+                }
+            }
+        }
+        for (number, orders) in db_info.account_orders.iter() {
+            for order in orders.iter() {
+                insert_order_row(&tx, number, order)?;
+            }
+        }
+        for (number, execs) in db_info.account_executions.iter() {
+            for exec in execs.iter() {
+                insert_execution_row(&tx, number, exec)?;
+            }
+        }
+        // activities have no key of their own, so we swap out the whole list for the account
+        // and running the import twice doesn't double them up.
+        for (number, acts) in db_info.account_activities.iter() {
+            tx.execute(
+                "DELETE FROM account_activities WHERE account = ?1",
+                params![number],
+            )?;
+            for act in acts.iter() {
+                insert_activity_row(&tx, number, act)?;
+            }
+        }
+        for (number, state) in db_info.account_activity_sync.iter() {
+            tx.execute(
+                "INSERT OR REPLACE INTO account_activity_sync (account, data) VALUES (?1, ?2)",
+                params![number, to_string(state)?],
+            )?;
+        }
+        for rate in db_info.fx_rates.iter() {
+            insert_fx_rate_row(&tx, rate)?;
+        }
+        // alerts have no key of their own either, so the whole history is swapped out.
+        if !db_info.alerts.is_empty() {
+            tx.execute("DELETE FROM alerts", params![])?;
+        }
+        for alert in db_info.alerts.iter() {
+            insert_alert_row(&tx, alert)?;
+        }
+        for (date, quotes) in db_info.quotes.values().flat_map(|days| days.iter()) {
+            for quote in quotes.iter() {
+                insert_quote_row(&tx, *date, quote)?;
+            }
+        }
+        for info in db_info.symbols.values() {
+            insert_symbol_row(&tx, info)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+    fn save(&self) -> Result<()> {
+        // every insert is already committed to the file.
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+//! Sub module to contain what we know about each symbol besides its ticker.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{Currency, Deserialize, HashMap, NaiveDate, PositionSymbol, Serialize};
+
+/// pub type def for the saved symbol info, there is only ever one per symbol.
+pub type DBInfoSymbolCollection = HashMap<PositionSymbol, DBInfoSymbol>;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+/// The metadata of a symbol from Questrade's symbol lookup.
+pub struct DBInfoSymbol {
+    pub symbol: PositionSymbol,
+    pub symbol_id: u32,
+    pub description: String,
+    /// Where it is listed, like TSX or NASDAQ.
+    pub exchange: String,
+    /// Stock, Option, Bond, MutualFund, and so on. ETFs are listed as stocks.
+    pub security_type: String,
+    pub currency: Currency,
+    /// Not every symbol has an industry, ETFs usually don't.
+    pub sector: Option<String>,
+    pub industry: Option<String>,
+    /// The day the info was last pulled.
+    pub updated: NaiveDate,
+}
@@ -0,0 +1,33 @@
+//! Module containing the errors that can come up while looking up symbols.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{error, fmt};
+
+#[derive(Debug)]
+// Enum representing errors that are possible while pulling symbol info from Questrade.
+pub enum SymbolError {
+    NotAuthenticatedError,
+    SymbolNotFoundError(String),
+    LookupStatusError(String, u16),
+}
+
+impl error::Error for SymbolError {}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAuthenticatedError => {
+                write!(f, "No Questrade authentication to look up symbols with.")
+            }
+            Self::SymbolNotFoundError(symbol) => {
+                write!(f, "Could not find a Questrade symbol id for {}.", symbol)
+            }
+            Self::LookupStatusError(symbol, status) => write!(
+                f,
+                "Questrade answered the symbol lookup for {} with status {}.",
+                symbol, status
+            ),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Sub module with the raw request for Questrade's symbol endpoint, questrade_rs only wraps search.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::{
+        include::{
+            AuthenticationInfo, BoxFuture, Client, Currency, Deserialize, FutureExt, Local, Result,
+        },
+        storage::DBInfoSymbol,
+    },
+    api_url, search_symbol_id, SymbolError, SymbolSource,
+};
+
+/// Helper functions
+// questrade sends an empty string for a symbol with no industry.
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+#[derive(Deserialize)]
+struct SymbolResponse {
+    symbols: Vec<SymbolDetails>,
+}
+
+#[derive(Deserialize)]
+struct SymbolDetails {
+    #[serde(rename = "symbolId")]
+    symbol_id: u32,
+    #[serde(default)]
+    description: String,
+    #[serde(rename = "listingExchange", default)]
+    listing_exchange: String,
+    #[serde(rename = "securityType", default)]
+    security_type: String,
+    currency: Currency,
+    #[serde(rename = "industrySector", default)]
+    industry_sector: String,
+    #[serde(rename = "industryGroup", default)]
+    industry_group: String,
+}
+
+/// Looks up symbols using the access token of the monitor.
+pub struct QuestradeSymbols {
+    client: Client,
+    auth: AuthenticationInfo,
+}
+
+impl QuestradeSymbols {
+    pub fn new(auth: AuthenticationInfo) -> Self {
+        Self {
+            client: Client::new(),
+            auth,
+        }
+    }
+}
+
+impl SymbolSource for QuestradeSymbols {
+    fn symbol_info<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<DBInfoSymbol>> {
+        async move {
+            let id = match search_symbol_id(&self.client, &self.auth, symbol).await? {
+                Some(id) => id,
+                None => return Err(SymbolError::SymbolNotFoundError(symbol.to_string()).into()),
+            };
+            let response = self
+                .client
+                .get(&api_url(&self.auth, &format!("symbols/{}", id)))
+                .bearer_auth(&self.auth.access_token)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(Box::new(SymbolError::LookupStatusError(
+                    symbol.to_string(),
+                    response.status().as_u16(),
+                )) as Box<dyn std::error::Error>);
+            }
+            let details = match response.json::<SymbolResponse>().await?.symbols.pop() {
+                Some(d) => d,
+                None => return Err(SymbolError::SymbolNotFoundError(symbol.to_string()).into()),
+            };
+            Ok(DBInfoSymbol {
+                // keep the name we were asked about so it lines up with the positions.
+                symbol: symbol.to_string(),
+                symbol_id: details.symbol_id,
+                description: details.description,
+                exchange: details.listing_exchange,
+                security_type: details.security_type,
+                currency: details.currency,
+                sector: non_empty(details.industry_sector),
+                industry: non_empty(details.industry_group),
+                updated: Local::today().naive_local(),
+            })
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,91 @@
+//! Module to keep the metadata of every symbol we hold or watch.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    include::{
+        warn, BoxFuture, Deserialize, Duration, NaiveDate, PositionSymbol, Result, Serialize,
+    },
+    storage::{DBInfoSymbol, Storage},
+};
+
+/// Sub modules
+mod errors;
+mod lookup;
+mod search;
+
+/// Re-export sub-modules so we can read from them in other modules.
+pub use errors::*;
+pub use lookup::*;
+pub use search::*;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// The symbols section of the config file.
+pub struct SymbolsConfig {
+    pub enabled: bool,
+    /// How many days the info of a symbol is kept before it is looked up again.
+    pub refresh_days: i64,
+}
+
+impl Default for SymbolsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            refresh_days: 7,
+        }
+    }
+}
+
+/// Anything that can tell us about a symbol.
+pub trait SymbolSource: Send + Sync {
+    fn symbol_info<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, Result<DBInfoSymbol>>;
+}
+
+/// The symbols held on any account or quoted for the watchlist today.
+pub fn tracked_symbols(db: &dyn Storage, today: NaiveDate) -> Result<Vec<PositionSymbol>> {
+    let mut symbols: Vec<PositionSymbol> = Vec::new();
+    for account in db.list_account_numbers()? {
+        // an account with no positions at all just has nothing to add.
+        for symbol in db.get_position_symbols(&account).unwrap_or_default() {
+            if db.get_latest_position(&account, &symbol, today).is_ok() {
+                symbols.push(symbol);
+            }
+        }
+    }
+    for symbol in db.get_quote_symbols().unwrap_or_default() {
+        if db.get_latest_quote(&symbol, today).is_ok() {
+            symbols.push(symbol);
+        }
+    }
+    symbols.sort();
+    symbols.dedup();
+    Ok(symbols)
+}
+
+/// Looks up every symbol we haven't seen before, or haven't looked up in refresh_days. A symbol
+/// that can't be looked up is skipped with a warning. Returns how many were stored.
+pub async fn refresh_symbols(
+    db: &dyn Storage,
+    source: &dyn SymbolSource,
+    config: &SymbolsConfig,
+    symbols: &[PositionSymbol],
+    today: NaiveDate,
+) -> Result<usize> {
+    let mut stored = 0;
+    for symbol in symbols.iter() {
+        if let Ok(info) = db.get_symbol(symbol) {
+            if info.updated + Duration::days(config.refresh_days) > today {
+                continue;
+            }
+        }
+        match source.symbol_info(symbol).await {
+            Ok(info) => {
+                db.insert_symbol(info)?;
+                stored += 1;
+            }
+            Err(e) => warn!("Could not look up symbol {}, with error: {}", symbol, e),
+        }
+    }
+    Ok(stored)
+}
@@ -0,0 +1,143 @@
+//! Sub module with the symbol search shared by the raw requests. questrade_rs only has it on a
+//! Questrade, which can't be held across an await in a future sent between threads.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{AuthenticationInfo, Client, Deserialize, Result};
+
+/// Version of the api the raw requests go to, the same one questrade_rs uses.
+const API_VERSION: &str = "v1";
+
+#[derive(Deserialize)]
+struct SymbolSearchResponse {
+    symbols: Vec<SymbolSearchResult>,
+}
+
+#[derive(Deserialize)]
+// only what we need, so an exchange questrade_rs doesn't know about can't fail the search.
+struct SymbolSearchResult {
+    symbol: String,
+    #[serde(rename = "symbolId")]
+    symbol_id: u32,
+}
+
+/// The url of a raw request to the api server handed to us with the access token.
+pub fn api_url(auth: &AuthenticationInfo, suffix: &str) -> String {
+    format!(
+        "{}/{}/{}",
+        auth.api_server.trim_end_matches('/'),
+        API_VERSION,
+        suffix
+    )
+}
+
+/// Positions and the watchlist don't keep the symbol id, so we look it up by name. The search
+/// matches on prefix, so only the exact symbol counts.
+pub async fn search_symbol_id(
+    client: &Client,
+    auth: &AuthenticationInfo,
+    symbol: &str,
+) -> Result<Option<u32>> {
+    Ok(client
+        .get(&api_url(auth, "symbols/search"))
+        .bearer_auth(&auth.access_token)
+        .query(&[("prefix", symbol)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<SymbolSearchResponse>()
+        .await?
+        .symbols
+        .into_iter()
+        .find(|s| s.symbol.eq_ignore_ascii_case(symbol))
+        .map(|s| s.symbol_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::super::include::tokio, *};
+    use std::time::Instant;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    // a pretend api server that answers one request with the status and body given, and hands
+    // back the request line it got.
+    async fn fake_server(
+        status: &'static str,
+        body: &'static str,
+    ) -> (AuthenticationInfo, tokio::task::JoinHandle<String>) {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let auth = AuthenticationInfo {
+            refresh_token: String::new(),
+            access_token: "token".to_string(),
+            expires_at: Instant::now(),
+            api_server: format!("http://{}/", listener.local_addr().unwrap()),
+            is_demo: false,
+        };
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut conn = BufReader::new(stream);
+            let mut request = String::new();
+            conn.read_line(&mut request).await.unwrap();
+            loop {
+                let mut line = String::new();
+                conn.read_line(&mut line).await.unwrap();
+                if line.trim_end().is_empty() {
+                    break;
+                }
+            }
+            conn.get_mut()
+                .write_all(
+                    format!(
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                         Connection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    )
+                    .as_bytes(),
+                )
+                .await
+                .unwrap();
+            request
+        });
+        (auth, handle)
+    }
+
+    #[tokio::test]
+    async fn finds_the_exact_symbol() {
+        let (auth, server) = fake_server(
+            "200 OK",
+            r#"{"symbols": [{"symbol": "XEQT.TO.U", "symbolId": 1, "listingExchange": "???"},
+                            {"symbol": "XEQT.TO", "symbolId": 2}]}"#,
+        )
+        .await;
+        let id = search_symbol_id(&Client::new(), &auth, "xeqt.to")
+            .await
+            .unwrap();
+        assert_eq!(id, Some(2));
+        assert!(server
+            .await
+            .unwrap()
+            .starts_with("GET /v1/symbols/search?prefix=xeqt.to "));
+    }
+
+    #[tokio::test]
+    async fn missing_symbol_and_errors() {
+        let (auth, server) = fake_server("200 OK", r#"{"symbols": []}"#).await;
+        assert_eq!(
+            search_symbol_id(&Client::new(), &auth, "AAA")
+                .await
+                .unwrap(),
+            None
+        );
+        server.await.unwrap();
+        let (auth, server) = fake_server("401 Unauthorized", "{}").await;
+        assert!(search_symbol_id(&Client::new(), &auth, "AAA")
+            .await
+            .is_err());
+        server.await.unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+//! Module containing the errors that can come up while working out taxes.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::super::include::{error, fmt};
+
+#[derive(Debug)]
+// Enum representing errors that are possible while building the ACB ledger.
+pub enum TaxError {
+    NoTaxableAccountsError,
+    InvalidYearError(String),
+}
+
+impl error::Error for TaxError {}
+
+impl fmt::Display for TaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoTaxableAccountsError => write!(
+                f,
+                "Could not build the ACB ledger! No cash or margin accounts are synced."
+            ),
+            Self::InvalidYearError(year) => {
+                write!(f, "Could not parse tax year: {}. Expected YYYY.", year)
+            }
+        }
+    }
+}
@@ -0,0 +1,378 @@
+//! Sub module to pool the cost of each symbol and work out the capital gains of every sale.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    super::include::{
+        AccountNumber, Currency, Datelike, Duration, HashMap, NaiveDate, PositionSymbol, Serialize,
+    },
+    TaxTrade,
+};
+
+/// A loss is superficial if the same symbol is bought within this many days either side of
+/// the sale and is still held at the end of it.
+const SUPERFICIAL_LOSS_DAYS: i64 = 30;
+/// Anything closer to zero than this is a rounding error, not shares.
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// A sale out of the pooled holdings of the non-registered accounts, the money values are in CAD.
+pub struct Disposition {
+    pub trade_date: NaiveDate,
+    pub settlement_date: NaiveDate,
+    pub account: AccountNumber,
+    pub symbol: PositionSymbol,
+    pub quantity: f64,
+    pub proceeds: f64,
+    /// The share of the pooled ACB that went out with the shares sold.
+    pub acb: f64,
+    /// Commission and fees of the sale.
+    pub outlays: f64,
+    /// Proceeds less the ACB and outlays, with any superficial loss added back in.
+    pub gain: f64,
+    /// How much of the loss was denied, it goes onto the ACB of the shares bought back.
+    pub superficial_loss: f64,
+    /// The sale or the shares it pooled with were converted at the configured rate.
+    pub configured_rate: bool,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// What is still held of one symbol across the non-registered accounts, in CAD.
+pub struct AcbPosition {
+    pub symbol: PositionSymbol,
+    pub quantity: f64,
+    pub acb: f64,
+    pub acb_per_share: f64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// Every sale settled in a tax year, with the totals for the year.
+pub struct CapitalGainsReport {
+    pub year: i32,
+    pub currency: Currency,
+    pub proceeds: f64,
+    pub acb: f64,
+    pub outlays: f64,
+    pub superficial_losses: f64,
+    pub gain: f64,
+    pub dispositions: Vec<Disposition>,
+    /// A line for every sale worked out with the configured rate instead of a recorded one.
+    pub warnings: Vec<String>,
+}
+
+// the running cost of one symbol. A denied loss with no shares left to go onto waits as
+// pending until the shares are bought back.
+#[derive(Default)]
+struct Pool {
+    quantity: f64,
+    acb: f64,
+    pending: f64,
+    // some of the cost was converted at the configured rate.
+    configured_rate: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// The cost pools and sales worked out from every trade.
+pub struct AcbLedger {
+    pub positions: Vec<AcbPosition>,
+    pub dispositions: Vec<Disposition>,
+    /// Symbols sold past zero, the trades that bought them aren't stored.
+    pub oversold: Vec<PositionSymbol>,
+}
+
+/// Helper functions
+// shares of the symbol bought within the window of a sale, all of them and just the
+// non-registered ones.
+fn bought_in_window(trades: &[TaxTrade], symbol: &str, date: NaiveDate) -> (f64, f64) {
+    let window = Duration::days(SUPERFICIAL_LOSS_DAYS);
+    trades
+        .iter()
+        .filter(|t| t.symbol == symbol && t.quantity > 0.0)
+        .filter(|t| t.trade_date >= date - window && t.trade_date <= date + window)
+        .fold((0.0, 0.0), |(all, taxable), t| {
+            let nonreg = if t.registered { 0.0 } else { t.quantity };
+            (all + t.quantity, taxable + nonreg)
+        })
+}
+// shares of the symbol held across every account once the window after a sale closes.
+fn held_after_window(trades: &[TaxTrade], symbol: &str, date: NaiveDate) -> f64 {
+    let end = date + Duration::days(SUPERFICIAL_LOSS_DAYS);
+    trades
+        .iter()
+        .filter(|t| t.symbol == symbol && t.trade_date <= end)
+        .fold(0.0, |total, t| total + t.quantity)
+}
+
+impl AcbLedger {
+    /// Walks the trades in order, pooling the cost of every symbol bought in a non-registered
+    /// account. Registered trades only count towards superficial losses.
+    pub fn build(trades: &[TaxTrade]) -> Self {
+        let mut pools: HashMap<PositionSymbol, Pool> = HashMap::new();
+        let mut dispositions: Vec<Disposition> = Vec::new();
+        let mut oversold: Vec<PositionSymbol> = Vec::new();
+        for trade in trades.iter().filter(|t| !t.registered) {
+            let pool = pools.entry(trade.symbol.clone()).or_default();
+            if trade.quantity > 0.0 {
+                pool.acb += trade.quantity * trade.price + trade.commission + pool.pending;
+                pool.quantity += trade.quantity;
+                pool.pending = 0.0;
+                pool.configured_rate |= trade.configured_rate;
+                continue;
+            }
+            let sold = -trade.quantity;
+            if sold > pool.quantity + QUANTITY_EPSILON && !oversold.contains(&trade.symbol) {
+                oversold.push(trade.symbol.clone());
+            }
+            // shares sold past what the pool holds have no cost we know of.
+            let acb = if pool.quantity > 0.0 {
+                pool.acb * (sold / pool.quantity).min(1.0)
+            } else {
+                0.0
+            };
+            let proceeds = sold * trade.price;
+            let mut gain = proceeds - acb - trade.commission;
+            let configured_rate = trade.configured_rate || pool.configured_rate;
+            pool.acb -= acb;
+            pool.quantity -= sold;
+            if pool.quantity < QUANTITY_EPSILON {
+                pool.quantity = 0.0;
+                pool.acb = 0.0;
+                pool.configured_rate = false;
+            }
+            // the loss is denied for as many shares as were sold, bought back, and still held.
+            let mut superficial_loss = 0.0;
+            if gain < 0.0 {
+                let (bought, bought_taxable) =
+                    bought_in_window(trades, &trade.symbol, trade.trade_date);
+                let held = held_after_window(trades, &trade.symbol, trade.trade_date);
+                let denied_quantity = sold.min(bought).min(held).max(0.0);
+                superficial_loss = -gain * denied_quantity / sold;
+                gain += superficial_loss;
+                // only shares bought back in a non-registered account have an ACB to add it to,
+                // the rest of the denied loss is gone for good.
+                let added = if bought > 0.0 {
+                    superficial_loss * (bought_taxable / bought)
+                } else {
+                    0.0
+                };
+                if pool.quantity > 0.0 {
+                    pool.acb += added;
+                } else {
+                    pool.pending += added;
+                }
+            }
+            dispositions.push(Disposition {
+                trade_date: trade.trade_date,
+                settlement_date: trade.settlement_date,
+                account: trade.account.clone(),
+                symbol: trade.symbol.clone(),
+                quantity: sold,
+                proceeds,
+                acb,
+                outlays: trade.commission,
+                gain,
+                superficial_loss,
+                configured_rate,
+            });
+        }
+        let mut positions: Vec<AcbPosition> = pools
+            .into_iter()
+            .filter(|(_, pool)| pool.quantity > 0.0)
+            .map(|(symbol, pool)| AcbPosition {
+                symbol,
+                quantity: pool.quantity,
+                acb: pool.acb,
+                acb_per_share: pool.acb / pool.quantity,
+            })
+            .collect();
+        positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Self {
+            positions,
+            dispositions,
+            oversold,
+        }
+    }
+    /// The sales that settled in a year, gains are taxed in the year they settle.
+    pub fn capital_gains(&self, year: i32) -> CapitalGainsReport {
+        let dispositions: Vec<Disposition> = self
+            .dispositions
+            .iter()
+            .filter(|d| d.settlement_date.year() == year)
+            .cloned()
+            .collect();
+        let total = |value: fn(&Disposition) -> f64| {
+            dispositions.iter().fold(0.0, |total, d| total + value(d))
+        };
+        let warnings = dispositions
+            .iter()
+            .filter(|d| d.configured_rate)
+            .map(|d| {
+                format!(
+                    "The sale of {} {} on {} from {} uses the configured USD/CAD rate for trades older than the recorded ones.",
+                    d.quantity, d.symbol, d.trade_date, d.account
+                )
+            })
+            .collect();
+        CapitalGainsReport {
+            year,
+            currency: Currency::CAD,
+            proceeds: total(|d| d.proceeds),
+            acb: total(|d| d.acb),
+            outlays: total(|d| d.outlays),
+            superficial_losses: total(|d| d.superficial_loss),
+            gain: total(|d| d.gain),
+            dispositions,
+            warnings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(day: u32, registered: bool, quantity: f64, price: f64, commission: f64) -> TaxTrade {
+        let date = NaiveDate::from_ymd(2026, 3, 1) + Duration::days(i64::from(day));
+        TaxTrade {
+            trade_date: date,
+            settlement_date: date + Duration::days(1),
+            account: if registered { "tfsa" } else { "cash" }.to_string(),
+            registered,
+            symbol: "XEQT.TO".to_string(),
+            quantity,
+            price,
+            commission,
+            configured_rate: false,
+        }
+    }
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn plain_gain() {
+        let ledger = AcbLedger::build(&[
+            trade(0, false, 10.0, 10.0, 5.0),
+            trade(5, false, -4.0, 20.0, 5.0),
+        ]);
+        let sale = &ledger.dispositions[0];
+        assert!(close(sale.proceeds, 80.0));
+        assert!(close(sale.acb, 42.0));
+        assert!(close(sale.gain, 33.0));
+        assert!(close(sale.superficial_loss, 0.0));
+        let held = &ledger.positions[0];
+        assert!(close(held.quantity, 6.0));
+        assert!(close(held.acb, 63.0));
+        assert!(ledger.oversold.is_empty());
+    }
+
+    #[test]
+    fn loss_bought_back_in_a_registered_account() {
+        let trades = [
+            trade(0, false, 10.0, 10.0, 0.0),
+            trade(5, false, -10.0, 5.0, 0.0),
+            trade(10, true, 10.0, 6.0, 0.0),
+        ];
+        let ledger = AcbLedger::build(&trades);
+        let sale = &ledger.dispositions[0];
+        // the whole loss is denied, but there are no taxable shares to carry it.
+        assert!(close(sale.superficial_loss, 50.0));
+        assert!(close(sale.gain, 0.0));
+        assert!(ledger.positions.is_empty());
+    }
+
+    #[test]
+    fn loss_bought_back_in_a_non_registered_account() {
+        let trades = [
+            trade(0, false, 10.0, 10.0, 0.0),
+            trade(5, false, -10.0, 5.0, 0.0),
+            trade(10, false, 10.0, 6.0, 0.0),
+        ];
+        let ledger = AcbLedger::build(&trades);
+        let sale = &ledger.dispositions[0];
+        assert!(close(sale.superficial_loss, 50.0));
+        assert!(close(sale.gain, 0.0));
+        // the denied loss waited as pending until the buy, then went onto its ACB.
+        let held = &ledger.positions[0];
+        assert!(close(held.quantity, 10.0));
+        assert!(close(held.acb, 110.0));
+    }
+
+    #[test]
+    fn partial_denial() {
+        let trades = [
+            trade(0, false, 10.0, 10.0, 0.0),
+            trade(5, false, -10.0, 5.0, 0.0),
+            trade(6, false, 4.0, 6.0, 0.0),
+        ];
+        let ledger = AcbLedger::build(&trades);
+        let sale = &ledger.dispositions[0];
+        assert!(close(sale.superficial_loss, 20.0));
+        assert!(close(sale.gain, -30.0));
+        let held = &ledger.positions[0];
+        assert!(close(held.acb, 44.0));
+    }
+
+    #[test]
+    fn pending_loss_carried_to_a_later_buy() {
+        let trades = [
+            trade(0, false, 10.0, 10.0, 0.0),
+            trade(5, false, -10.0, 5.0, 0.0),
+            trade(30, false, 5.0, 6.0, 0.0),
+            trade(40, false, 5.0, 7.0, 0.0),
+        ];
+        let ledger = AcbLedger::build(&trades);
+        // only the 5 shares bought inside the window count, and all of the pending loss goes onto
+        // the first buy after the sale.
+        assert!(close(ledger.dispositions[0].superficial_loss, 25.0));
+        let held = &ledger.positions[0];
+        assert!(close(held.quantity, 10.0));
+        assert!(close(held.acb, 30.0 + 25.0 + 35.0));
+    }
+
+    #[test]
+    fn no_denial_once_sold_again_in_the_window() {
+        let trades = [
+            trade(0, false, 10.0, 10.0, 0.0),
+            trade(5, false, -10.0, 5.0, 0.0),
+            trade(10, false, 10.0, 6.0, 0.0),
+            trade(20, false, -10.0, 6.0, 0.0),
+        ];
+        let ledger = AcbLedger::build(&trades);
+        assert!(close(ledger.dispositions[0].superficial_loss, 0.0));
+        assert!(close(ledger.dispositions[0].gain, -50.0));
+    }
+
+    #[test]
+    fn oversell() {
+        let ledger = AcbLedger::build(&[
+            trade(0, false, 5.0, 10.0, 0.0),
+            trade(5, false, -8.0, 20.0, 0.0),
+        ]);
+        assert_eq!(ledger.oversold, vec!["XEQT.TO".to_string()]);
+        let sale = &ledger.dispositions[0];
+        // only the shares we know the cost of bring any ACB with them.
+        assert!(close(sale.acb, 50.0));
+        assert!(close(sale.gain, 110.0));
+        assert!(ledger.positions.is_empty());
+    }
+
+    #[test]
+    fn configured_rate_follows_the_pool() {
+        let mut old_buy = trade(0, false, 10.0, 10.0, 0.0);
+        old_buy.configured_rate = true;
+        let trades = [
+            old_buy,
+            trade(5, false, -10.0, 20.0, 0.0),
+            trade(50, false, 10.0, 10.0, 0.0),
+            trade(55, false, -10.0, 20.0, 0.0),
+        ];
+        let ledger = AcbLedger::build(&trades);
+        // the pool was emptied by the first sale, so the second one has nothing from the config.
+        assert!(ledger.dispositions[0].configured_rate);
+        assert!(!ledger.dispositions[1].configured_rate);
+        let report = ledger.capital_gains(2026);
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].contains("2026-03-06"));
+    }
+}
@@ -0,0 +1,221 @@
+//! Module to track the adjusted cost base of what is held in taxable accounts.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::{
+    fx::FxConverter,
+    include::{
+        warn, AccountNumber, AccountType, Currency, HashSet, NaiveDate, NaiveDateTime, OrderSide,
+        PositionSymbol, Result, Serialize,
+    },
+    portfolio::listing_currency,
+    storage::{ActivityType, DBInfoAccountActivity, DBInfoAccountExecution, Storage},
+};
+
+/// Sub modules
+mod errors;
+mod ledger;
+
+/// Re-export sub-modules so we can read from them in other modules.
+pub use errors::*;
+pub use ledger::*;
+
+/// Helper functions
+// Questrade opened in 1999, so there are no trades to find before then.
+fn ledger_start() -> NaiveDate {
+    NaiveDate::from_ymd(1999, 1, 1)
+}
+// the converter for a trade and whether it is the fallback. Trades older than every recorded
+// rate get the one from the config, since the first rate recorded could be years off.
+fn trade_converter<'a>(
+    cad: &'a FxConverter,
+    fallback: &'a FxConverter,
+    currency: &Currency,
+    at: NaiveDateTime,
+) -> (&'a FxConverter, bool) {
+    match currency {
+        Currency::CAD => (cad, false),
+        _ if cad.covers(at) => (cad, false),
+        _ => (fallback, true),
+    }
+}
+/// Only cash and margin accounts are taxed on their gains, everything else is registered.
+pub fn is_registered(account_type: &AccountType) -> bool {
+    !matches!(account_type, AccountType::Cash | AccountType::Margin)
+}
+
+/// Reads the tax year from the api path.
+pub fn parse_year(year: &str) -> Result<i32> {
+    match year.parse::<i32>() {
+        Ok(y) if (1000..=9999).contains(&y) => Ok(y),
+        _ => Err(Box::new(TaxError::InvalidYearError(year.to_string()))),
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+/// A buy or sell from any account, in CAD.
+pub struct TaxTrade {
+    pub trade_date: NaiveDate,
+    /// Gains land in the tax year the trade settles in.
+    pub settlement_date: NaiveDate,
+    pub account: AccountNumber,
+    /// Trades in registered accounts only matter for superficial losses.
+    pub registered: bool,
+    pub symbol: PositionSymbol,
+    /// Positive for a buy and negative for a sell.
+    pub quantity: f64,
+    pub price: f64,
+    /// Commission and fees, always positive.
+    pub commission: f64,
+    /// Converted at the usd_cad_rate of the config, the trade is older than the recorded rates.
+    pub configured_rate: bool,
+}
+
+impl TaxTrade {
+    fn from_activity(
+        account: &str,
+        registered: bool,
+        activity: &DBInfoAccountActivity,
+        cad: &FxConverter,
+        fallback: &FxConverter,
+    ) -> Self {
+        let quantity = match activity.action.to_lowercase().as_str() {
+            "buy" => activity.quantity.abs(),
+            "sell" => -activity.quantity.abs(),
+            // reinvested dividends and anything else go by the sign Questrade sent.
+            _ => activity.quantity,
+        };
+        let at = activity.trade_date.and_hms(23, 59, 59);
+        let (cad, configured_rate) = trade_converter(cad, fallback, &activity.currency, at);
+        let convert = |amount: f64| cad.convert(amount, &activity.currency, at);
+        Self {
+            trade_date: activity.trade_date,
+            settlement_date: activity.settlement_date,
+            account: account.to_string(),
+            registered,
+            symbol: activity.symbol.clone(),
+            quantity,
+            price: convert(activity.price),
+            commission: convert(activity.commission.abs()),
+            configured_rate,
+        }
+    }
+    // executions show up before the activity does, so they fill in the last few days. They
+    // have no settlement date, so the trade date stands in for it.
+    fn from_execution(
+        db: &dyn Storage,
+        account: &str,
+        registered: bool,
+        execution: &DBInfoAccountExecution,
+        cad: &FxConverter,
+        fallback: &FxConverter,
+    ) -> Self {
+        let date = execution.timestamp.naive_local().date();
+        let quantity = match execution.side {
+            OrderSide::Sell | OrderSide::Short | OrderSide::SellToClose | OrderSide::SellToOpen => {
+                -execution.quantity.abs()
+            }
+            _ => execution.quantity.abs(),
+        };
+        let currency = listing_currency(db, &execution.symbol);
+        let at = execution.timestamp.naive_local();
+        let (cad, configured_rate) = trade_converter(cad, fallback, &currency, at);
+        let convert = |amount: f64| cad.convert(amount, &currency, at);
+        Self {
+            trade_date: date,
+            settlement_date: date,
+            account: account.to_string(),
+            registered,
+            symbol: execution.symbol.clone(),
+            quantity,
+            price: convert(execution.price),
+            commission: convert(
+                execution.commission.abs()
+                    + execution.execution_fee.abs()
+                    + execution.sec_fee.abs()
+                    + execution.canadian_execution_fee.abs(),
+            ),
+            configured_rate,
+        }
+    }
+}
+
+/// Every trade stored for every account, earliest first with buys ahead of sells on the same
+/// day. Trades come from the activities, with executions filling in the days the activities
+/// haven't caught up to yet.
+pub fn tax_trades(db: &dyn Storage, usd_cad_rate: f64, today: NaiveDate) -> Result<Vec<TaxTrade>> {
+    // the ACB has to be in CAD, so USD trades are converted at the rate recorded closest
+    // before them, or the one from the config if no rates had been recorded yet.
+    let cad = FxConverter::load_or_fixed(db, ledger_start(), today, Currency::CAD, usd_cad_rate);
+    let fallback = FxConverter::fixed(Currency::CAD, usd_cad_rate);
+    let mut trades: Vec<TaxTrade> = Vec::new();
+    let mut taxable = false;
+    for number in db.list_account_numbers()? {
+        let registered = is_registered(&db.get_account_info(&number)?.account_type);
+        taxable |= !registered;
+        // an account that never traded has nothing synced, which is fine.
+        let activities: Vec<DBInfoAccountActivity> = db
+            .get_activities(&number, ledger_start(), today, None)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|a| {
+                matches!(
+                    a.activity_type,
+                    ActivityType::Trades | ActivityType::DividendReinvestment
+                ) && !a.symbol.is_empty()
+                    && a.quantity != 0.0
+            })
+            .collect();
+        let mut covered: HashSet<(PositionSymbol, NaiveDate)> = HashSet::new();
+        for activity in activities.iter() {
+            covered.insert((activity.symbol.clone(), activity.trade_date));
+            trades.push(TaxTrade::from_activity(
+                &number, registered, activity, &cad, &fallback,
+            ));
+        }
+        for execution in db
+            .get_executions(&number, ledger_start(), today)
+            .unwrap_or_default()
+            .iter()
+        {
+            let key = (
+                execution.symbol.clone(),
+                execution.timestamp.naive_local().date(),
+            );
+            if !covered.contains(&key) {
+                trades.push(TaxTrade::from_execution(
+                    db, &number, registered, execution, &cad, &fallback,
+                ));
+            }
+        }
+    }
+    if !taxable {
+        return Err(Box::new(TaxError::NoTaxableAccountsError));
+    }
+    trades.sort_by_key(|t| (t.trade_date, t.quantity < 0.0));
+    Ok(trades)
+}
+
+/// Builds the ledger from everything stored, see tax_trades.
+pub fn build_ledger(db: &dyn Storage, usd_cad_rate: f64, today: NaiveDate) -> Result<AcbLedger> {
+    let trades = tax_trades(db, usd_cad_rate, today)?;
+    let ledger = AcbLedger::build(&trades);
+    for symbol in ledger.oversold.iter() {
+        warn!(
+            "Sold more {} than the trades stored ever bought, its gains are missing some cost.",
+            symbol
+        );
+    }
+    let estimated = ledger
+        .dispositions
+        .iter()
+        .filter(|d| d.configured_rate)
+        .count();
+    if estimated > 0 {
+        warn!(
+            "{} sales use USD trades older than the recorded rates, converted at the configured rate.",
+            estimated
+        );
+    }
+    Ok(ledger)
+}
@@ -0,0 +1,21 @@
+//! Module for the symbols we want quotes of without holding them.
+//! By: Curtis Jones <mail@curtisjones.ca>
+//! Started on: October 17, 2026
+
+use super::include::{Deserialize, PositionSymbol, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+/// The watchlist section of the config file.
+pub struct WatchlistConfig {
+    /// Symbols as Questrade lists them, like XEQT.TO or AAPL.
+    pub symbols: Vec<PositionSymbol>,
+}
+
+impl Default for WatchlistConfig {
+    fn default() -> Self {
+        Self {
+            symbols: Vec::new(),
+        }
+    }
+}